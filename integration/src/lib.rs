@@ -0,0 +1,3 @@
+//! Integration tests of `register-actix-routes`, kept out of the workspace so
+//! the macro crate builds without `actix-web`. Run with `cargo test` from this
+//! directory.
@@ -0,0 +1,29 @@
+use actix_web::guard::{self, Guard};
+use actix_web::{get, test, App, Responder};
+use register_actix_routes::{auto_register, generate_register_service};
+
+fn admin_guard() -> impl Guard {
+    guard::Header("x-role", "admin")
+}
+
+#[auto_register("/admin")]
+#[get("/users")]
+pub async fn users() -> impl Responder {
+    "Users"
+}
+
+generate_register_service!(["/admin", use_scope = true, admin = admin_guard()]);
+
+#[actix_web::test]
+async fn answers_404_to_requests_the_admin_guard_turns_down() {
+    let app = test::init_service(App::new().configure(register_service)).await;
+
+    for (role, expected) in [(None, 404), (Some("editor"), 404), (Some("admin"), 200)] {
+        let mut request = test::TestRequest::get().uri("/admin/users");
+        if let Some(role) = role {
+            request = request.insert_header(("x-role", role));
+        }
+        let response = test::call_service(&app, request.to_request()).await;
+        assert_eq!(response.status().as_u16(), expected, "{:?}", role);
+    }
+}
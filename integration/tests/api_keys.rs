@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use actix_web::{get, test, web, App, Responder};
+use register_actix_routes::{auto_register, generate_register_service};
+use register_actix_routes_runtime::ApiKeyStore;
+
+struct Keys;
+
+impl ApiKeyStore for Keys {
+    fn is_valid(&self, key: &str) -> bool {
+        key == "secret"
+    }
+}
+
+#[auto_register("/reports", api_key = "x-api-key")]
+#[get("/export")]
+pub async fn export() -> impl Responder {
+    "Report"
+}
+
+generate_register_service!(["/reports", use_scope = true]);
+
+#[actix_web::test]
+async fn answers_401_without_a_valid_api_key() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::from(Arc::new(Keys) as Arc<dyn ApiKeyStore>))
+            .configure(register_service),
+    )
+    .await;
+
+    for (key, expected) in [(None, 401), (Some("guess"), 401), (Some("secret"), 200)] {
+        let mut request = test::TestRequest::get().uri("/reports/export");
+        if let Some(key) = key {
+            request = request.insert_header(("x-api-key", key));
+        }
+        let response = test::call_service(&app, request.to_request()).await;
+        assert_eq!(response.status().as_u16(), expected, "{:?}", key);
+    }
+}
+
+#[actix_web::test]
+async fn answers_401_without_a_store() {
+    let app = test::init_service(App::new().configure(register_service)).await;
+
+    let request = test::TestRequest::get()
+        .uri("/reports/export")
+        .insert_header(("x-api-key", "secret"))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, request).await.status().as_u16(),
+        401
+    );
+}
@@ -0,0 +1,77 @@
+use std::sync::{Arc, Mutex};
+
+use actix_web::{delete, get, test, web, App, HttpRequest, Responder};
+use register_actix_routes::{auto_register, generate_register_service};
+use register_actix_routes_runtime::AuditSink;
+
+#[auto_register("/accounts", audit = true)]
+#[delete("/{id}")]
+pub async fn close_account() -> impl Responder {
+    "Closed"
+}
+
+#[auto_register("/accounts")]
+#[get("/{id}")]
+pub async fn account() -> impl Responder {
+    "Account"
+}
+
+generate_register_service!(["/accounts", use_scope = true]);
+
+#[derive(Debug, PartialEq)]
+struct Entry {
+    method: String,
+    path: String,
+    caller: Option<String>,
+    status: u16,
+}
+
+#[derive(Default)]
+struct Entries(Mutex<Vec<Entry>>);
+
+impl AuditSink<HttpRequest> for Entries {
+    fn caller(&self, req: &HttpRequest) -> Option<String> {
+        let caller = req.headers().get("x-caller")?.to_str().ok()?;
+        Some(caller.to_string())
+    }
+
+    fn record(&self, method: &str, path: &str, caller: Option<&str>, status: u16) {
+        self.0.lock().unwrap().push(Entry {
+            method: method.to_string(),
+            path: path.to_string(),
+            caller: caller.map(str::to_string),
+            status,
+        });
+    }
+}
+
+#[actix_web::test]
+async fn reports_audited_requests_to_the_sink() {
+    let entries = Arc::new(Entries::default());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::from(
+                entries.clone() as Arc<dyn AuditSink<HttpRequest>>
+            ))
+            .configure(register_service),
+    )
+    .await;
+
+    let request = test::TestRequest::delete()
+        .uri("/accounts/7")
+        .insert_header(("x-caller", "ops"))
+        .to_request();
+    test::call_service(&app, request).await;
+    let request = test::TestRequest::get().uri("/accounts/7").to_request();
+    test::call_service(&app, request).await;
+
+    assert_eq!(
+        *entries.0.lock().unwrap(),
+        [Entry {
+            method: "DELETE".to_string(),
+            path: "/accounts/{id}".to_string(),
+            caller: Some("ops".to_string()),
+            status: 200,
+        }]
+    );
+}
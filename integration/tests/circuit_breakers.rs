@@ -0,0 +1,36 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use actix_web::{get, test, App, HttpResponse, Responder};
+use register_actix_routes::{auto_register, generate_register_service};
+
+// Number of requests that reached `charge`
+static CHARGES: AtomicU32 = AtomicU32::new(0);
+
+#[auto_register("/payments", circuit_breaker(threshold = 2, cooldown = "1m"))]
+#[get("/charge")]
+pub async fn charge() -> impl Responder {
+    CHARGES.fetch_add(1, Ordering::Relaxed);
+    HttpResponse::BadGateway().finish()
+}
+
+generate_register_service!(["/payments", use_scope = true]);
+
+#[actix_web::test]
+async fn opens_the_breaker_after_consecutive_failures() {
+    let app = test::init_service(App::new().configure(register_service)).await;
+
+    let mut statuses = Vec::new();
+    for _ in 0..3 {
+        let request = test::TestRequest::get()
+            .uri("/payments/charge")
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        statuses.push(response.status().as_u16());
+        if response.status().as_u16() == 503 {
+            assert_eq!(response.headers().get("Retry-After").unwrap(), "60");
+        }
+    }
+
+    assert_eq!(statuses, [502, 502, 503]);
+    assert_eq!(CHARGES.load(Ordering::Relaxed), 2);
+}
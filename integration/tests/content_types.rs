@@ -0,0 +1,29 @@
+use actix_web::{post, test, App, Responder};
+use register_actix_routes::{auto_register, generate_register_service};
+
+#[auto_register("/events", consumes = "application/json")]
+#[post("/import")]
+pub async fn import() -> impl Responder {
+    "Imported"
+}
+
+generate_register_service!(["/events", use_scope = true]);
+
+#[actix_web::test]
+async fn answers_415_to_other_media_types() {
+    let app = test::init_service(App::new().configure(register_service)).await;
+
+    for (content_type, expected) in [
+        (None, 415),
+        (Some("text/plain"), 415),
+        (Some("application/json"), 200),
+        (Some("application/json; charset=utf-8"), 200),
+    ] {
+        let mut request = test::TestRequest::post().uri("/events/import");
+        if let Some(content_type) = content_type {
+            request = request.insert_header(("Content-Type", content_type));
+        }
+        let response = test::call_service(&app, request.to_request()).await;
+        assert_eq!(response.status().as_u16(), expected, "{:?}", content_type);
+    }
+}
@@ -0,0 +1,75 @@
+// Renamed, as `actix_web::test` would shadow the `#[test]` attribute
+use actix_web::test as actix_test;
+use actix_web::{get, web, App, Responder};
+use register_actix_routes::{auto_register, generate_fuzz_targets, generate_register_service};
+
+pub struct EventsController {
+    name: &'static str,
+}
+
+#[auto_register("/events")]
+impl EventsController {
+    #[get("/name")]
+    pub async fn name(&self) -> impl Responder {
+        self.name
+    }
+
+    #[cfg(test)]
+    #[get("/enabled")]
+    pub async fn enabled(&self) -> impl Responder {
+        "Enabled"
+    }
+
+    #[cfg(not(test))]
+    #[get("/disabled")]
+    pub async fn disabled(&self) -> impl Responder {
+        "Disabled"
+    }
+}
+
+generate_register_service!(["/events", use_scope = true]);
+
+generate_fuzz_targets!();
+
+#[actix_web::test]
+async fn serves_the_methods_of_the_controller() {
+    let app = actix_test::init_service(
+        App::new()
+            .app_data(web::Data::new(EventsController { name: "Events" }))
+            .configure(register_service),
+    )
+    .await;
+
+    let request = actix_test::TestRequest::get()
+        .uri("/events/name")
+        .to_request();
+    assert_eq!(
+        actix_test::call_and_read_body(&app, request).await,
+        "Events"
+    );
+    let request = actix_test::TestRequest::get()
+        .uri("/events/enabled")
+        .to_request();
+    assert_eq!(
+        actix_test::call_and_read_body(&app, request).await,
+        "Enabled"
+    );
+    let request = actix_test::TestRequest::get()
+        .uri("/events/disabled")
+        .to_request();
+    assert_eq!(
+        actix_test::call_service(&app, request)
+            .await
+            .status()
+            .as_u16(),
+        404
+    );
+}
+
+#[test]
+fn fuzz_targets_skip_the_methods_compiled_out() {
+    assert_eq!(
+        fuzz_targets::ROUTES,
+        ["GET /events/enabled", "GET /events/name"]
+    );
+}
@@ -0,0 +1,81 @@
+use actix_web::{post, web, App, HttpRequest, Responder};
+// Renamed, as `actix_web::test` would shadow the `#[test]` attribute
+use actix_web::test as actix_test;
+use register_actix_routes::{auto_register, generate_csrf_exemptions, generate_register_service};
+
+// Whether the CSRF protection would let the request through, the services being
+// configured under `/api`
+fn exempt(req: &HttpRequest) -> String {
+    req.match_pattern()
+        .and_then(|pattern| {
+            let pattern = pattern.strip_prefix("/api")?;
+            Some(is_csrf_exempt(req.method().as_str(), pattern))
+        })
+        .unwrap_or(false)
+        .to_string()
+}
+
+mod webhooks {
+    use super::*;
+
+    #[auto_register("/webhooks", csrf_exempt = true)]
+    #[post("/stripe")]
+    pub async fn stripe(req: HttpRequest) -> impl Responder {
+        exempt(&req)
+    }
+
+    generate_register_service!(["/webhooks", use_scope = true]);
+}
+
+mod events {
+    use super::*;
+
+    #[auto_register("/events", csrf_exempt = true)]
+    #[post("/hook")]
+    pub async fn hook(req: HttpRequest) -> impl Responder {
+        exempt(&req)
+    }
+
+    #[auto_register("/events")]
+    #[post("/admin/hook")]
+    pub async fn admin_hook(req: HttpRequest) -> impl Responder {
+        exempt(&req)
+    }
+
+    generate_register_service!(["/events"]);
+}
+
+generate_csrf_exemptions!();
+
+async fn body(uri: &str) -> String {
+    let app = actix_test::init_service(
+        App::new().service(
+            web::scope("/api")
+                .configure(webhooks::register_service)
+                .configure(events::register_service),
+        ),
+    )
+    .await;
+    let request = actix_test::TestRequest::post().uri(uri).to_request();
+    let response = actix_test::call_service(&app, request).await;
+    String::from_utf8(actix_test::read_body(response).await.to_vec()).unwrap()
+}
+
+#[actix_web::test]
+async fn exempts_routes_of_services_nested_under_outer_scopes() {
+    assert_eq!(body("/api/webhooks/stripe").await, "true");
+    assert_eq!(body("/api/hook").await, "true");
+}
+
+#[actix_web::test]
+async fn keeps_protecting_routes_ending_with_an_exempt_pattern() {
+    assert_eq!(body("/api/admin/hook").await, "false");
+}
+
+#[test]
+fn lists_exemptions_relative_to_the_service() {
+    assert_eq!(
+        CSRF_EXEMPT_ROUTES,
+        &[("POST", "/hook"), ("POST", "/webhooks/stripe")]
+    );
+}
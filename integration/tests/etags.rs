@@ -0,0 +1,53 @@
+use actix_web::{get, test, App, Responder};
+use register_actix_routes::{auto_register, generate_register_service};
+
+#[auto_register("/payments", etag = true)]
+#[get("/receipt")]
+pub async fn receipt() -> impl Responder {
+    "Receipt"
+}
+
+generate_register_service!(["/payments", use_scope = true]);
+
+#[actix_web::test]
+async fn tags_a_body_with_its_stable_hash() {
+    let app = test::init_service(App::new().configure(register_service)).await;
+
+    let request = test::TestRequest::get()
+        .uri("/payments/receipt")
+        .to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(
+        response.headers().get("ETag").unwrap(),
+        "\"6c2e396048d50863\""
+    );
+}
+
+#[actix_web::test]
+async fn answers_304_to_a_matching_etag() {
+    let app = test::init_service(App::new().configure(register_service)).await;
+
+    let request = test::TestRequest::get()
+        .uri("/payments/receipt")
+        .to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let etag = response.headers().get("ETag").unwrap().clone();
+    assert_eq!(test::read_body(response).await, "Receipt");
+
+    let tag = etag.to_str().unwrap();
+    for (if_none_match, expected) in [
+        (tag.to_string(), 304),
+        (format!("W/{}", tag), 304),
+        ("*".to_string(), 304),
+        ("\"other\"".to_string(), 200),
+    ] {
+        let request = test::TestRequest::get()
+            .uri("/payments/receipt")
+            .insert_header(("If-None-Match", if_none_match.as_str()))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status().as_u16(), expected, "{}", if_none_match);
+        assert_eq!(response.headers().get("ETag"), Some(&etag));
+    }
+}
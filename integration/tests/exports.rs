@@ -0,0 +1,181 @@
+use actix_web::{get, post, web, Responder};
+use register_actix_routes::{
+    auto_register, generate_asyncapi, generate_har, generate_insomnia, generate_k6_script,
+    generate_openapi, generate_pact, generate_register_service, generate_routes_dot,
+    generate_routes_markdown, generate_routes_mermaid,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct NewEvent {
+    pub name: String,
+}
+
+/// Finds an event.
+#[auto_register("/events", errors = [404], example_response = r#"{"id": 1}"#)]
+#[get("/{id}")]
+pub async fn detail(id: web::Path<u32>) -> impl Responder {
+    id.to_string()
+}
+
+/// Creates an event.
+#[auto_register("/events", example_request = r#"{"name": "Meetup"}"#)]
+#[post("/")]
+pub async fn create(event: web::Json<NewEvent>) -> impl Responder {
+    event.into_inner().name
+}
+
+/// Streams the events as they are created.
+#[auto_register("/events", sse = true, example_response = "data: 1")]
+#[get("/stream")]
+pub async fn stream() -> impl Responder {
+    "data: 1"
+}
+
+generate_register_service!(["/events", use_scope = true]);
+
+generate_routes_mermaid!();
+generate_routes_dot!();
+generate_routes_markdown!();
+generate_openapi!(version = "3.0", title = "Events");
+generate_asyncapi!(title = "Events");
+generate_pact!(consumer = "web", provider = "events-api");
+generate_k6_script!(
+    base_url = "https://staging.example.com",
+    rate = 50,
+    duration = "5m"
+);
+generate_insomnia!(
+    name = "Events API",
+    base_url = "https://staging.example.com"
+);
+generate_har!(base_url = "https://staging.example.com");
+
+#[test]
+fn mermaid_draws_a_node_per_scope_and_route() {
+    let diagram = routes_mermaid();
+
+    assert!(diagram.starts_with("flowchart LR\n"));
+    assert!(diagram.contains(r#"scope_0["/events"]"#));
+    assert!(diagram.contains(r#"scope_0_route_0["GET /{id}<br/>detail"]"#));
+    assert!(diagram.contains("scope_0 --> scope_0_route_1"));
+}
+
+#[test]
+fn dot_clusters_the_routes_of_a_scope_colored_by_verb() {
+    let graph = routes_dot();
+
+    assert!(graph.starts_with("digraph routes {"));
+    assert!(graph.contains(r#"label="/events";"#));
+    assert!(graph.contains(r##"scope_0_route_1 [label="POST /\ncreate", fillcolor="#49cc90"];"##));
+}
+
+#[test]
+fn markdown_documents_each_resource_with_its_docs() {
+    let document = routes_markdown();
+
+    assert!(document.starts_with("# API Reference\n\n## `/events`\n"));
+    assert!(
+        document.contains("| GET | `detail` | Finds an event. |  |  | 404 | `tests/exports.rs:")
+    );
+    assert!(document.contains("| POST | `create` | Creates an event. |  | `NewEvent` |"));
+    assert!(document.contains("```json\n{\"id\": 1}\n```"));
+}
+
+#[test]
+fn openapi_describes_the_operations_in_the_requested_version() {
+    let document: Value = serde_json::from_str(&openapi()).unwrap();
+
+    assert_eq!(document["openapi"], "3.0.3");
+    assert_eq!(document["info"]["title"], "Events");
+    let operation = &document["paths"]["/events/{id}"]["get"];
+    assert_eq!(operation["operationId"], "detail");
+    assert_eq!(operation["summary"], "Finds an event.");
+    assert_eq!(operation["parameters"][0]["name"], "id");
+    assert_eq!(operation["responses"]["404"]["description"], "Not Found");
+    let body = &document["paths"]["/events/"]["post"]["requestBody"]["content"]["application/json"];
+    assert_eq!(body["schema"]["title"], "NewEvent");
+    assert_eq!(body["example"], json!({ "name": "Meetup" }));
+}
+
+#[test]
+fn asyncapi_lists_the_streaming_routes_alone() {
+    let document: Value = serde_json::from_str(&asyncapi()).unwrap();
+
+    assert_eq!(document["asyncapi"], "2.6.0");
+    let channels = document["channels"].as_object().unwrap();
+    assert_eq!(channels.keys().collect::<Vec<_>>(), ["/events/stream"]);
+    let message = &channels["/events/stream"]["subscribe"]["message"];
+    assert_eq!(message["contentType"], "text/event-stream");
+    assert_eq!(message["examples"][0]["payload"], "data: 1");
+}
+
+#[test]
+fn pact_adds_an_interaction_per_route_and_error() {
+    let contract: Value = serde_json::from_str(&pact_contract()).unwrap();
+
+    assert_eq!(contract["consumer"]["name"], "web");
+    assert_eq!(contract["provider"]["name"], "events-api");
+    let interactions = contract["interactions"].as_array().unwrap();
+    assert_eq!(interactions.len(), 4);
+    assert_eq!(interactions[0]["request"]["path"], "/events/1");
+    assert_eq!(
+        interactions[0]["request"]["matchingRules"]["$.path"]["regex"],
+        "^/events/[^/]+$"
+    );
+    assert_eq!(interactions[0]["response"]["body"], json!({ "id": 1 }));
+    assert_eq!(interactions[1]["providerState"], "detail answers 404");
+    assert_eq!(interactions[1]["response"]["status"], 404);
+}
+
+#[test]
+fn k6_requests_the_routes_of_each_scope_but_streams() {
+    let script = k6_script();
+
+    assert!(script.contains(r#"const BASE_URL = __ENV.BASE_URL || "https://staging.example.com";"#));
+    assert!(script.contains("const RATE = Number(__ENV.RATE || 50);"));
+    assert!(script.contains(r#"["GET", "/events/1", "GET /events/{id}"],"#));
+    assert!(script.contains(
+        r#"["POST", "/events/", "POST /events/", "{\"name\": \"Meetup\"}", "application/json"],"#
+    ));
+    assert!(!script.contains("/events/stream"));
+}
+
+#[test]
+fn insomnia_groups_the_requests_of_a_scope_in_a_folder() {
+    let export: Value = serde_json::from_str(&insomnia_export()).unwrap();
+
+    assert_eq!(export["__export_format"], 4);
+    let resources = export["resources"].as_array().unwrap();
+    assert_eq!(resources[0]["name"], "Events API");
+    assert_eq!(
+        resources[1]["data"]["base_url"],
+        "https://staging.example.com"
+    );
+    assert_eq!(resources[2]["_type"], "request_group");
+    assert_eq!(resources[2]["name"], "/events");
+    assert_eq!(resources[3]["url"], "{{ _.base_url }}/events/{{ _.id }}");
+    assert_eq!(resources[3]["parentId"], resources[2]["_id"]);
+    assert_eq!(resources[4]["body"]["text"], r#"{"name": "Meetup"}"#);
+}
+
+#[test]
+fn har_adds_an_entry_per_route_with_its_examples() {
+    let har: Value = serde_json::from_str(&har_template()).unwrap();
+
+    assert_eq!(har["log"]["version"], "1.2");
+    let entries = har["log"]["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 3);
+    assert_eq!(
+        entries[0]["request"]["url"],
+        "https://staging.example.com/events/{id}"
+    );
+    assert_eq!(entries[0]["response"]["content"]["text"], r#"{"id": 1}"#);
+    assert_eq!(entries[0]["comment"], "GET /events/{id} (detail)");
+    assert_eq!(
+        entries[1]["request"]["postData"]["text"],
+        r#"{"name": "Meetup"}"#
+    );
+}
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use actix_web::{get, test, web, App, Responder};
+use register_actix_routes::{auto_register, generate_register_service};
+use register_actix_routes_runtime::FeatureFlagProvider;
+
+struct Flags(&'static [&'static str]);
+
+impl FeatureFlagProvider for Flags {
+    fn is_enabled(&self, flag: &str) -> bool {
+        self.0.contains(&flag)
+    }
+}
+
+#[auto_register("/events", flag = "new-search")]
+#[get("/search")]
+pub async fn search() -> impl Responder {
+    "Search"
+}
+
+generate_register_service!(["/events", use_scope = true]);
+
+#[actix_web::test]
+async fn hides_flagged_routes_while_their_flag_is_off() {
+    for (enabled, expected) in [
+        (None, 404),
+        (Some(&[][..]), 404),
+        (Some(&["new-search"][..]), 200),
+    ] {
+        let mut app = App::new();
+        if let Some(enabled) = enabled {
+            app = app.app_data(web::Data::from(
+                Arc::new(Flags(enabled)) as Arc<dyn FeatureFlagProvider>
+            ));
+        }
+        let app = test::init_service(app.configure(register_service)).await;
+
+        let request = test::TestRequest::get().uri("/events/search").to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status().as_u16(), expected, "{:?}", enabled);
+    }
+}
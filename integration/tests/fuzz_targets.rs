@@ -0,0 +1,62 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use actix_web::{get, web, Responder};
+use register_actix_routes::{auto_register, generate_fuzz_targets, generate_register_service};
+
+static ROOT_CALLS: AtomicUsize = AtomicUsize::new(0);
+static TENANT_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+// The targets reference the handlers from the crate root, so only the generators
+// are in modules
+
+// Served at the root, as the key isn't a scope
+#[auto_register("/root")]
+#[get("/items/{id}")]
+pub async fn item(id: web::Path<String>) -> impl Responder {
+    ROOT_CALLS.fetch_add(1, Ordering::SeqCst);
+    id.into_inner()
+}
+
+// Served under the tenant segment, then the key
+#[auto_register("/tenants")]
+#[get("/users/{id}")]
+pub async fn user(path: web::Path<(String, String)>) -> impl Responder {
+    TENANT_CALLS.fetch_add(1, Ordering::SeqCst);
+    path.into_inner().1
+}
+
+mod root {
+    use super::*;
+
+    generate_register_service!(["/root"]);
+}
+
+mod tenants {
+    use super::*;
+
+    generate_register_service!(["/tenants", use_scope = true, tenant_segment = "/{tenant}"]);
+}
+
+generate_fuzz_targets!();
+
+#[test]
+fn targets_reach_handlers_served_at_the_root() {
+    fuzz_targets::fuzz_root_item(b"7\0body");
+
+    assert_eq!(ROOT_CALLS.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn targets_reach_handlers_served_under_a_tenant_segment() {
+    fuzz_targets::fuzz_tenants_user(b"acme\x007\0body");
+
+    assert_eq!(TENANT_CALLS.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn routes_are_the_patterns_the_handlers_are_served_under() {
+    assert_eq!(
+        fuzz_targets::ROUTES,
+        ["GET /items/{id}", "GET /{tenant}/tenants/users/{id}"]
+    );
+}
@@ -0,0 +1,37 @@
+use actix_web::{test, web, App, Responder};
+use register_actix_routes::{auto_register, generate_register_service};
+
+pub trait Store: 'static {
+    fn name() -> &'static str;
+}
+
+pub struct PgStore;
+
+impl Store for PgStore {
+    fn name() -> &'static str {
+        "postgres"
+    }
+}
+
+#[auto_register("/events", method = "GET", path = "/search", generics = "<PgStore>")]
+pub async fn search<S: Store>() -> impl Responder {
+    S::name()
+}
+
+generate_register_service!(["/events", use_scope = true]);
+
+#[actix_web::test]
+async fn registers_generic_handlers_with_their_arguments() {
+    let app = test::init_service(App::new().configure(register_service)).await;
+    let response = test::call_service(
+        &app,
+        test::TestRequest::get().uri("/events/search").to_request(),
+    )
+    .await;
+
+    assert!(response.status().is_success());
+    assert_eq!(
+        test::read_body(response).await,
+        web::Bytes::from_static(b"postgres")
+    );
+}
@@ -0,0 +1,33 @@
+use actix_web::{get, test, App, Responder};
+use register_actix_routes::{auto_register, generate_register_service};
+
+#[auto_register("/stats")]
+#[get("/daily")]
+pub async fn daily() -> impl Responder {
+    "Daily"
+}
+
+#[auto_register("/stats")]
+#[get("/monthly")]
+pub async fn monthly() -> impl Responder {
+    "Monthly"
+}
+
+generate_register_service!(["/stats", use_scope = true, hit_counts = true]);
+
+#[actix_web::test]
+async fn counts_the_requests_of_each_route() {
+    let app = test::init_service(App::new().configure(register_service)).await;
+
+    for uri in [
+        "/stats/daily",
+        "/stats/daily",
+        "/stats/monthly",
+        "/stats/daily",
+    ] {
+        let request = test::TestRequest::get().uri(uri).to_request();
+        test::call_service(&app, request).await;
+    }
+
+    assert_eq!(route_hit_counts(), [("daily", 3), ("monthly", 1)]);
+}
@@ -0,0 +1,47 @@
+use actix_web::{get, post, test, App, Responder};
+use register_actix_routes::{auto_register, generate_register_service};
+
+#[auto_register("/billing")]
+#[get("/invoices")]
+pub async fn invoices() -> impl Responder {
+    "Invoices"
+}
+
+#[auto_register("/billing")]
+#[post("/pay")]
+pub async fn pay() -> impl Responder {
+    "Paid"
+}
+
+generate_register_service!(["/billing", use_scope = true, require_https = true]);
+
+#[actix_web::test]
+async fn redirects_plaintext_reads_and_rejects_plaintext_writes() {
+    let app = test::init_service(App::new().configure(register_service)).await;
+
+    let request = test::TestRequest::get()
+        .uri("/billing/invoices?page=2")
+        .insert_header(("Host", "example.com"))
+        .to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status().as_u16(), 308);
+    assert_eq!(
+        response.headers().get("Location").unwrap(),
+        "https://example.com/billing/invoices?page=2"
+    );
+
+    let request = test::TestRequest::post().uri("/billing/pay").to_request();
+    assert_eq!(
+        test::call_service(&app, request).await.status().as_u16(),
+        403
+    );
+
+    let request = test::TestRequest::post()
+        .uri("/billing/pay")
+        .insert_header(("X-Forwarded-Proto", "https"))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, request).await.status().as_u16(),
+        200
+    );
+}
@@ -0,0 +1,87 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use actix_web::{post, test, web, App, HttpResponse, Responder};
+use register_actix_routes::{auto_register, generate_register_service};
+use register_actix_routes_runtime::IdempotencyStore;
+
+#[derive(Default)]
+struct SeenKeys(Mutex<HashSet<String>>);
+
+impl IdempotencyStore for SeenKeys {
+    fn claim(&self, handler: &str, key: &str) -> bool {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(format!("{}:{}", handler, key))
+    }
+
+    fn release(&self, handler: &str, key: &str) {
+        self.0
+            .lock()
+            .unwrap()
+            .remove(&format!("{}:{}", handler, key));
+    }
+}
+
+#[auto_register("/payments", idempotent = true)]
+#[post("/charge")]
+pub async fn charge() -> impl Responder {
+    "Charged"
+}
+
+#[auto_register("/payments", idempotent = true)]
+#[post("/refund")]
+pub async fn refund() -> impl Responder {
+    HttpResponse::ServiceUnavailable().finish()
+}
+
+generate_register_service!(["/payments", use_scope = true]);
+
+fn store() -> web::Data<dyn IdempotencyStore> {
+    web::Data::from(Arc::new(SeenKeys::default()) as Arc<dyn IdempotencyStore>)
+}
+
+fn request(uri: &str, key: Option<&str>) -> test::TestRequest {
+    let mut request = test::TestRequest::post().uri(uri);
+    if let Some(key) = key {
+        request = request.insert_header(("Idempotency-Key", key));
+    }
+    request
+}
+
+#[actix_web::test]
+async fn turns_retries_down_with_a_conflict() {
+    let app = test::init_service(App::new().app_data(store()).configure(register_service)).await;
+
+    for (key, expected) in [
+        (None, 400),
+        (Some("a"), 200),
+        (Some("a"), 409),
+        (Some("b"), 200),
+    ] {
+        let response =
+            test::call_service(&app, request("/payments/charge", key).to_request()).await;
+        assert_eq!(response.status().as_u16(), expected, "{:?}", key);
+    }
+}
+
+#[actix_web::test]
+async fn releases_the_key_of_failed_requests() {
+    let app = test::init_service(App::new().app_data(store()).configure(register_service)).await;
+
+    for _ in 0..2 {
+        let response =
+            test::call_service(&app, request("/payments/refund", Some("a")).to_request()).await;
+        assert_eq!(response.status().as_u16(), 503);
+    }
+}
+
+#[actix_web::test]
+async fn refuses_requests_without_a_store() {
+    let app = test::init_service(App::new().configure(register_service)).await;
+
+    let response =
+        test::call_service(&app, request("/payments/charge", Some("a")).to_request()).await;
+    assert_eq!(response.status().as_u16(), 500);
+}
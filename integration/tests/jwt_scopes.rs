@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use actix_web::{get, test, web, App, Responder};
+use register_actix_routes::{auto_register, generate_register_service};
+use register_actix_routes_runtime::JwtVerifier;
+
+// Grants the scopes listed in the token, e.g. "events:read events:list"
+struct Tokens;
+
+impl JwtVerifier for Tokens {
+    fn scopes(&self, token: &str) -> Option<Vec<String>> {
+        (token != "expired").then(|| token.split(' ').map(String::from).collect())
+    }
+}
+
+#[auto_register("/events", jwt_scopes = ["events:read"])]
+#[get("/list")]
+pub async fn list() -> impl Responder {
+    "List"
+}
+
+generate_register_service!(["/events", use_scope = true]);
+
+#[actix_web::test]
+async fn answers_401_to_invalid_tokens_and_403_to_missing_scopes() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::from(Arc::new(Tokens) as Arc<dyn JwtVerifier>))
+            .configure(register_service),
+    )
+    .await;
+
+    for (authorization, expected) in [
+        (None, 401),
+        (Some("Basic events:read"), 401),
+        (Some("Bearer expired"), 401),
+        (Some("Bearer events:list"), 403),
+        (Some("Bearer events:list events:read"), 200),
+    ] {
+        let mut request = test::TestRequest::get().uri("/events/list");
+        if let Some(authorization) = authorization {
+            request = request.insert_header(("Authorization", authorization));
+        }
+        let response = test::call_service(&app, request.to_request()).await;
+        assert_eq!(response.status().as_u16(), expected, "{:?}", authorization);
+    }
+}
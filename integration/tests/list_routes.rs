@@ -0,0 +1,186 @@
+use actix_web::{get, post, Responder};
+use register_actix_routes::{auto_register, generate_register_service};
+
+#[auto_register("/accounts", api_key = "x-api-key")]
+#[get("/{id}")]
+pub async fn account() -> impl Responder {
+    "Account"
+}
+
+#[auto_register("/accounts")]
+#[post("/")]
+pub async fn open_account() -> impl Responder {
+    "Opened"
+}
+
+#[auto_register("/accounts")]
+#[get("/internal")]
+#[doc(hidden)]
+pub async fn internal() -> impl Responder {
+    "Internal"
+}
+
+#[auto_register("/probes")]
+#[get("/ping")]
+pub async fn ping() -> impl Responder {
+    "Ping"
+}
+
+#[auto_register("/probes")]
+#[get("/ping")]
+pub async fn pong() -> impl Responder {
+    "Pong"
+}
+
+generate_register_service!(["/accounts", use_scope = true]);
+
+// Served at the root, for the `Full path` column to differ from the scope
+mod probes {
+    use super::{ping, pong};
+    use register_actix_routes::generate_register_service;
+
+    generate_register_service!(["/probes", use_scope = false]);
+}
+
+// Each set of options defines the listing functions in a module of its own
+mod table {
+    use register_actix_routes::generate_list_routes;
+
+    generate_list_routes!(startup = "summary");
+}
+
+mod badges {
+    use register_actix_routes::generate_list_routes;
+
+    generate_list_routes!(badges = true);
+}
+
+mod grouped {
+    use register_actix_routes::generate_list_routes;
+
+    generate_list_routes!(grouped = true);
+}
+
+mod truncated {
+    use register_actix_routes::generate_list_routes;
+
+    generate_list_routes!(overflow = "truncate", max_width = 60);
+}
+
+// The row of a handler in a listing
+fn row<'a>(listing: &'a str, handler: &str) -> &'a str {
+    listing
+        .lines()
+        .find(|line| line.contains(&format!(" {} ", handler)))
+        .unwrap_or_else(|| panic!("no row for `{}` in:\n{}", handler, listing))
+}
+
+#[test]
+fn lists_the_routes_under_a_heading() {
+    let listing = table::list_routes_string();
+
+    assert!(listing.starts_with("List of the automatically registered routes:\n"));
+    for header in [
+        "Scope",
+        "Path",
+        "Full path",
+        "Handler",
+        "Verb",
+        "Auth",
+        "Errors",
+        "Source",
+    ] {
+        assert!(
+            listing.lines().nth(2).unwrap().contains(header),
+            "{}",
+            header
+        );
+    }
+    assert!(!listing.contains("Status"));
+}
+
+#[test]
+fn lists_the_paths_clients_call() {
+    let listing = table::list_routes_string();
+
+    assert!(row(&listing, "account").contains("/accounts/{id}"));
+    let probe = row(&listing, "ping");
+    assert!(probe.contains("/probes"));
+    assert!(probe.contains(" /ping "));
+}
+
+#[test]
+fn flags_the_unprotected_mutations() {
+    let listing = table::list_routes_string();
+
+    assert!(row(&listing, "account").contains(" yes "));
+    assert!(row(&listing, "open_account").contains(" no ⚠ "));
+    assert!(row(&listing, "internal").contains(" no "));
+    assert!(listing.contains("\n⚠ 1 route changes state without requiring authentication"));
+}
+
+#[test]
+fn flags_the_routes_sharing_a_verb_and_path() {
+    let listing = table::list_routes_string();
+
+    assert!(row(&listing, "ping").contains("GET ⚠ duplicate"));
+    assert!(row(&listing, "pong").contains("GET ⚠ duplicate"));
+    assert!(!row(&listing, "account").contains("duplicate"));
+    assert!(listing.contains(
+        "\n⚠ 2 routes share their verb and path with another route, Actix only serves the first registered"
+    ));
+}
+
+#[test]
+fn summarizes_the_routes_per_verb() {
+    assert_eq!(
+        table::routes_summary(),
+        "5 routes across 2 scopes (GET 4, POST 1)"
+    );
+    // Printed once, however many workers start
+    table::on_startup();
+    table::on_startup();
+}
+
+#[test]
+fn badges_the_routes_to_audit() {
+    let listing = badges::list_routes_string();
+
+    assert!(listing.lines().nth(2).unwrap().contains("Status"));
+    assert!(row(&listing, "account").contains("🔒"));
+    assert!(row(&listing, "internal").contains("🙈"));
+    assert!(!row(&listing, "ping").contains("🔒"));
+    assert!(
+        listing.contains("\n⛔ deprecated  🔒 auth required  🙈 hidden from docs  📡 streaming")
+    );
+}
+
+#[test]
+fn groups_the_routes_under_their_scope() {
+    let listing = grouped::list_routes_string();
+
+    assert!(!listing.lines().nth(2).unwrap().contains("Scope"));
+    let scopes = listing
+        .lines()
+        .filter(|line| line.starts_with('│') && line.matches('│').count() == 2)
+        .map(|line| line.trim_matches(|c: char| c == '│' || c.is_whitespace()))
+        .collect::<Vec<_>>();
+    assert_eq!(scopes, ["/accounts", "/probes"]);
+    assert!(row(&listing, "account").contains("/accounts/{id}"));
+}
+
+#[test]
+fn truncates_the_table_to_the_terminal_width() {
+    // The only listing of this file fitted to `COLUMNS`
+    std::env::set_var("COLUMNS", "60");
+    let listing = truncated::list_routes_string();
+
+    for line in listing
+        .lines()
+        .skip(1)
+        .take_while(|line| !line.starts_with('⚠'))
+    {
+        assert!(line.chars().count() <= 60, "{}", line);
+    }
+    assert!(listing.contains('…'));
+}
@@ -0,0 +1,57 @@
+use actix_web::{get, middleware::Logger, web, App, Responder};
+// Renamed, as `actix_web::test` would shadow the `#[test]` attribute
+use actix_web::test as actix_test;
+use register_actix_routes::{auto_register, generate_log_exclusions, generate_register_service};
+
+mod health {
+    use super::*;
+
+    #[auto_register("/health", log = false)]
+    #[get("/live")]
+    pub async fn live() -> impl Responder {
+        "OK"
+    }
+
+    generate_register_service!(["/health"]);
+}
+
+mod payments {
+    use super::*;
+
+    #[auto_register("/payments", log_format = "%r %s")]
+    #[get("/{id:\\d+}")]
+    pub async fn payment() -> impl Responder {
+        "Payment"
+    }
+
+    generate_register_service!(["/payments", use_scope = true]);
+}
+
+generate_log_exclusions!();
+
+#[test]
+fn excludes_the_paths_the_routes_are_served_under() {
+    assert_eq!(LOG_EXCLUDED_PATHS, &["^/live$", r"^/payments/(?:\d+)$"]);
+}
+
+#[actix_web::test]
+async fn serves_the_routes_behind_the_filtered_logger() {
+    let app = actix_test::init_service(
+        App::new()
+            .wrap(exclude_route_logging(Logger::default(), "/api/"))
+            .service(
+                web::scope("/api")
+                    .configure(payments::register_service)
+                    .configure(health::register_service),
+            ),
+    )
+    .await;
+
+    for uri in ["/api/live", "/api/payments/1"] {
+        let request = actix_test::TestRequest::get().uri(uri).to_request();
+        assert!(actix_test::call_service(&app, request)
+            .await
+            .status()
+            .is_success());
+    }
+}
@@ -0,0 +1,43 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use actix_web::{get, test, App, Responder};
+use register_actix_routes::{auto_register, generate_register_service};
+
+pub static EVENTS_MAINTENANCE: AtomicBool = AtomicBool::new(false);
+
+#[auto_register("/events")]
+#[get("/search")]
+pub async fn search() -> impl Responder {
+    "Search"
+}
+
+generate_register_service!([
+    "/events",
+    use_scope = true,
+    maintenance = EVENTS_MAINTENANCE,
+    retry_after = 300
+]);
+
+#[actix_web::test]
+async fn answers_503_during_maintenance() {
+    let app = test::init_service(App::new().configure(register_service)).await;
+
+    let request = test::TestRequest::get().uri("/events/search").to_request();
+    assert_eq!(
+        test::call_service(&app, request).await.status().as_u16(),
+        200
+    );
+
+    EVENTS_MAINTENANCE.store(true, Ordering::Relaxed);
+    let request = test::TestRequest::get().uri("/events/search").to_request();
+    let response = test::call_service(&app, request).await;
+    EVENTS_MAINTENANCE.store(false, Ordering::Relaxed);
+    assert_eq!(response.status().as_u16(), 503);
+    assert_eq!(response.headers().get("Retry-After").unwrap(), "300");
+
+    let request = test::TestRequest::get().uri("/events/search").to_request();
+    assert_eq!(
+        test::call_service(&app, request).await.status().as_u16(),
+        200
+    );
+}
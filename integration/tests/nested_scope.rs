@@ -0,0 +1,137 @@
+use actix_web::{get, test, web, App, Responder};
+use register_actix_routes::{auto_register, generate_register_service};
+use register_actix_routes_runtime::ApiKeyStore;
+use std::sync::Arc;
+
+struct StaticKey;
+
+impl ApiKeyStore for StaticKey {
+    fn is_valid(&self, key: &str) -> bool {
+        key == "secret"
+    }
+}
+
+mod events {
+    use super::*;
+
+    #[auto_register("/events", api_key = "x-api-key")]
+    #[get("/{id}")]
+    pub async fn event(id: web::Path<u32>) -> impl Responder {
+        format!("Event {}", id)
+    }
+
+    generate_register_service!(["/events"]);
+}
+
+mod reports {
+    use super::*;
+
+    #[auto_register("/reports", api_key = "x-api-key")]
+    #[get("/export")]
+    pub async fn export() -> impl Responder {
+        "Report"
+    }
+
+    generate_register_service!(["/reports", use_scope = true]);
+}
+
+// A protected route whose served pattern is also the path of an open route
+mod items {
+    use super::*;
+
+    #[auto_register("/items", api_key = "x-api-key")]
+    #[get("/{id}")]
+    pub async fn item(id: web::Path<u32>) -> impl Responder {
+        format!("Item {}", id)
+    }
+
+    #[auto_register("/items")]
+    #[get("/x/{id}")]
+    pub async fn open_item(id: web::Path<u32>) -> impl Responder {
+        format!("Open item {}", id)
+    }
+
+    #[auto_register("/items", api_key = "x-api-key", method = "DELETE", path = "/{id}")]
+    pub async fn delete_item(id: web::Path<u32>) -> impl Responder {
+        format!("Deleted item {}", id)
+    }
+
+    generate_register_service!(["/items"]);
+}
+
+fn store() -> web::Data<dyn ApiKeyStore> {
+    web::Data::from(Arc::new(StaticKey) as Arc<dyn ApiKeyStore>)
+}
+
+// Statuses of a GET request, without and with the key
+macro_rules! statuses {
+    ($app:expr, $uri:expr) => {{
+        let without = test::TestRequest::get().uri($uri).to_request();
+        let with = test::TestRequest::get()
+            .uri($uri)
+            .insert_header(("x-api-key", "secret"))
+            .to_request();
+        (
+            test::call_service(&$app, without).await.status().as_u16(),
+            test::call_service(&$app, with).await.status().as_u16(),
+        )
+    }};
+}
+
+#[actix_web::test]
+async fn checks_routes_mounted_under_the_scope_of_their_key() {
+    let app = test::init_service(
+        App::new()
+            .app_data(store())
+            .service(web::scope("/events").configure(events::register_service)),
+    )
+    .await;
+
+    assert_eq!(statuses!(app, "/events/1"), (401, 200));
+}
+
+#[actix_web::test]
+async fn checks_routes_nested_under_outer_scopes() {
+    let app = test::init_service(
+        App::new()
+            .app_data(store())
+            .service(web::scope("/api/v1/events").configure(events::register_service))
+            .service(web::scope("/api/v1").configure(reports::register_service)),
+    )
+    .await;
+
+    assert_eq!(statuses!(app, "/api/v1/reports/export"), (401, 200));
+    assert_eq!(statuses!(app, "/api/v1/events/1"), (401, 200));
+}
+
+#[actix_web::test]
+async fn answers_not_found_outside_the_registered_routes() {
+    let app = test::init_service(
+        App::new()
+            .app_data(store())
+            .service(web::scope("/api").configure(reports::register_service)),
+    )
+    .await;
+
+    assert_eq!(statuses!(app, "/api/reports/missing"), (404, 404));
+}
+
+#[actix_web::test]
+async fn checks_the_route_that_serves_the_request() {
+    let app = test::init_service(
+        App::new()
+            .app_data(store())
+            .service(web::scope("/x").configure(items::register_service)),
+    )
+    .await;
+
+    // `/x/5` is served by `item` under the `/x/{id}` pattern, the path of `open_item`
+    assert_eq!(statuses!(app, "/x/5"), (401, 200));
+    assert_eq!(statuses!(app, "/x/x/5"), (200, 200));
+
+    let without = test::TestRequest::delete().uri("/x/5").to_request();
+    assert_eq!(
+        test::call_service(&app, without).await.status().as_u16(),
+        401
+    );
+}
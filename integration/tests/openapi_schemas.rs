@@ -0,0 +1,84 @@
+use actix_web::{post, web, Responder};
+use register_actix_routes::{auto_register, generate_register_service};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct Venue {
+    pub city: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct NewEvent {
+    pub name: String,
+    pub note: Option<String>,
+    pub venue: Option<Venue>,
+}
+
+#[auto_register("/events")]
+#[post("/create")]
+pub async fn create(event: web::Json<NewEvent>) -> impl Responder {
+    event.into_inner().name
+}
+
+generate_register_service!(["/events", use_scope = true]);
+
+mod v3_0 {
+    register_actix_routes::generate_openapi!(version = "3.0");
+}
+
+mod v3_1 {
+    register_actix_routes::generate_openapi!(version = "3.1");
+}
+
+// The document, and the schema of the request body of `create`
+fn body_schema(openapi: String) -> (Value, Value) {
+    let document: Value = serde_json::from_str(&openapi).unwrap();
+    let schema = document["paths"]["/events/create"]["post"]["requestBody"]["content"]
+        ["application/json"]["schema"]
+        .clone();
+    (document, schema)
+}
+
+#[test]
+fn moves_definitions_to_the_components() {
+    for openapi in [v3_0::openapi(), v3_1::openapi()] {
+        let (document, schema) = body_schema(openapi);
+
+        assert!(schema.get("$schema").is_none());
+        assert!(schema.get("definitions").is_none());
+        assert_eq!(
+            document["components"]["schemas"]["Venue"]["properties"]["city"],
+            json!({ "type": "string" })
+        );
+    }
+}
+
+#[test]
+fn marks_nullable_schemas_for_openapi_3_0() {
+    let (_, schema) = body_schema(v3_0::openapi());
+
+    assert_eq!(
+        schema["properties"]["note"],
+        json!({ "type": "string", "nullable": true })
+    );
+    assert_eq!(
+        schema["properties"]["venue"],
+        json!({ "allOf": [{ "$ref": "#/components/schemas/Venue" }], "nullable": true })
+    );
+}
+
+#[test]
+fn keeps_null_types_for_openapi_3_1() {
+    let (_, schema) = body_schema(v3_1::openapi());
+
+    assert_eq!(
+        schema["properties"]["note"],
+        json!({ "type": ["string", "null"] })
+    );
+    assert_eq!(
+        schema["properties"]["venue"],
+        json!({ "anyOf": [{ "$ref": "#/components/schemas/Venue" }, { "type": "null" }] })
+    );
+}
@@ -0,0 +1,80 @@
+use actix_web::{get, test, web, App, Responder};
+use register_actix_routes::{auto_register, generate_register_service};
+
+pub struct Settings(&'static str);
+
+pub struct Pool;
+
+#[auto_register("/settings")]
+#[get("/name")]
+pub async fn name(settings: web::Data<Settings>) -> impl Responder {
+    settings.0
+}
+
+#[auto_register("/settings", requires = [Pool])]
+#[get("/pool")]
+pub async fn pool(_pool: web::Data<Pool>) -> impl Responder {
+    "Pool"
+}
+
+generate_register_service!([
+    "/settings",
+    use_scope = true,
+    requires = [Settings = Settings("default")]
+]);
+
+#[actix_web::test]
+async fn serves_the_default_when_the_app_has_no_value() {
+    let app = test::init_service(App::new().configure(register_service)).await;
+
+    let request = test::TestRequest::get().uri("/settings/name").to_request();
+    assert_eq!(test::call_and_read_body(&app, request).await, "default");
+}
+
+#[actix_web::test]
+async fn serves_the_value_registered_after_the_default() {
+    let app = test::init_service(
+        App::new()
+            .configure(register_service)
+            .app_data(web::Data::new(Settings("app"))),
+    )
+    .await;
+
+    let request = test::TestRequest::get().uri("/settings/name").to_request();
+    assert_eq!(test::call_and_read_body(&app, request).await, "app");
+}
+
+#[actix_web::test]
+async fn serves_routes_whose_required_data_is_registered() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(Pool))
+            .configure(register_service),
+    )
+    .await;
+
+    let request = test::TestRequest::get().uri("/settings/pool").to_request();
+    assert_eq!(test::call_and_read_body(&app, request).await, "Pool");
+}
+
+// A key of its own, as each route is only checked on its first request
+mod audits {
+    use super::*;
+
+    #[auto_register("/audits", requires = [super::Pool])]
+    #[get("/pool")]
+    pub async fn audit_pool(_pool: web::Data<Pool>) -> impl Responder {
+        "Pool"
+    }
+
+    generate_register_service!(["/audits", use_scope = true]);
+}
+
+#[actix_web::test]
+#[should_panic(expected = "route `audit_pool` of `/audits` requires `web::Data<super::Pool>`")]
+async fn panics_with_the_missing_type() {
+    let app = test::init_service(App::new().configure(audits::register_service)).await;
+
+    let request = test::TestRequest::get().uri("/audits/pool").to_request();
+    test::call_service(&app, request).await;
+}
@@ -0,0 +1,31 @@
+use actix_web::{get, test, App, Responder};
+use register_actix_routes::{auto_register, generate_register_service};
+
+#[auto_register("/drafts", headers = [("X-Robots-Tag", "noindex"), ("Cache-Control", "no-store")])]
+#[get("/{id}")]
+pub async fn draft() -> impl Responder {
+    "Draft"
+}
+
+#[auto_register("/drafts")]
+#[get("/")]
+pub async fn drafts() -> impl Responder {
+    "Drafts"
+}
+
+generate_register_service!(["/drafts", use_scope = true]);
+
+#[actix_web::test]
+async fn adds_the_headers_of_the_route() {
+    let app = test::init_service(App::new().configure(register_service)).await;
+
+    let request = test::TestRequest::get().uri("/drafts/1").to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.headers().get("X-Robots-Tag").unwrap(), "noindex");
+    assert_eq!(response.headers().get("Cache-Control").unwrap(), "no-store");
+
+    let request = test::TestRequest::get().uri("/drafts/").to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.headers().get("X-Robots-Tag").is_none());
+}
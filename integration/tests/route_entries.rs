@@ -0,0 +1,36 @@
+use actix_web::{get, post, Responder};
+use register_actix_routes::{auto_register, generate_register_service, generate_route_entries};
+use register_actix_routes_runtime::RouteEntry;
+
+#[auto_register("/events")]
+#[get("/{id}")]
+pub async fn detail() -> impl Responder {
+    "Detail"
+}
+
+#[auto_register("/events")]
+#[post("/")]
+pub async fn create() -> impl Responder {
+    "Created"
+}
+
+generate_register_service!(["/events", use_scope = true]);
+
+generate_route_entries!();
+
+#[test]
+fn entries_round_trip_through_json() {
+    let json = serde_json::to_string(ROUTES).unwrap();
+    let routes: Vec<RouteEntry> = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(routes, ROUTES);
+}
+
+#[test]
+fn entries_serialize_their_fields() {
+    let json = serde_json::to_value(&ROUTES[0]).unwrap();
+
+    assert_eq!(json["scope"], "/events");
+    assert_eq!(json["verb"], ROUTES[0].verb.as_ref());
+    assert_eq!(json["handler"], ROUTES[0].handler.as_ref());
+}
@@ -0,0 +1,69 @@
+use actix_web::{get, Responder};
+use register_actix_routes::{
+    auto_register, generate_register_service, generate_route_entries, generate_route_paths,
+};
+
+mod events {
+    use super::*;
+
+    #[auto_register("/events")]
+    #[get("/{id}")]
+    pub async fn detail() -> impl Responder {
+        "Detail"
+    }
+
+    #[auto_register("/events")]
+    #[get("/{id}/files/{path}*")]
+    pub async fn file() -> impl Responder {
+        "File"
+    }
+
+    generate_register_service!(["/events", use_scope = true]);
+}
+
+mod health {
+    use super::*;
+
+    #[auto_register("/health")]
+    #[get("/live")]
+    pub async fn live() -> impl Responder {
+        "OK"
+    }
+
+    generate_register_service!(["/health"]);
+}
+
+generate_route_paths!();
+generate_route_entries!();
+
+#[test]
+fn paths_are_the_ones_routes_are_served_under() {
+    assert_eq!(route_paths::EVENTS_DETAIL, "/events/{id}");
+    assert_eq!(route_paths::HEALTH_LIVE, "/live");
+}
+
+#[test]
+fn finds_routes_by_the_pattern_they_are_served_under() {
+    assert_route_exists("GET", "/events/{id}");
+    assert_route_exists("GET", "/live");
+}
+
+#[test]
+#[should_panic(expected = "No route is registered for `GET /health/live`")]
+fn rejects_the_key_of_a_route_served_without_scope() {
+    assert_route_exists("GET", "/health/live");
+}
+
+#[test]
+fn urls_percent_encode_the_segment_values() {
+    assert_eq!(route_paths::events_detail_url("a b/c"), "/events/a%20b%2Fc");
+    assert_eq!(route_paths::events_detail_url(7), "/events/7");
+}
+
+#[test]
+fn urls_keep_the_slashes_of_tail_segments() {
+    assert_eq!(
+        route_paths::events_file_url("a/b", "docs/read me.txt"),
+        "/events/a%2Fb/files/docs/read%20me.txt"
+    );
+}
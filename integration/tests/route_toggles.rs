@@ -0,0 +1,35 @@
+use actix_web::{get, test, App, Responder};
+use register_actix_routes::{auto_register, generate_register_service};
+
+#[auto_register("/bookings")]
+#[get("/list")]
+pub async fn list() -> impl Responder {
+    "List"
+}
+
+#[auto_register("/bookings")]
+#[get("/{id}")]
+pub async fn detail() -> impl Responder {
+    "Detail"
+}
+
+generate_register_service!(["/bookings", use_scope = true, toggles = true]);
+
+#[actix_web::test]
+async fn answers_503_while_a_route_is_disabled() {
+    let app = test::init_service(App::new().configure(register_service)).await;
+    let status = |uri: &'static str| {
+        let request = test::TestRequest::get().uri(uri).to_request();
+        let response = test::call_service(&app, request);
+        async move { response.await.status().as_u16() }
+    };
+
+    assert!(RouteToggles::disable("list"));
+    assert_eq!(RouteToggles::is_enabled("list"), Some(false));
+    assert_eq!(status("/bookings/list").await, 503);
+    assert_eq!(status("/bookings/1").await, 200);
+
+    assert!(RouteToggles::enable("list"));
+    assert_eq!(status("/bookings/list").await, 200);
+    assert!(!RouteToggles::disable("missing"));
+}
@@ -0,0 +1,39 @@
+use actix_web::web::ServiceConfig;
+use actix_web::{test, App};
+use register_actix_routes_runtime::RouterModule;
+
+mod events {
+    use actix_web::{get, Responder};
+    use register_actix_routes::{auto_register, RouterModule};
+
+    #[auto_register("/events")]
+    #[get("/search")]
+    pub async fn search() -> impl Responder {
+        "Search"
+    }
+
+    #[derive(RouterModule)]
+    #[router_module("/events", use_scope = true)]
+    pub struct EventsModule;
+}
+
+#[actix_web::test]
+async fn registers_the_routes_of_boxed_modules() {
+    let modules: Vec<Box<dyn RouterModule<ServiceConfig>>> = vec![Box::new(events::EventsModule)];
+    let app = test::init_service(App::new().configure(|cfg| {
+        for module in &modules {
+            module.configure(cfg);
+        }
+    }))
+    .await;
+
+    let request = test::TestRequest::get().uri("/events/search").to_request();
+    assert_eq!(test::call_and_read_body(&app, request).await, "Search");
+
+    let routes = modules[0].routes();
+    assert_eq!(routes.len(), 1);
+    assert_eq!(
+        (routes[0].verb.as_ref(), routes[0].path.as_ref()),
+        ("GET", "/search")
+    );
+}
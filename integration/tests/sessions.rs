@@ -0,0 +1,53 @@
+use actix_session::{storage::CookieSessionStore, Session, SessionMiddleware};
+use actix_web::cookie::Key;
+use actix_web::{get, test, web, App, Responder};
+use register_actix_routes::{auto_register, generate_register_service};
+
+#[auto_register("/account", session = required)]
+#[get("/profile")]
+pub async fn profile() -> impl Responder {
+    "Profile"
+}
+
+generate_register_service!(["/account", use_scope = true]);
+
+#[actix_web::test]
+async fn answers_401_without_a_session() {
+    let app = test::init_service(
+        App::new()
+            .wrap(SessionMiddleware::new(
+                CookieSessionStore::default(),
+                Key::generate(),
+            ))
+            .route(
+                "/login",
+                web::post().to(|session: Session| async move {
+                    session.insert("user_id", "42").unwrap();
+                    "Logged in"
+                }),
+            )
+            .configure(register_service),
+    )
+    .await;
+
+    let request = test::TestRequest::get()
+        .uri("/account/profile")
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, request).await.status().as_u16(),
+        401
+    );
+
+    let request = test::TestRequest::post().uri("/login").to_request();
+    let login = test::call_service(&app, request).await;
+    let cookie = login.response().cookies().next().unwrap().into_owned();
+
+    let request = test::TestRequest::get()
+        .uri("/account/profile")
+        .cookie(cookie)
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, request).await.status().as_u16(),
+        200
+    );
+}
@@ -0,0 +1,28 @@
+use actix_web::{test, App, Responder};
+use register_actix_routes::{auto_register, generate_register_service};
+
+#[auto_register("/events", method = "GET", path = "/{id}")]
+pub async fn show() -> impl Responder {
+    "show"
+}
+
+#[auto_register("/events", method = "DELETE", path = "/{id}")]
+pub async fn delete() -> impl Responder {
+    "delete"
+}
+
+generate_register_service!(["/events", use_scope = true]);
+
+#[actix_web::test]
+async fn serves_every_method_of_a_shared_path() {
+    let app = test::init_service(App::new().configure(register_service)).await;
+
+    for (request, expected) in [
+        (test::TestRequest::get(), "show"),
+        (test::TestRequest::delete(), "delete"),
+    ] {
+        let response = test::call_service(&app, request.uri("/events/1").to_request()).await;
+        assert!(response.status().is_success());
+        assert_eq!(test::read_body(response).await, expected.as_bytes());
+    }
+}
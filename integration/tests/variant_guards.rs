@@ -0,0 +1,67 @@
+use actix_web::{test, App};
+use register_actix_routes::generate_register_service;
+
+// The handlers don't see the module of the generator, which defines their guards
+mod pages {
+    use actix_web::{get, Responder};
+    use register_actix_routes::auto_register;
+
+    #[auto_register("/pages", variant = "A")]
+    #[get("/home")]
+    pub async fn home_a() -> impl Responder {
+        "A"
+    }
+
+    #[auto_register("/pages", variant = "B")]
+    #[get("/home")]
+    pub async fn home_b() -> impl Responder {
+        "B"
+    }
+
+    #[auto_register("/pages", produces = "application/json")]
+    #[get("/{id}")]
+    pub async fn detail_json() -> impl Responder {
+        "json"
+    }
+
+    #[auto_register("/pages", produces = "text/html")]
+    #[get("/{id}")]
+    pub async fn detail_html() -> impl Responder {
+        "html"
+    }
+}
+
+use pages::*;
+
+generate_register_service!(["/pages", use_scope = true]);
+
+#[actix_web::test]
+async fn splits_requests_between_variants() {
+    let app = test::init_service(App::new().configure(register_service)).await;
+
+    let mut bodies = Vec::new();
+    for _ in 0..4 {
+        let request = test::TestRequest::get().uri("/pages/home").to_request();
+        bodies.push(test::call_and_read_body(&app, request).await);
+    }
+    bodies.sort();
+
+    assert_eq!(bodies, ["A", "A", "B", "B"]);
+}
+
+#[actix_web::test]
+async fn picks_the_handler_of_the_accepted_media_type() {
+    let app = test::init_service(App::new().configure(register_service)).await;
+
+    for (accept, expected) in [
+        ("text/html", "html"),
+        ("application/json", "json"),
+        ("text/html;q=0.5, application/json", "json"),
+    ] {
+        let request = test::TestRequest::get()
+            .uri("/pages/1")
+            .insert_header(("accept", accept))
+            .to_request();
+        assert_eq!(test::call_and_read_body(&app, request).await, expected);
+    }
+}
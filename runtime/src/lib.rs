@@ -0,0 +1,136 @@
+//! Runtime companion of `register-actix-routes`, which as a proc-macro crate can
+//! only export macros.
+//!
+//! `generate_register_service!` implements [`Registrable`] for the struct named by
+//! its `registrable` option, so applications can collect the generated modules as
+//! `Vec<Box<dyn Registrable<ServiceConfig>>>` and mount them dynamically (e.g.,
+//! plugins enabled by the configuration, or a subset of modules in tests).
+//! `#[derive(RouterModule)]` implements [`RouterModule`] the same way for a marker
+//! struct, which also lists its routes.
+//!
+//! The middlewares generated for route options read the app's collaborators as
+//! trait objects of this crate from the app data (e.g., a [`FeatureFlagProvider`]
+//! for the routes declared with a `flag`).
+//!
+//! The generated route registries list their routes as [`RouteEntry`], so
+//! downstream code shares this type instead of declaring a struct of its own.
+//!
+//! The traits are generic over the configuration type, so this crate doesn't depend
+//! on Actix Web and works with both actix-web 3 and 4.
+
+use std::borrow::Cow;
+
+/// A route registered automatically, as listed by `list_routes`. The generated
+/// entries borrow static strings, and deserialized ones own theirs.
+///
+/// With the `serde` feature, `RouteEntry` derives `Serialize` and `Deserialize`
+/// (e.g., to send the routes to other services or read them back from a file).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RouteEntry {
+    /// The scope or module key (e.g., "/events")
+    pub scope: Cow<'static, str>,
+    /// The route path (e.g., "/search")
+    pub path: Cow<'static, str>,
+    /// The HTTP method (e.g., "GET")
+    pub verb: Cow<'static, str>,
+    /// The name of the handler function, or the directory of a static mount
+    pub handler: Cow<'static, str>,
+    /// The file and line of the handler (e.g., "src/api/events.rs:7")
+    pub source: Cow<'static, str>,
+}
+
+/// A module of routes registered by `register_service`
+pub trait Registrable<Config: ?Sized>: Send + Sync {
+    /// The key the routes were registered under with `auto_register` (e.g., "/events")
+    fn key(&self) -> &'static str;
+
+    /// Registers the routes of the module
+    fn register(&self, cfg: &mut Config);
+}
+
+/// A group of routes registered together, implemented with
+/// `#[derive(RouterModule)]`. Modules can be collected as
+/// `Vec<Box<dyn RouterModule<ServiceConfig>>>` and registered in turn.
+pub trait RouterModule<Config: ?Sized>: Send + Sync {
+    /// Registers the routes of the module
+    fn configure(&self, cfg: &mut Config);
+
+    /// The routes registered by `configure`
+    fn routes(&self) -> &'static [RouteEntry];
+}
+
+/// Registers every module in turn
+pub fn register_all<Config: ?Sized>(cfg: &mut Config, modules: &[Box<dyn Registrable<Config>>]) {
+    for module in modules {
+        module.register(cfg);
+    }
+}
+
+/// Registers the modules whose key is listed, in the order of `modules`
+pub fn register_only<Config: ?Sized>(
+    cfg: &mut Config,
+    modules: &[Box<dyn Registrable<Config>>],
+    keys: &[&str],
+) {
+    for module in modules {
+        if keys.contains(&module.key()) {
+            module.register(cfg);
+        }
+    }
+}
+
+/// Decides which feature flags are on, for the routes registered with a `flag`.
+/// Registered as `web::Data<dyn FeatureFlagProvider>` app data; flagged routes
+/// answer `404 Not Found` while their flag is off or without a provider.
+pub trait FeatureFlagProvider: Send + Sync {
+    /// Whether the flag is on for the current request
+    fn is_enabled(&self, flag: &str) -> bool;
+}
+
+/// Validates the API keys of the routes registered with `api_key`. Registered as
+/// `web::Data<dyn ApiKeyStore>` app data; key-protected routes answer
+/// `401 Unauthorized` without a valid key or without a store.
+pub trait ApiKeyStore: Send + Sync {
+    /// Whether the key sent with the request is valid
+    fn is_valid(&self, key: &str) -> bool;
+}
+
+/// Verifies the bearer tokens of the routes registered with `jwt_scopes`.
+/// Registered as `web::Data<dyn JwtVerifier>` app data; the routes answer
+/// `401 Unauthorized` without a valid token or without a verifier, and
+/// `403 Forbidden` when the token lacks one of their scopes.
+pub trait JwtVerifier: Send + Sync {
+    /// The scopes granted by a token (e.g., its `scope` claim), or `None` when
+    /// the token is invalid or expired
+    fn scopes(&self, token: &str) -> Option<Vec<String>>;
+}
+
+/// Receives the requests of the routes registered with `audit = true`.
+/// Registered as `web::Data<dyn AuditSink<HttpRequest>>` app data; nothing is
+/// reported without a sink. The trait is generic over the request type, which
+/// is Actix Web's `HttpRequest`.
+pub trait AuditSink<Request: ?Sized>: Send + Sync {
+    /// Identifies the caller of a request (e.g., from a header or an extension
+    /// set by the authentication middleware)
+    fn caller(&self, _req: &Request) -> Option<String> {
+        None
+    }
+
+    /// Records a request once answered, with its method, the path pattern of its
+    /// route (e.g., "/accounts/{id}"), its caller and its status code
+    fn record(&self, method: &str, path: &str, caller: Option<&str>, status: u16);
+}
+
+/// Remembers the `Idempotency-Key` headers of the routes registered with
+/// `idempotent = true`. Registered as `web::Data<dyn IdempotencyStore>` app
+/// data; the routes answer `400 Bad Request` without a key, `409 Conflict`
+/// when the key was already used for the handler, and `500 Internal Server
+/// Error` without a store.
+pub trait IdempotencyStore: Send + Sync {
+    /// Claims a key for a request to a handler, `false` when it was already claimed
+    fn claim(&self, handler: &str, key: &str) -> bool;
+
+    /// Releases the key of a request that failed, so the client can retry it
+    fn release(&self, handler: &str, key: &str);
+}
@@ -0,0 +1,1799 @@
+use std::collections::BTreeMap;
+
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{LitStr, Meta, Token};
+
+// Arguments of `#[auto_register("/prefix", option = value, ...)]`
+pub(crate) struct AutoRegisterArgs {
+    pub(crate) prefix: String,
+    pub(crate) example_response: Option<String>,
+    pub(crate) example_request: Option<String>,
+    pub(crate) operation_id: Option<String>,
+    pub(crate) errors: Vec<u16>,
+    pub(crate) generics: Option<String>,
+    pub(crate) debug: bool,
+    // Resource-style registration of a handler without an Actix route macro
+    pub(crate) method: Option<String>,
+    pub(crate) path: Option<String>,
+    // The handler upgrades the connection to a WebSocket
+    pub(crate) ws: bool,
+    // The handler streams Server-Sent Events
+    pub(crate) sse: bool,
+    // The handler receives a multipart upload, with its total size limit if any (e.g., "25MB")
+    pub(crate) multipart: bool,
+    pub(crate) multipart_limit: Option<String>,
+    // Log a warning when a request takes longer than this many milliseconds
+    pub(crate) warn_slower_than_ms: Option<u64>,
+    // Consecutive failures opening the route's circuit, and how long it stays open in milliseconds
+    pub(crate) circuit_breaker: Option<(u32, u64)>,
+    // Percentage of the traffic sent to this canary handler rather than the stable one
+    pub(crate) canary: Option<u8>,
+    // Request header hashed to pick the canary (e.g., "x-user-id")
+    pub(crate) canary_header: Option<String>,
+    // Name of this A/B variant of the path, and its share of the traffic relative to the other variants
+    pub(crate) variant: Option<String>,
+    pub(crate) weight: Option<u32>,
+    // Handler receiving a copy of every request in the background, its response discarded
+    pub(crate) shadow_to: Option<syn::Path>,
+    // Feature flag serving the route only while the app's `FeatureFlagProvider` enables it
+    pub(crate) flag: Option<String>,
+    // Roles allowed to call the route, listed by `generate_authz_matrix!`
+    pub(crate) roles: Vec<String>,
+    // Header carrying the API key checked against the app's `ApiKeyStore` (e.g., "x-api-key")
+    pub(crate) api_key: Option<String>,
+    // Listed by `generate_csrf_exemptions!` for CSRF middlewares to skip the route
+    pub(crate) csrf_exempt: bool,
+    // Reject the requests without an actix-session session
+    pub(crate) session_required: bool,
+    // Scopes the bearer token must grant, checked with the app's `JwtVerifier`
+    pub(crate) jwt_scopes: Vec<String>,
+    // Report each request to the app's `AuditSink`
+    pub(crate) audit: bool,
+    // Require an `Idempotency-Key` header, claimed in the app's `IdempotencyStore`
+    pub(crate) idempotent: bool,
+    // Size limit of the request body, enforced with `PayloadConfig` and `JsonConfig` (e.g., "1MB")
+    pub(crate) body_limit: Option<String>,
+    // Media type of the request body, other Content-Types are answered with 415 (e.g., "application/json")
+    pub(crate) consumes: Option<String>,
+    // Media type of the response, negotiated with the `Accept` header against the
+    // other handlers of the path (e.g., "text/html")
+    pub(crate) produces: Option<String>,
+    // Tag the responses with a strong ETag and answer 304 to a matching `If-None-Match`
+    pub(crate) etag: bool,
+    // Headers added to every response of the route (e.g., `[("X-Robots-Tag", "noindex")]`)
+    pub(crate) headers: Vec<(String, String)>,
+    // Format of the route's own `Logger` (e.g., "%a %r %s %Dms")
+    pub(crate) log_format: Option<String>,
+    // Whether the app's `Logger` logs the route, see `generate_log_exclusions!`
+    pub(crate) log: bool,
+    // Path the generated registration references instead of the handler's name
+    // (e.g., "crate::api::v2::search")
+    pub(crate) handler_path: Option<String>,
+    // Types the route's handler extracts as `web::Data<T>`, checked in the app data
+    pub(crate) requires: Vec<syn::Path>,
+    // Path of Actix Web in the code generated next to the handler (e.g., `my_framework::actix_web`)
+    pub(crate) actix_crate: syn::Path,
+}
+
+impl Parse for AutoRegisterArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // Without a prefix, the handler is keyed by its module (e.g., `crate::api::events`
+        // -> "/api/events")
+        let mut errors: Vec<syn::Error> = Vec::new();
+        let prefix = if input.peek(LitStr) {
+            let prefix: LitStr = input.parse()?;
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+            prefix.value()
+        } else {
+            crate::call_site_module_prefix()
+        };
+
+        let mut args = AutoRegisterArgs {
+            prefix,
+            example_response: None,
+            example_request: None,
+            operation_id: None,
+            errors: Vec::new(),
+            generics: None,
+            debug: false,
+            method: None,
+            path: None,
+            ws: false,
+            sse: false,
+            multipart: false,
+            multipart_limit: None,
+            warn_slower_than_ms: None,
+            circuit_breaker: None,
+            canary: None,
+            canary_header: None,
+            variant: None,
+            weight: None,
+            shadow_to: None,
+            flag: None,
+            roles: Vec::new(),
+            api_key: None,
+            csrf_exempt: false,
+            session_required: false,
+            jwt_scopes: Vec::new(),
+            audit: false,
+            idempotent: false,
+            body_limit: None,
+            consumes: None,
+            produces: None,
+            etag: false,
+            headers: Vec::new(),
+            log_format: None,
+            log: true,
+            handler_path: None,
+            requires: Vec::new(),
+            actix_crate: syn::parse_quote!(actix_web),
+        };
+
+        // Where each option was given, for the errors about combining them
+        let mut spans = BTreeMap::new();
+        for option in Punctuated::<Meta, Token![,]>::parse_terminated(input)? {
+            if let Some(ident) = option.path().get_ident() {
+                spans.insert(ident.to_string(), ident.span());
+            }
+            let parsed = if option.path().is_ident("example_response") {
+                string_value(&option).map(|value| args.example_response = Some(value))
+            } else if option.path().is_ident("example_request") {
+                string_value(&option).map(|value| args.example_request = Some(value))
+            } else if option.path().is_ident("operation_id") {
+                operation_id_value(&option).map(|value| args.operation_id = Some(value))
+            } else if option.path().is_ident("errors") {
+                status_codes_value(&option).map(|value| args.errors = value)
+            } else if option.path().is_ident("generics") {
+                generics_value(&option).map(|value| args.generics = Some(value))
+            } else if option.path().is_ident("debug") {
+                bool_value(&option).map(|value| args.debug = value)
+            } else if option.path().is_ident("method") {
+                method_value(&option).map(|value| args.method = Some(value))
+            } else if option.path().is_ident("path") {
+                string_value(&option).map(|value| args.path = Some(value))
+            } else if option.path().is_ident("ws") {
+                bool_value(&option).map(|value| args.ws = value)
+            } else if option.path().is_ident("sse") {
+                bool_value(&option).map(|value| args.sse = value)
+            } else if option.path().is_ident("multipart") {
+                multipart_value(&option).map(|limit| {
+                    args.multipart = true;
+                    args.multipart_limit = limit;
+                })
+            } else if option.path().is_ident("warn_slower_than_ms") {
+                millis_value(&option).map(|value| args.warn_slower_than_ms = Some(value))
+            } else if option.path().is_ident("circuit_breaker") {
+                circuit_breaker_value(&option).map(|value| args.circuit_breaker = Some(value))
+            } else if option.path().is_ident("canary") {
+                percent_value(&option).map(|value| args.canary = Some(value))
+            } else if option.path().is_ident("canary_header") {
+                string_value(&option).map(|value| args.canary_header = Some(value))
+            } else if option.path().is_ident("variant") {
+                string_value(&option).map(|value| args.variant = Some(value))
+            } else if option.path().is_ident("weight") {
+                weight_value(&option).map(|value| args.weight = Some(value))
+            } else if option.path().is_ident("shadow_to") {
+                path_value(&option).map(|value| args.shadow_to = Some(value))
+            } else if option.path().is_ident("flag") {
+                string_value(&option).map(|value| args.flag = Some(value))
+            } else if option.path().is_ident("roles") {
+                names_value(
+                    &option,
+                    "Expected a non-empty array of role names (e.g., `roles = [\"admin\", \"editor\"]`).",
+                )
+                .map(|value| args.roles = value)
+            } else if option.path().is_ident("api_key") {
+                string_value(&option).map(|value| args.api_key = Some(value))
+            } else if option.path().is_ident("csrf_exempt") {
+                bool_value(&option).map(|value| args.csrf_exempt = value)
+            } else if option.path().is_ident("session") {
+                session_value(&option).map(|()| args.session_required = true)
+            } else if option.path().is_ident("jwt_scopes") {
+                names_value(
+                    &option,
+                    "Expected a non-empty array of scopes (e.g., `jwt_scopes = [\"events:read\"]`).",
+                )
+                .map(|value| args.jwt_scopes = value)
+            } else if option.path().is_ident("audit") {
+                bool_value(&option).map(|value| args.audit = value)
+            } else if option.path().is_ident("idempotent") {
+                bool_value(&option).map(|value| args.idempotent = value)
+            } else if option.path().is_ident("body_limit") {
+                size_value(&option).map(|value| args.body_limit = Some(value))
+            } else if option.path().is_ident("consumes") {
+                media_type_value(&option).map(|value| args.consumes = Some(value))
+            } else if option.path().is_ident("produces") {
+                media_type_value(&option).map(|value| args.produces = Some(value))
+            } else if option.path().is_ident("etag") {
+                bool_value(&option).map(|value| args.etag = value)
+            } else if option.path().is_ident("headers") {
+                headers_value(&option).map(|value| args.headers = value)
+            } else if option.path().is_ident("log_format") {
+                string_value(&option).map(|value| args.log_format = Some(value))
+            } else if option.path().is_ident("log") {
+                bool_value(&option).map(|value| args.log = value)
+            } else if option.path().is_ident("handler_path") {
+                handler_path_value(&option).map(|value| args.handler_path = Some(value))
+            } else if option.path().is_ident("requires") {
+                match &option {
+                    Meta::NameValue(name_value) => {
+                        requires_expr(&name_value.value, false).map(|value| {
+                            args.requires = value.into_iter().map(|(path, _)| path).collect()
+                        })
+                    }
+                    _ => Err(syn::Error::new_spanned(&option, REQUIRES_MESSAGE)),
+                }
+            } else if option.path().is_ident("crate") {
+                crate_value(&option).map(|value| args.actix_crate = value)
+            } else {
+                Err(syn::Error::new_spanned(
+                    option.path(),
+                    "Unknown auto_register option, expected `example_response`, `example_request`, `operation_id`, `errors`, `generics`, `debug`, `method`, `path`, `ws`, `sse`, `multipart`, `warn_slower_than_ms`, `circuit_breaker`, `canary`, `canary_header`, `variant`, `weight`, `shadow_to`, `flag`, `roles`, `api_key`, `csrf_exempt`, `session`, `jwt_scopes`, `audit`, `idempotent`, `body_limit`, `consumes`, `produces`, `etag`, `headers`, `log_format`, `log`, `handler_path`, `requires` or `crate`.",
+                ))
+            };
+            if let Err(error) = parsed {
+                errors.push(error);
+            }
+        }
+
+        if args.canary_header.is_some() && args.canary.is_none() {
+            errors.push(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "`canary_header` only applies to canary handlers, add `canary = <percent>`.",
+            ));
+        }
+
+        if args.weight.is_some() && args.variant.is_none() {
+            errors.push(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "`weight` only applies to A/B variants, add `variant = \"A\"`.",
+            ));
+        }
+
+        if args.variant.is_some() && args.canary.is_some() {
+            errors.push(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "A route cannot be both a canary (`canary = ...`) and an A/B variant (`variant = ...`).",
+            ));
+        }
+
+        // Flags, access checks, audits and the other per-route behaviors apply to
+        // the route's own index, which canaries share with their stable handler,
+        // and variants and negotiated handlers don't have
+        for (option, enabled) in [
+            ("warn_slower_than_ms", args.warn_slower_than_ms.is_some()),
+            ("circuit_breaker", args.circuit_breaker.is_some()),
+            ("flag", args.flag.is_some()),
+            ("api_key", args.api_key.is_some()),
+            ("session", args.session_required),
+            ("jwt_scopes", !args.jwt_scopes.is_empty()),
+            ("audit", args.audit),
+            ("idempotent", args.idempotent),
+            ("consumes", args.consumes.is_some()),
+            ("etag", args.etag),
+            ("headers", !args.headers.is_empty()),
+        ] {
+            let span = spans
+                .get(option)
+                .copied()
+                .unwrap_or_else(proc_macro2::Span::call_site);
+            if enabled && (args.canary.is_some() || args.variant.is_some()) {
+                errors.push(syn::Error::new(
+                    span,
+                    format!(
+                        "`{}` cannot be combined with `canary` or `variant`, set it on the stable handler of the path instead.",
+                        option
+                    ),
+                ));
+            }
+            if enabled && args.produces.is_some() {
+                errors.push(syn::Error::new(
+                    span,
+                    format!(
+                        "`{}` cannot be combined with `produces`, negotiated handlers share their path pattern with the other representations of the resource.",
+                        option
+                    ),
+                ));
+            }
+        }
+
+        if args.produces.is_some() && (args.canary.is_some() || args.variant.is_some()) {
+            errors.push(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "A negotiated handler (`produces = ...`) cannot be a canary or an A/B variant.",
+            ));
+        }
+
+        if args.consumes.is_some() && args.multipart {
+            errors.push(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "Multipart handlers already consume `multipart/form-data`, remove `consumes`.",
+            ));
+        }
+
+        if args.log_format.is_some() && !args.log {
+            errors.push(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "A route without logging (`log = false`) has no use for `log_format`.",
+            ));
+        }
+
+        if args.etag && args.sse {
+            errors.push(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "An event stream (`sse = true`) never ends and can't be tagged, remove `etag`.",
+            ));
+        }
+
+        if args.ws && args.sse {
+            errors.push(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "A route cannot be both a WebSocket upgrade (`ws = true`) and an event stream (`sse = true`).",
+            ));
+        }
+
+        match crate::combine_errors(errors) {
+            Some(error) => Err(error),
+            None => Ok(args),
+        }
+    }
+}
+
+// Arguments of `auto_register_static!("/prefix", dir = "./static", ...)`
+pub(crate) struct StaticArgs {
+    pub(crate) prefix: LitStr,
+    pub(crate) dir: String,
+    pub(crate) path: String,
+    pub(crate) debug: bool,
+}
+
+impl Parse for StaticArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let prefix: LitStr = input.parse()?;
+        let mut errors: Vec<syn::Error> = Vec::new();
+
+        let mut dir = None;
+        let mut path = String::new();
+        let mut debug = false;
+
+        if !input.is_empty() {
+            input.parse::<Token![,]>()?;
+        }
+
+        for option in Punctuated::<Meta, Token![,]>::parse_terminated(input)? {
+            let parsed = if option.path().is_ident("dir") {
+                string_value(&option).map(|value| dir = Some(value))
+            } else if option.path().is_ident("path") {
+                string_value(&option).map(|value| path = value)
+            } else if option.path().is_ident("debug") {
+                bool_value(&option).map(|value| debug = value)
+            } else {
+                Err(syn::Error::new_spanned(
+                    option.path(),
+                    "Unknown auto_register_static option, expected `dir`, `path` or `debug`.",
+                ))
+            };
+            if let Err(error) = parsed {
+                errors.push(error);
+            }
+        }
+
+        if dir.is_none() {
+            errors.push(syn::Error::new_spanned(
+                &prefix,
+                "auto_register_static needs the directory to serve (e.g., `dir = \"./static\"`).",
+            ));
+        }
+
+        match crate::combine_errors(errors) {
+            Some(error) => Err(error),
+            None => Ok(StaticArgs {
+                prefix,
+                dir: dir.expect("The directory is checked above"),
+                path,
+                debug,
+            }),
+        }
+    }
+}
+
+// Read the string literal of an `option = "value"` argument
+fn string_value(option: &Meta) -> syn::Result<String> {
+    if let Meta::NameValue(syn::MetaNameValue {
+        value:
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(value),
+                ..
+            }),
+        ..
+    }) = option
+    {
+        return Ok(value.value());
+    }
+
+    Err(syn::Error::new_spanned(
+        option,
+        "Expected a string literal (e.g., `option = \"value\"`).",
+    ))
+}
+
+// Read the path of an `option = some::function` argument
+fn path_value(option: &Meta) -> syn::Result<syn::Path> {
+    if let Meta::NameValue(syn::MetaNameValue {
+        value: syn::Expr::Path(syn::ExprPath { path, .. }),
+        ..
+    }) = option
+    {
+        return Ok(path.clone());
+    }
+
+    Err(syn::Error::new_spanned(
+        option,
+        "Expected the path of a function (e.g., `shadow_to = search_v2`).",
+    ))
+}
+
+// Read the path of Actix Web of a `crate = "my_framework::actix_web"` argument
+fn crate_value(option: &Meta) -> syn::Result<syn::Path> {
+    let message = "The value of `crate` must be the path of Actix Web as a string literal (e.g., `crate = \"my_framework::actix_web\"`).";
+    let value = string_value(option).map_err(|_| syn::Error::new_spanned(option, message))?;
+    syn::parse_str(&value).map_err(|_| syn::Error::new_spanned(option, message))
+}
+
+// Read the path of a `handler_path = "crate::api::search"` argument
+fn handler_path_value(option: &Meta) -> syn::Result<String> {
+    let message = "The value of `handler_path` must be the path of the handler as a string literal (e.g., `handler_path = \"crate::api::v2::search\"`).";
+    let value = string_value(option).map_err(|_| syn::Error::new_spanned(option, message))?;
+    match syn::parse_str::<syn::Path>(&value) {
+        Ok(_) => Ok(value),
+        Err(_) => Err(syn::Error::new_spanned(option, message)),
+    }
+}
+
+// Read the OpenAPI operationId of an `operation_id = "listEvents"` argument, kept
+// to the characters client generators turn into method names
+fn operation_id_value(option: &Meta) -> syn::Result<String> {
+    let message = "The value of `operation_id` must be made of letters, digits, `_`, `-` and `.` (e.g., `operation_id = \"listEvents\"`).";
+    let value = string_value(option).map_err(|_| syn::Error::new_spanned(option, message))?;
+    if value.is_empty()
+        || !value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    {
+        return Err(syn::Error::new_spanned(option, message));
+    }
+    Ok(value)
+}
+
+// Read the boolean literal of an `option = true` argument
+fn bool_value(option: &Meta) -> syn::Result<bool> {
+    match option {
+        Meta::NameValue(syn::MetaNameValue { value, .. }) => {
+            bool_expr(value, "Expected a boolean literal (e.g., `option = true`).")
+        }
+        _ => Err(syn::Error::new_spanned(
+            option,
+            "Expected a boolean literal (e.g., `option = true`).",
+        )),
+    }
+}
+
+// HTTP methods a handler can be registered with through `method = "GET"`
+const RESOURCE_METHODS: [&str; 6] = ["GET", "POST", "PUT", "DELETE", "PATCH", "HEAD"];
+
+// Read the HTTP method of a `method = "GET"` argument, in upper case
+fn method_value(option: &Meta) -> syn::Result<String> {
+    let method = string_value(option)?.to_uppercase();
+    if RESOURCE_METHODS.contains(&method.as_str()) {
+        return Ok(method);
+    }
+
+    Err(syn::Error::new_spanned(
+        option,
+        format!(
+            "Unsupported `method`, expected one of {}.",
+            RESOURCE_METHODS.join(", ")
+        ),
+    ))
+}
+
+// Read a `session = required` argument
+fn session_value(option: &Meta) -> syn::Result<()> {
+    if let Meta::NameValue(syn::MetaNameValue {
+        value: syn::Expr::Path(syn::ExprPath { path, .. }),
+        ..
+    }) = option
+    {
+        if path.is_ident("required") {
+            return Ok(());
+        }
+    }
+
+    Err(syn::Error::new_spanned(
+        option,
+        "Expected `session = required`.",
+    ))
+}
+
+// Read a `multipart` or `multipart(limit = "25MB")` argument, returning the limit
+fn multipart_value(option: &Meta) -> syn::Result<Option<String>> {
+    let list = match option {
+        Meta::Path(_) => return Ok(None),
+        Meta::List(list) => list,
+        Meta::NameValue(_) => {
+            return Err(syn::Error::new_spanned(
+                option,
+                "Expected `multipart` or `multipart(limit = \"25MB\")`.",
+            ))
+        }
+    };
+
+    let mut limit = None;
+    for nested in list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)? {
+        if !nested.path().is_ident("limit") {
+            return Err(syn::Error::new_spanned(
+                nested.path(),
+                "Unknown multipart option, expected `limit`.",
+            ));
+        }
+        limit = Some(size_value(&nested)?);
+    }
+    Ok(limit)
+}
+
+// Read the media type of a `consumes = "application/json"` or `produces = "text/html"` argument
+fn media_type_value(option: &Meta) -> syn::Result<String> {
+    let value = string_value(option)?;
+    match value.split_once('/') {
+        Some((kind, subtype))
+            if !kind.is_empty() && !subtype.is_empty() && !value.contains([';', ' ']) =>
+        {
+            Ok(value.to_ascii_lowercase())
+        }
+        _ => Err(syn::Error::new_spanned(
+            option,
+            format!(
+                "Expected a media type without parameters (e.g., `{} = \"application/json\"`).",
+                option
+                    .path()
+                    .get_ident()
+                    .map(|ident| ident.to_string())
+                    .unwrap_or_default()
+            ),
+        )),
+    }
+}
+
+// Read the size of an `option = "25MB"` argument
+fn size_value(option: &Meta) -> syn::Result<String> {
+    let value = string_value(option)?;
+    if parse_size(&value).is_none() {
+        return Err(syn::Error::new_spanned(
+            option,
+            format!(
+                "Expected a size in bytes, optionally with a KB, MB or GB unit (e.g., `{} = \"25MB\"`).",
+                option.path().get_ident().map(|ident| ident.to_string()).unwrap_or_default()
+            ),
+        ));
+    }
+    Ok(value)
+}
+
+// Convert a size such as "512", "64KB" or "25MB" into bytes (units are powers of 1024)
+pub(crate) fn parse_size(size: &str) -> Option<usize> {
+    let size = size.trim();
+    let digits = size
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(digits);
+    let multiplier = match unit.trim().to_uppercase().as_str() {
+        "" | "B" => 1,
+        "KB" => 1024,
+        "MB" => 1024 * 1024,
+        "GB" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    number.parse::<usize>().ok()?.checked_mul(multiplier)
+}
+
+// Read a `circuit_breaker(threshold = 5, cooldown = "30s")` argument, returning
+// the threshold and the cooldown in milliseconds
+fn circuit_breaker_value(option: &Meta) -> syn::Result<(u32, u64)> {
+    let message = "Expected `circuit_breaker(threshold = 5, cooldown = \"30s\")`.";
+    let Meta::List(list) = option else {
+        return Err(syn::Error::new_spanned(option, message));
+    };
+
+    let mut threshold = None;
+    let mut cooldown = None;
+    for nested in list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)? {
+        if nested.path().is_ident("threshold") {
+            let value = match &nested {
+                Meta::NameValue(syn::MetaNameValue {
+                    value:
+                        syn::Expr::Lit(syn::ExprLit {
+                            lit: syn::Lit::Int(value),
+                            ..
+                        }),
+                    ..
+                }) => value.base10_parse::<u32>()?,
+                _ => return Err(syn::Error::new_spanned(&nested, message)),
+            };
+            if value == 0 {
+                return Err(syn::Error::new_spanned(
+                    &nested,
+                    "The circuit breaker `threshold` must be at least 1.",
+                ));
+            }
+            threshold = Some(value);
+        } else if nested.path().is_ident("cooldown") {
+            let value = string_value(&nested)?;
+            cooldown = Some(parse_duration_ms(&value).ok_or_else(|| {
+                syn::Error::new_spanned(
+                    &nested,
+                    "Expected a duration with a ms, s, m or h unit (e.g., `cooldown = \"30s\"`).",
+                )
+            })?);
+        } else {
+            return Err(syn::Error::new_spanned(
+                nested.path(),
+                "Unknown circuit_breaker option, expected `threshold` or `cooldown`.",
+            ));
+        }
+    }
+
+    match (threshold, cooldown) {
+        (Some(threshold), Some(cooldown)) => Ok((threshold, cooldown)),
+        _ => Err(syn::Error::new_spanned(option, message)),
+    }
+}
+
+// Convert a duration such as "500ms", "30s", "5m" or "1h" into milliseconds
+fn parse_duration_ms(duration: &str) -> Option<u64> {
+    let duration = duration.trim();
+    let digits = duration
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(duration.len());
+    let (number, unit) = duration.split_at(digits);
+    let multiplier = match unit.trim() {
+        "ms" => 1,
+        "s" => 1000,
+        "m" => 60 * 1000,
+        "h" => 60 * 60 * 1000,
+        _ => return None,
+    };
+    number.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+// Read the percentage of an `option = 10` argument, between 1 and 99
+fn percent_value(option: &Meta) -> syn::Result<u8> {
+    if let Meta::NameValue(syn::MetaNameValue {
+        value:
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Int(percent),
+                ..
+            }),
+        ..
+    }) = option
+    {
+        if let Ok(percent @ 1..=99) = percent.base10_parse::<u8>() {
+            return Ok(percent);
+        }
+    }
+
+    Err(syn::Error::new_spanned(
+        option,
+        "Expected a percentage between 1 and 99 (e.g., `canary = 10`).",
+    ))
+}
+
+// Read the positive integer of a `weight = 3` argument
+fn weight_value(option: &Meta) -> syn::Result<u32> {
+    if let Meta::NameValue(syn::MetaNameValue {
+        value:
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Int(weight),
+                ..
+            }),
+        ..
+    }) = option
+    {
+        if let Ok(weight @ 1..) = weight.base10_parse::<u32>() {
+            return Ok(weight);
+        }
+    }
+
+    Err(syn::Error::new_spanned(
+        option,
+        "Expected a positive weight (e.g., `weight = 3`).",
+    ))
+}
+
+// Read the positive integer of a `rate = 50` argument
+fn rate_value(option: &Meta) -> syn::Result<u32> {
+    if let Meta::NameValue(syn::MetaNameValue {
+        value:
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Int(rate),
+                ..
+            }),
+        ..
+    }) = option
+    {
+        if let Ok(rate @ 1..) = rate.base10_parse::<u32>() {
+            return Ok(rate);
+        }
+    }
+
+    Err(syn::Error::new_spanned(
+        option,
+        "Expected a positive number of requests per second (e.g., `rate = 50`).",
+    ))
+}
+
+// Read the integer of an `option = 500` argument
+fn millis_value(option: &Meta) -> syn::Result<u64> {
+    match option {
+        Meta::NameValue(syn::MetaNameValue { value, .. }) => millis_expr(value),
+        _ => Err(syn::Error::new_spanned(
+            option,
+            "Expected a number of milliseconds (e.g., `warn_slower_than_ms = 500`).",
+        )),
+    }
+}
+
+// Read a number of milliseconds option value
+fn millis_expr(expr: &syn::Expr) -> syn::Result<u64> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(millis),
+            ..
+        }) => millis.base10_parse(),
+        _ => Err(syn::Error::new_spanned(
+            expr,
+            "Expected a number of milliseconds (e.g., `warn_slower_than_ms = 500`).",
+        )),
+    }
+}
+
+// Read the generic arguments of a `generics = "<PgStore>"` argument
+fn generics_value(option: &Meta) -> syn::Result<String> {
+    let generics = string_value(option)?;
+    match syn::parse_str::<syn::AngleBracketedGenericArguments>(&generics) {
+        Ok(_) => Ok(generics),
+        Err(_) => Err(syn::Error::new_spanned(
+            option,
+            "Expected generic arguments between angle brackets (e.g., `generics = \"<PgStore>\"`).",
+        )),
+    }
+}
+
+// Read the HTTP status codes of an `option = [400, 404]` argument
+fn status_codes_value(option: &Meta) -> syn::Result<Vec<u16>> {
+    let Meta::NameValue(syn::MetaNameValue {
+        value: syn::Expr::Array(array),
+        ..
+    }) = option
+    else {
+        return Err(syn::Error::new_spanned(
+            option,
+            "Expected an array of status codes (e.g., `errors = [400, 404]`).",
+        ));
+    };
+
+    array
+        .elems
+        .iter()
+        .map(|elem| match elem {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Int(code),
+                ..
+            }) => match code.base10_parse::<u16>() {
+                Ok(code @ 100..=599) => Ok(code),
+                _ => Err(syn::Error::new_spanned(
+                    code,
+                    "Expected an HTTP status code between 100 and 599.",
+                )),
+            },
+            _ => Err(syn::Error::new_spanned(
+                elem,
+                "Expected an integer status code (e.g., `404`).",
+            )),
+        })
+        .collect()
+}
+
+// Read the names of an `option = ["admin", "editor"]` argument
+fn names_value(option: &Meta, message: &str) -> syn::Result<Vec<String>> {
+    let Meta::NameValue(syn::MetaNameValue {
+        value: syn::Expr::Array(array),
+        ..
+    }) = option
+    else {
+        return Err(syn::Error::new_spanned(option, message));
+    };
+
+    let names = array
+        .elems
+        .iter()
+        .map(|elem| match string_expr(elem, message)? {
+            name if name.trim().is_empty() => Err(syn::Error::new_spanned(elem, message)),
+            name => Ok(name),
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    if names.is_empty() {
+        return Err(syn::Error::new_spanned(option, message));
+    }
+    Ok(names)
+}
+
+// Read the `(name, value)` pairs of a `headers = [("X-Robots-Tag", "noindex")]` argument
+fn headers_value(option: &Meta) -> syn::Result<Vec<(String, String)>> {
+    let message =
+        "Expected a non-empty array of header names and values (e.g., `headers = [(\"X-Robots-Tag\", \"noindex\")]`).";
+    let Meta::NameValue(syn::MetaNameValue {
+        value: syn::Expr::Array(array),
+        ..
+    }) = option
+    else {
+        return Err(syn::Error::new_spanned(option, message));
+    };
+
+    let headers = array
+        .elems
+        .iter()
+        .map(|elem| {
+            let syn::Expr::Tuple(tuple) = elem else {
+                return Err(syn::Error::new_spanned(elem, message));
+            };
+            let parts = tuple
+                .elems
+                .iter()
+                .map(|part| string_expr(part, message))
+                .collect::<syn::Result<Vec<_>>>()?;
+            let [name, value] = <[String; 2]>::try_from(parts)
+                .map_err(|_| syn::Error::new_spanned(elem, message))?;
+            // Header names are tokens, and values visible ASCII or spaces
+            let valid_name = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c));
+            if !valid_name {
+                return Err(syn::Error::new_spanned(
+                    elem,
+                    format!("`{}` is not a valid header name.", name),
+                ));
+            }
+            if !value.chars().all(|c| c == ' ' || c.is_ascii_graphic()) {
+                return Err(syn::Error::new_spanned(
+                    elem,
+                    format!("The value of the `{}` header must be visible ASCII.", name),
+                ));
+            }
+            Ok((name, value))
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    if headers.is_empty() {
+        return Err(syn::Error::new_spanned(option, message));
+    }
+    Ok(headers)
+}
+
+// Arguments of `generate_register_service!(["/prefix", option = value, ...])`
+pub(crate) struct RegisterServiceArgs {
+    pub(crate) module_key: String,
+    pub(crate) use_scope: bool,
+    // Path of the Actix Web crate in generated code, for renamed or re-exported dependencies
+    pub(crate) actix_crate: syn::Path,
+    pub(crate) debug: bool,
+    pub(crate) doc_hidden: bool,
+    // Dynamic path segment prepended to every scope (e.g., "/{tenant}")
+    pub(crate) tenant_segment: Option<String>,
+    // Locales every scope is served under, captured as the `locale` path parameter
+    pub(crate) locales: Vec<String>,
+    // Host the scopes answer for, through a `Host` guard (e.g., "api.example.com")
+    pub(crate) host: Option<String>,
+    // Environment variable read at registration time to nest every scope under a base path
+    pub(crate) base_path_env: Option<String>,
+    // Prefix placeholders replaced by the values given to `register_service` (e.g., "version")
+    pub(crate) prefix_vars: Vec<String>,
+    // Predicate called with each scope's prefix when registering, skipping the scope when false
+    pub(crate) enabled_if: Option<syn::Expr>,
+    // Shared `AtomicBool` turning the scopes into 503 responses while set
+    pub(crate) maintenance: Option<syn::Expr>,
+    // Seconds sent in the `Retry-After` header of maintenance responses
+    pub(crate) retry_after: u32,
+    // Guard every scope admits admins through, marking the routes as admin-only.
+    // Only a guard: the requests it turns down fall through, to a 404 by default
+    pub(crate) admin: Option<syn::Expr>,
+    // Redirect the plaintext GET and HEAD requests of the scopes to HTTPS, and reject the others
+    pub(crate) require_https: bool,
+    // Generate `RouteToggles` to disable routes at runtime
+    pub(crate) toggles: bool,
+    // Count the requests of every route, read with `route_hit_counts()`
+    pub(crate) hit_counts: bool,
+    // Log the requests slower than this many milliseconds, unless the route sets its own threshold
+    pub(crate) warn_slower_than_ms: Option<u64>,
+    // File or directory whose handlers are registered as found in the sources,
+    // instead of those stored by `auto_register`
+    pub(crate) scan: Option<String>,
+    // Struct implementing the runtime crate's `Registrable` through `register_service`
+    pub(crate) registrable: Option<syn::Ident>,
+    // Types every route of the key extracts as `web::Data<T>`, with the value
+    // added to the app data when the app doesn't provide one, if any
+    pub(crate) requires: Vec<(syn::Path, Option<syn::Expr>)>,
+}
+
+impl Parse for RegisterServiceArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // The brackets may be left out (e.g., `generate_register_service!(scan = "src/api")`)
+        let array: syn::ExprArray = if input.peek(syn::token::Bracket) {
+            input.parse()?
+        } else {
+            syn::ExprArray {
+                attrs: Vec::new(),
+                bracket_token: Default::default(),
+                elems: Punctuated::parse_terminated(input)?,
+            }
+        };
+        let mut elems = array.elems.iter().peekable();
+        let explicit_key = matches!(
+            elems.peek(),
+            Some(syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(_),
+                ..
+            }))
+        );
+
+        // Without a key, the handlers of the invoking module are registered, as
+        // keyed by `auto_register` without a prefix
+        let module_key = match elems.peek() {
+            Some(syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(lit_str),
+                ..
+            })) => {
+                elems.next();
+                lit_str.value()
+            }
+            Some(syn::Expr::Assign(_)) | None => crate::call_site_module_prefix(),
+            Some(_) => return Err(syn::Error::new_spanned(
+                &array,
+                "Expected the first argument to be a string literal representing the module key, or an option to key by the invoking module.",
+            )),
+        };
+
+        let mut args = RegisterServiceArgs {
+            module_key,
+            use_scope: false, // Default to not using the prefix as the scope
+            actix_crate: syn::parse_quote!(actix_web),
+            debug: false,
+            doc_hidden: false,
+            tenant_segment: None,
+            locales: Vec::new(),
+            host: None,
+            base_path_env: None,
+            prefix_vars: Vec::new(),
+            enabled_if: None,
+            maintenance: None,
+            retry_after: 60,
+            require_https: false,
+            admin: None,
+            toggles: false,
+            hit_counts: false,
+            warn_slower_than_ms: None,
+            scan: None,
+            registrable: None,
+            requires: Vec::new(),
+        };
+
+        let mut errors = Vec::new();
+        for elem in elems {
+            let syn::Expr::Assign(syn::ExprAssign { left, right, .. }) = elem else {
+                errors.push(syn::Error::new_spanned(
+                    elem,
+                    "Expected an option assignment (e.g., `use_scope = true`).",
+                ));
+                continue;
+            };
+            let syn::Expr::Path(option) = &**left else {
+                errors.push(syn::Error::new_spanned(left, "Expected an option name."));
+                continue;
+            };
+
+            let parsed = if option.path.is_ident("use_scope") {
+                bool_expr(right, "The value of `use_scope` must be a boolean.")
+                    .map(|value| args.use_scope = value)
+            } else if option.path.is_ident("crate") {
+                string_expr(
+                    right,
+                    "The value of `crate` must be a string literal (e.g., `crate = \"my_framework::actix_web\"`).",
+                )
+                .and_then(|path| {
+                    syn::parse_str(&path).map_err(|_| {
+                        syn::Error::new_spanned(right, "The value of `crate` must be a valid path.")
+                    })
+                })
+                .map(|path| args.actix_crate = path)
+            } else if option.path.is_ident("debug") {
+                bool_expr(right, "The value of `debug` must be a boolean.")
+                    .map(|value| args.debug = value)
+            } else if option.path.is_ident("doc_hidden") {
+                bool_expr(right, "The value of `doc_hidden` must be a boolean.")
+                    .map(|value| args.doc_hidden = value)
+            } else if option.path.is_ident("tenant_segment") {
+                string_expr(
+                    right,
+                    "The value of `tenant_segment` must be a string literal (e.g., `tenant_segment = \"{tenant}\"`).",
+                )
+                .and_then(|segment| {
+                    let segment = segment.trim_matches('/');
+                    if segment.is_empty() || segment.contains('/') {
+                        return Err(syn::Error::new_spanned(
+                            right,
+                            "The value of `tenant_segment` must be a single path segment (e.g., `\"{tenant}\"`).",
+                        ));
+                    }
+                    Ok(format!("/{}", segment))
+                })
+                .map(|segment| args.tenant_segment = Some(segment))
+            } else if option.path.is_ident("locales") {
+                locales_expr(right).map(|locales| args.locales = locales)
+            } else if option.path.is_ident("host") {
+                string_expr(
+                    right,
+                    "The value of `host` must be a string literal (e.g., `host = \"api.example.com\"`).",
+                )
+                .map(|host| args.host = Some(host))
+            } else if option.path.is_ident("base_path_env") {
+                string_expr(
+                    right,
+                    "The value of `base_path_env` must be a string literal (e.g., `base_path_env = \"APP_BASE_PATH\"`).",
+                )
+                .map(|name| args.base_path_env = Some(name))
+            } else if option.path.is_ident("prefix_vars") {
+                prefix_vars_expr(right).map(|names| args.prefix_vars = names)
+            } else if option.path.is_ident("enabled_if") {
+                args.enabled_if = Some((**right).clone());
+                Ok(())
+            } else if option.path.is_ident("maintenance") {
+                args.maintenance = Some((**right).clone());
+                Ok(())
+            } else if option.path.is_ident("retry_after") {
+                match &**right {
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Int(seconds),
+                        ..
+                    }) => seconds.base10_parse().map(|seconds| args.retry_after = seconds),
+                    _ => Err(syn::Error::new_spanned(
+                        right,
+                        "The value of `retry_after` must be a number of seconds (e.g., `retry_after = 120`).",
+                    )),
+                }
+            } else if option.path.is_ident("admin") {
+                args.admin = Some((**right).clone());
+                Ok(())
+            } else if option.path.is_ident("require_https") {
+                bool_expr(right, "The value of `require_https` must be a boolean.")
+                    .map(|value| args.require_https = value)
+            } else if option.path.is_ident("toggles") {
+                bool_expr(right, "The value of `toggles` must be a boolean.")
+                    .map(|value| args.toggles = value)
+            } else if option.path.is_ident("hit_counts") {
+                bool_expr(right, "The value of `hit_counts` must be a boolean.")
+                    .map(|value| args.hit_counts = value)
+            } else if option.path.is_ident("warn_slower_than_ms") {
+                millis_expr(right).map(|value| args.warn_slower_than_ms = Some(value))
+            } else if option.path.is_ident("scan") {
+                string_expr(
+                    right,
+                    "The value of `scan` must be a string literal (e.g., `scan = \"src/api\"`).",
+                )
+                .map(|path| args.scan = Some(path))
+            } else if option.path.is_ident("requires") {
+                requires_expr(right, true).map(|value| args.requires = value)
+            } else if option.path.is_ident("registrable") {
+                match &**right {
+                    syn::Expr::Path(path) if path.path.get_ident().is_some() => {
+                        args.registrable = path.path.get_ident().cloned();
+                        Ok(())
+                    }
+                    _ => Err(syn::Error::new_spanned(
+                        right,
+                        "The value of `registrable` must be the name of the struct to define (e.g., `registrable = EventsRoutes`).",
+                    )),
+                }
+            } else {
+                Err(syn::Error::new_spanned(
+                    left,
+                    "Unknown generate_register_service option, expected `use_scope`, `crate`, `debug`, `doc_hidden`, `tenant_segment`, `locales`, `host`, `base_path_env`, `prefix_vars`, `enabled_if`, `maintenance`, `retry_after`, `admin`, `require_https`, `toggles`, `hit_counts`, `warn_slower_than_ms`, `scan`, `registrable` or `requires`.",
+                ))
+            };
+            if let Err(error) = parsed {
+                errors.push(error);
+            }
+        }
+
+        // `Registrable::register` only passes the configuration
+        if !args.prefix_vars.is_empty() && args.registrable.is_some() {
+            errors.push(syn::Error::new_spanned(
+                &array,
+                "`registrable` can't be combined with `prefix_vars`, `register_service` needs the values of the placeholders.",
+            ));
+        }
+
+        // Without the scope, the prefix is not part of the registered paths
+        if !args.prefix_vars.is_empty() && !args.use_scope {
+            errors.push(syn::Error::new_spanned(
+                &array,
+                "`prefix_vars` requires `use_scope = true`, the placeholders are resolved in the scope path.",
+            ));
+        }
+
+        // Scanned handlers are registered as declared, without the registry the
+        // scope options work from
+        if args.scan.is_some() {
+            if explicit_key {
+                errors.push(syn::Error::new_spanned(
+                    &array,
+                    "`scan` registers the handlers found in the sources, remove the module key.",
+                ));
+            }
+            for (option, enabled) in [
+                ("use_scope", args.use_scope),
+                ("tenant_segment", args.tenant_segment.is_some()),
+                ("locales", !args.locales.is_empty()),
+                ("host", args.host.is_some()),
+                ("base_path_env", args.base_path_env.is_some()),
+                ("prefix_vars", !args.prefix_vars.is_empty()),
+                ("enabled_if", args.enabled_if.is_some()),
+                ("maintenance", args.maintenance.is_some()),
+                ("admin", args.admin.is_some()),
+                ("require_https", args.require_https),
+                ("toggles", args.toggles),
+                ("hit_counts", args.hit_counts),
+                ("warn_slower_than_ms", args.warn_slower_than_ms.is_some()),
+                ("requires", !args.requires.is_empty()),
+            ] {
+                if enabled {
+                    errors.push(syn::Error::new_spanned(
+                        &array,
+                        format!(
+                            "`{}` can't be combined with `scan`, scanned handlers are registered as declared.",
+                            option
+                        ),
+                    ));
+                }
+            }
+        }
+
+        // Middlewares are built with `middleware::from_fn`, added in actix-web 4
+        if cfg!(feature = "actix3") && cfg!(feature = "otel") {
+            errors.push(syn::Error::new_spanned(
+                &array,
+                "The `otel` feature needs actix-web 4 and can't be combined with the `actix3` feature.",
+            ));
+        }
+        if cfg!(feature = "actix3") {
+            for (option, enabled) in [
+                ("maintenance", args.maintenance.is_some()),
+                ("require_https", args.require_https),
+                ("toggles", args.toggles),
+                ("hit_counts", args.hit_counts),
+                ("warn_slower_than_ms", args.warn_slower_than_ms.is_some()),
+                ("requires", !args.requires.is_empty()),
+            ] {
+                if enabled {
+                    errors.push(syn::Error::new_spanned(
+                        &array,
+                        format!(
+                            "The `{}` option needs actix-web 4 and is not available with the `actix3` feature.",
+                            option
+                        ),
+                    ));
+                }
+            }
+        }
+
+        match crate::combine_errors(errors) {
+            Some(error) => Err(error),
+            None => Ok(args),
+        }
+    }
+}
+
+// Arguments of `generate_list_routes!(option = value, ...)`
+pub(crate) struct ListRoutesArgs {
+    pub(crate) doc_hidden: bool,
+    pub(crate) badges: bool, // Add a column of badges flagging the routes to audit
+    pub(crate) grouped: bool, // Show each scope in a row of its own above its routes
+    pub(crate) overflow: Option<TableOverflow>, // Fit the table to the terminal width
+    pub(crate) max_width: Option<usize>, // Width used when `COLUMNS` is not set
+    pub(crate) startup: Option<StartupBanner>, // Generate `on_startup` printing this
+}
+
+// What the `on_startup` function of `generate_list_routes!(startup = "summary")` prints
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum StartupBanner {
+    Table,
+    Summary,
+}
+
+// How `generate_list_routes!(overflow = "wrap")` fits a table wider than the terminal
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum TableOverflow {
+    Truncate,
+    Wrap,
+}
+
+impl Parse for ListRoutesArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = ListRoutesArgs {
+            doc_hidden: false,
+            badges: false,
+            grouped: false,
+            overflow: None,
+            max_width: None,
+            startup: None,
+        };
+
+        let mut errors = Vec::new();
+        for option in Punctuated::<Meta, Token![,]>::parse_terminated(input)? {
+            let parsed = if option.path().is_ident("doc_hidden") {
+                bool_value(&option).map(|value| args.doc_hidden = value)
+            } else if option.path().is_ident("badges") {
+                bool_value(&option).map(|value| args.badges = value)
+            } else if option.path().is_ident("grouped") {
+                bool_value(&option).map(|value| args.grouped = value)
+            } else if option.path().is_ident("overflow") {
+                string_value(&option)
+                    .and_then(|value| match value.as_str() {
+                        "truncate" => Ok(TableOverflow::Truncate),
+                        "wrap" => Ok(TableOverflow::Wrap),
+                        _ => Err(syn::Error::new_spanned(
+                            &option,
+                            "The table `overflow` must be \"truncate\" or \"wrap\".",
+                        )),
+                    })
+                    .map(|overflow| args.overflow = Some(overflow))
+            } else if option.path().is_ident("max_width") {
+                width_value(&option).map(|width| args.max_width = Some(width))
+            } else if option.path().is_ident("startup") {
+                string_value(&option)
+                    .and_then(|value| match value.as_str() {
+                        "table" => Ok(StartupBanner::Table),
+                        "summary" => Ok(StartupBanner::Summary),
+                        _ => Err(syn::Error::new_spanned(
+                            &option,
+                            "The `startup` banner must be \"table\" or \"summary\".",
+                        )),
+                    })
+                    .map(|startup| args.startup = Some(startup))
+            } else {
+                Err(syn::Error::new_spanned(
+                    option.path(),
+                    "Unknown generate_list_routes option, expected `doc_hidden`, `badges`, `grouped`, `overflow`, `max_width` or `startup`.",
+                ))
+            };
+            if let Err(error) = parsed {
+                errors.push(error);
+            }
+        }
+
+        // A fallback width has nothing to apply to without an overflow mode
+        if args.max_width.is_some() && args.overflow.is_none() {
+            errors.push(syn::Error::new(
+                proc_macro2::Span::call_site(),
+                "`max_width` requires `overflow = \"truncate\"` or `overflow = \"wrap\"`.",
+            ));
+        }
+
+        match crate::combine_errors(errors) {
+            Some(error) => Err(error),
+            None => Ok(args),
+        }
+    }
+}
+
+// Read the number of columns of a `max_width = 120` argument, wide enough to
+// keep a few characters per cell
+fn width_value(option: &Meta) -> syn::Result<usize> {
+    if let Meta::NameValue(syn::MetaNameValue {
+        value:
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Int(width),
+                ..
+            }),
+        ..
+    }) = option
+    {
+        if let Ok(width @ 20..) = width.base10_parse::<usize>() {
+            return Ok(width);
+        }
+    }
+
+    Err(syn::Error::new_spanned(
+        option,
+        "Expected a width of at least 20 columns (e.g., `max_width = 120`).",
+    ))
+}
+
+// Read a boolean literal option value
+fn bool_expr(expr: &syn::Expr, message: &str) -> syn::Result<bool> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Bool(lit_bool),
+            ..
+        }) => Ok(lit_bool.value()),
+        _ => Err(syn::Error::new_spanned(expr, message)),
+    }
+}
+
+// Read the locale codes of a `locales = ["en", "fr"]` option value
+fn locales_expr(expr: &syn::Expr) -> syn::Result<Vec<String>> {
+    let message = "The value of `locales` must be an array of locale codes (e.g., `locales = [\"en\", \"fr\"]`).";
+    let syn::Expr::Array(array) = expr else {
+        return Err(syn::Error::new_spanned(expr, message));
+    };
+
+    let locales = array
+        .elems
+        .iter()
+        .map(|elem| {
+            let locale = string_expr(elem, message)?;
+            // Locales end up in a path regex, so only plain codes are accepted
+            if locale.is_empty()
+                || !locale
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(syn::Error::new_spanned(
+                    elem,
+                    "Locale codes may only contain letters, digits, `-` and `_` (e.g., \"en-GB\").",
+                ));
+            }
+            Ok(locale)
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    if locales.is_empty() {
+        return Err(syn::Error::new_spanned(expr, message));
+    }
+    Ok(locales)
+}
+
+// Read the names of the prefix placeholders resolved when registering
+fn prefix_vars_expr(expr: &syn::Expr) -> syn::Result<Vec<String>> {
+    let message = "The value of `prefix_vars` must be an array of placeholder names (e.g., `prefix_vars = [\"version\"]`).";
+    let syn::Expr::Array(array) = expr else {
+        return Err(syn::Error::new_spanned(expr, message));
+    };
+
+    let names = array
+        .elems
+        .iter()
+        .map(|elem| {
+            let name = string_expr(elem, message)?;
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(syn::Error::new_spanned(
+                    elem,
+                    "Placeholder names may only contain letters, digits and `_` (e.g., \"version\" for `{version}`).",
+                ));
+            }
+            Ok(name)
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    if names.is_empty() {
+        return Err(syn::Error::new_spanned(expr, message));
+    }
+    Ok(names)
+}
+
+const REQUIRES_MESSAGE: &str =
+    "Expected a non-empty array of app data types (e.g., `requires = [AppState, DbPool]`).";
+
+// Read the types of a `requires = [AppState, Settings = Settings::default()]` value,
+// with the default value of each type when `defaults` are allowed
+fn requires_expr(
+    expr: &syn::Expr,
+    defaults: bool,
+) -> syn::Result<Vec<(syn::Path, Option<syn::Expr>)>> {
+    let syn::Expr::Array(array) = expr else {
+        return Err(syn::Error::new_spanned(expr, REQUIRES_MESSAGE));
+    };
+
+    let requires = array
+        .elems
+        .iter()
+        .map(|elem| match elem {
+            syn::Expr::Path(syn::ExprPath {
+                qself: None, path, ..
+            }) => Ok((path.clone(), None)),
+            syn::Expr::Assign(syn::ExprAssign { left, right, .. }) if defaults => match &**left {
+                syn::Expr::Path(syn::ExprPath {
+                    qself: None, path, ..
+                }) => Ok((path.clone(), Some((**right).clone()))),
+                _ => Err(syn::Error::new_spanned(left, REQUIRES_MESSAGE)),
+            },
+            syn::Expr::Assign(assign) => Err(syn::Error::new_spanned(
+                assign,
+                "Default app data values are given to generate_register_service!, which registers them for the whole key.",
+            )),
+            _ => Err(syn::Error::new_spanned(elem, REQUIRES_MESSAGE)),
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    if requires.is_empty() {
+        return Err(syn::Error::new_spanned(expr, REQUIRES_MESSAGE));
+    }
+    Ok(requires)
+}
+
+// Read a string literal option value
+fn string_expr(expr: &syn::Expr, message: &str) -> syn::Result<String> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(lit_str),
+            ..
+        }) => Ok(lit_str.value()),
+        _ => Err(syn::Error::new_spanned(expr, message)),
+    }
+}
+
+// OpenAPI version targeted by `generate_openapi!`
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum OpenApiVersion {
+    V3_0,
+    V3_1,
+}
+
+// Output format of `generate_openapi!` and `generate_asyncapi!`
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum OpenApiFormat {
+    Json,
+    Yaml,
+}
+
+// Arguments of `generate_openapi!(version = "3.1", format = "yaml", ...)`
+pub(crate) struct OpenApiArgs {
+    pub(crate) version: OpenApiVersion,
+    pub(crate) format: OpenApiFormat,
+    pub(crate) title: String,
+    pub(crate) api_version: String,
+}
+
+impl Parse for OpenApiArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // Default to the package being compiled for the `info` section
+        let mut args = OpenApiArgs {
+            version: OpenApiVersion::V3_1,
+            format: OpenApiFormat::Json,
+            title: std::env::var("CARGO_PKG_NAME").unwrap_or_else(|_| "API".to_string()),
+            api_version: std::env::var("CARGO_PKG_VERSION").unwrap_or_else(|_| "0.1.0".to_string()),
+        };
+
+        let mut errors = Vec::new();
+        for option in Punctuated::<Meta, Token![,]>::parse_terminated(input)? {
+            let parsed = if option.path().is_ident("version") {
+                string_value(&option)
+                    .and_then(|value| match value.as_str() {
+                        "3.0" => Ok(OpenApiVersion::V3_0),
+                        "3.1" => Ok(OpenApiVersion::V3_1),
+                        _ => Err(syn::Error::new_spanned(
+                            &option,
+                            "The OpenAPI `version` must be \"3.0\" or \"3.1\".",
+                        )),
+                    })
+                    .map(|version| args.version = version)
+            } else if option.path().is_ident("format") {
+                string_value(&option)
+                    .and_then(|value| match value.as_str() {
+                        "json" => Ok(OpenApiFormat::Json),
+                        "yaml" => Ok(OpenApiFormat::Yaml),
+                        _ => Err(syn::Error::new_spanned(
+                            &option,
+                            "The OpenAPI `format` must be \"json\" or \"yaml\".",
+                        )),
+                    })
+                    .map(|format| args.format = format)
+            } else if option.path().is_ident("title") {
+                string_value(&option).map(|value| args.title = value)
+            } else if option.path().is_ident("api_version") {
+                string_value(&option).map(|value| args.api_version = value)
+            } else {
+                Err(syn::Error::new_spanned(
+                    option.path(),
+                    "Unknown generate_openapi option, expected `version`, `format`, `title` or `api_version`.",
+                ))
+            };
+            if let Err(error) = parsed {
+                errors.push(error);
+            }
+        }
+
+        match crate::combine_errors(errors) {
+            Some(error) => Err(error),
+            None => Ok(args),
+        }
+    }
+}
+
+// Arguments of `generate_pact!(consumer = "...", provider = "...")`
+pub(crate) struct PactArgs {
+    pub(crate) consumer: String,
+    pub(crate) provider: String,
+}
+
+impl Parse for PactArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // Default to the package being compiled for the provider
+        let mut args = PactArgs {
+            consumer: "consumer".to_string(),
+            provider: std::env::var("CARGO_PKG_NAME").unwrap_or_else(|_| "provider".to_string()),
+        };
+
+        let mut errors = Vec::new();
+        for option in Punctuated::<Meta, Token![,]>::parse_terminated(input)? {
+            let parsed = if option.path().is_ident("consumer") {
+                string_value(&option).map(|value| args.consumer = value)
+            } else if option.path().is_ident("provider") {
+                string_value(&option).map(|value| args.provider = value)
+            } else {
+                Err(syn::Error::new_spanned(
+                    option.path(),
+                    "Unknown generate_pact option, expected `consumer` or `provider`.",
+                ))
+            };
+            if let Err(error) = parsed {
+                errors.push(error);
+            }
+        }
+
+        match crate::combine_errors(errors) {
+            Some(error) => Err(error),
+            None => Ok(args),
+        }
+    }
+}
+
+// Arguments of `generate_fuzz_targets!` and `generate_path_param_tests!`
+// (e.g., `generate_fuzz_targets!(configure = crate::fuzz::setup)`)
+pub(crate) struct TestAppArgs {
+    // Function configuring the app calling the handlers (e.g., with its `web::Data`)
+    pub(crate) configure: Option<syn::Path>,
+    // Path of Actix Web in the generated code (e.g., `my_framework::actix_web`)
+    pub(crate) actix_crate: syn::Path,
+}
+
+impl Parse for TestAppArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = TestAppArgs {
+            configure: None,
+            actix_crate: syn::parse_quote!(actix_web),
+        };
+
+        let mut errors = Vec::new();
+        for option in Punctuated::<Meta, Token![,]>::parse_terminated(input)? {
+            let parsed = if option.path().is_ident("configure") {
+                path_value(&option).map(|value| args.configure = Some(value))
+            } else if option.path().is_ident("crate") {
+                crate_value(&option).map(|value| args.actix_crate = value)
+            } else {
+                Err(syn::Error::new_spanned(
+                    option.path(),
+                    "Unknown option, expected `configure` or `crate`.",
+                ))
+            };
+            if let Err(error) = parsed {
+                errors.push(error);
+            }
+        }
+
+        match crate::combine_errors(errors) {
+            Some(error) => Err(error),
+            None => Ok(args),
+        }
+    }
+}
+
+// Arguments of the generators whose only option is the path of Actix Web
+// (e.g., `generate_log_exclusions!(crate = "my_framework::actix_web")`)
+pub(crate) struct CrateArgs {
+    pub(crate) actix_crate: syn::Path,
+}
+
+impl Parse for CrateArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = CrateArgs {
+            actix_crate: syn::parse_quote!(actix_web),
+        };
+
+        let mut errors = Vec::new();
+        for option in Punctuated::<Meta, Token![,]>::parse_terminated(input)? {
+            let parsed = if option.path().is_ident("crate") {
+                crate_value(&option).map(|value| args.actix_crate = value)
+            } else {
+                Err(syn::Error::new_spanned(
+                    option.path(),
+                    "Unknown option, expected `crate`.",
+                ))
+            };
+            if let Err(error) = parsed {
+                errors.push(error);
+            }
+        }
+
+        match crate::combine_errors(errors) {
+            Some(error) => Err(error),
+            None => Ok(args),
+        }
+    }
+}
+
+// Arguments of `generate_k6_script!(base_url = "...", rate = 50, duration = "5m")`
+pub(crate) struct K6Args {
+    pub(crate) base_url: String,
+    pub(crate) rate: u32,        // Requests per second of each scenario
+    pub(crate) duration: String, // k6 duration of the scenarios (e.g., "30s")
+}
+
+impl Parse for K6Args {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = K6Args {
+            base_url: "http://localhost:8080".to_string(),
+            rate: 10,
+            duration: "1m".to_string(),
+        };
+
+        let mut errors = Vec::new();
+        for option in Punctuated::<Meta, Token![,]>::parse_terminated(input)? {
+            let parsed = if option.path().is_ident("base_url") {
+                string_value(&option).map(|value| args.base_url = value)
+            } else if option.path().is_ident("rate") {
+                rate_value(&option).map(|value| args.rate = value)
+            } else if option.path().is_ident("duration") {
+                string_value(&option).map(|value| args.duration = value)
+            } else {
+                Err(syn::Error::new_spanned(
+                    option.path(),
+                    "Unknown generate_k6_script option, expected `base_url`, `rate` or `duration`.",
+                ))
+            };
+            if let Err(error) = parsed {
+                errors.push(error);
+            }
+        }
+
+        match crate::combine_errors(errors) {
+            Some(error) => Err(error),
+            None => Ok(args),
+        }
+    }
+}
+
+// Arguments of `generate_insomnia!(name = "...", base_url = "...")`
+pub(crate) struct InsomniaArgs {
+    pub(crate) name: String,
+    pub(crate) base_url: String,
+}
+
+impl Parse for InsomniaArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // Default to the package being compiled for the name of the workspace
+        let mut args = InsomniaArgs {
+            name: std::env::var("CARGO_PKG_NAME").unwrap_or_else(|_| "API".to_string()),
+            base_url: "http://localhost:8080".to_string(),
+        };
+
+        let mut errors = Vec::new();
+        for option in Punctuated::<Meta, Token![,]>::parse_terminated(input)? {
+            let parsed = if option.path().is_ident("name") {
+                string_value(&option).map(|value| args.name = value)
+            } else if option.path().is_ident("base_url") {
+                string_value(&option).map(|value| args.base_url = value)
+            } else {
+                Err(syn::Error::new_spanned(
+                    option.path(),
+                    "Unknown generate_insomnia option, expected `name` or `base_url`.",
+                ))
+            };
+            if let Err(error) = parsed {
+                errors.push(error);
+            }
+        }
+
+        match crate::combine_errors(errors) {
+            Some(error) => Err(error),
+            None => Ok(args),
+        }
+    }
+}
+
+// Arguments of `generate_har!(base_url = "...")`
+pub(crate) struct HarArgs {
+    pub(crate) base_url: String,
+}
+
+impl Parse for HarArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = HarArgs {
+            base_url: "http://localhost:8080".to_string(),
+        };
+
+        let mut errors = Vec::new();
+        for option in Punctuated::<Meta, Token![,]>::parse_terminated(input)? {
+            let parsed = if option.path().is_ident("base_url") {
+                string_value(&option).map(|value| args.base_url = value)
+            } else {
+                Err(syn::Error::new_spanned(
+                    option.path(),
+                    "Unknown generate_har option, expected `base_url`.",
+                ))
+            };
+            if let Err(error) = parsed {
+                errors.push(error);
+            }
+        }
+
+        match crate::combine_errors(errors) {
+            Some(error) => Err(error),
+            None => Ok(args),
+        }
+    }
+}
+
+// Arguments of `generate_asyncapi!(format = "yaml", title = "...", api_version = "...")`
+pub(crate) struct AsyncApiArgs {
+    pub(crate) format: OpenApiFormat,
+    pub(crate) title: String,
+    pub(crate) api_version: String,
+}
+
+impl Parse for AsyncApiArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // Default to the package being compiled for the `info` section
+        let mut args = AsyncApiArgs {
+            format: OpenApiFormat::Json,
+            title: std::env::var("CARGO_PKG_NAME").unwrap_or_else(|_| "API".to_string()),
+            api_version: std::env::var("CARGO_PKG_VERSION").unwrap_or_else(|_| "0.1.0".to_string()),
+        };
+
+        let mut errors = Vec::new();
+        for option in Punctuated::<Meta, Token![,]>::parse_terminated(input)? {
+            let parsed = if option.path().is_ident("format") {
+                string_value(&option)
+                    .and_then(|value| match value.as_str() {
+                        "json" => Ok(OpenApiFormat::Json),
+                        "yaml" => Ok(OpenApiFormat::Yaml),
+                        _ => Err(syn::Error::new_spanned(
+                            &option,
+                            "The AsyncAPI `format` must be \"json\" or \"yaml\".",
+                        )),
+                    })
+                    .map(|format| args.format = format)
+            } else if option.path().is_ident("title") {
+                string_value(&option).map(|value| args.title = value)
+            } else if option.path().is_ident("api_version") {
+                string_value(&option).map(|value| args.api_version = value)
+            } else {
+                Err(syn::Error::new_spanned(
+                    option.path(),
+                    "Unknown generate_asyncapi option, expected `format`, `title` or `api_version`.",
+                ))
+            };
+            if let Err(error) = parsed {
+                errors.push(error);
+            }
+        }
+
+        match crate::combine_errors(errors) {
+            Some(error) => Err(error),
+            None => Ok(args),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use crate::args::AsyncApiArgs;
+use crate::document::Value;
+use crate::openapi::{example_value, openapi_path};
+use crate::{Registry, RouteInfo};
+
+// The message a streaming route sends, or receives for WebSocket routes
+fn message(route: &RouteInfo, content_type: &str) -> Value {
+    let mut message = vec![
+        ("name", Value::str(&route.handler_name)),
+        ("contentType", Value::str(content_type)),
+        (
+            "payload",
+            Value::object(vec![("type", Value::str("string"))]),
+        ),
+    ];
+    if let Some(example) = &route.example_response {
+        message.push((
+            "examples",
+            Value::Array(vec![Value::object(vec![(
+                "payload",
+                example_value(example),
+            )])]),
+        ));
+    }
+    Value::object(message)
+}
+
+// An operation of a channel, described by the doc comments of its handler
+fn operation(route: &RouteInfo, suffix: &str, message: Value) -> Value {
+    let mut operation = vec![(
+        "operationId",
+        Value::str(&format!(
+            "{}{}",
+            route.operation_id.as_deref().unwrap_or(&route.handler_name),
+            suffix
+        )),
+    )];
+    let mut doc_lines = route.docs.lines().filter(|line| !line.is_empty());
+    if let Some(summary) = doc_lines.next() {
+        operation.push(("summary", Value::str(summary)));
+    }
+    operation.push(("message", message));
+    Value::object(operation)
+}
+
+// Build an AsyncAPI (2.6) document with a channel per WebSocket and Server-Sent
+// Events route: clients subscribe to the messages of both, and publish to
+// WebSocket routes
+pub(crate) fn routes_to_asyncapi(map: &Registry, args: &AsyncApiArgs) -> Value {
+    let mut channels = Vec::new();
+    for (scope, routes) in map.iter() {
+        for route in routes {
+            let websocket = route.verb.as_ref() == "WS";
+            if !websocket && !route.sse {
+                continue;
+            }
+
+            let (path, parameters) = openapi_path(&crate::served_pattern(scope, route));
+            let mut channel = Vec::new();
+            if !route.docs.is_empty() {
+                channel.push(("description", Value::str(&route.docs)));
+            }
+            if !parameters.is_empty() {
+                let parameters = parameters.iter().map(|name| {
+                    (
+                        name.clone(),
+                        Value::object(vec![(
+                            "schema",
+                            Value::object(vec![("type", Value::str("string"))]),
+                        )]),
+                    )
+                });
+                channel.push(("parameters", Value::Object(parameters.collect())));
+            }
+
+            if websocket {
+                let content_type = route.produces.as_deref().unwrap_or("text/plain");
+                channel.push((
+                    "subscribe",
+                    operation(route, "_receive", message(route, content_type)),
+                ));
+                channel.push((
+                    "publish",
+                    operation(route, "_send", message(route, content_type)),
+                ));
+                channel.push((
+                    "bindings",
+                    Value::object(vec![(
+                        "ws",
+                        Value::object(vec![
+                            ("method", Value::str("GET")),
+                            ("bindingVersion", Value::str("0.1.0")),
+                        ]),
+                    )]),
+                ));
+            } else {
+                channel.push((
+                    "subscribe",
+                    operation(route, "", message(route, "text/event-stream")),
+                ));
+            }
+
+            let path = if path.is_empty() {
+                "/".to_string()
+            } else {
+                path
+            };
+            channels.push((path, Value::object(channel)));
+        }
+    }
+
+    Value::object(vec![
+        ("asyncapi", Value::str("2.6.0")),
+        (
+            "info",
+            Value::object(vec![
+                ("title", Value::str(&args.title)),
+                ("version", Value::str(&args.api_version)),
+            ]),
+        ),
+        ("channels", Value::Object(channels)),
+    ])
+}
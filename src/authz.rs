@@ -0,0 +1,58 @@
+use crate::markdown::table_cell;
+use crate::Registry;
+use std::collections::BTreeSet;
+
+// Render the registry as a Markdown authorization matrix: one row per route,
+// one column per role, followed by the routes no role nor admin guard protects
+pub(crate) fn routes_to_authz_matrix(map: &Registry) -> String {
+    let roles = map
+        .values()
+        .flatten()
+        .flat_map(|route| route.roles.iter().map(String::as_str))
+        .collect::<BTreeSet<_>>();
+
+    let mut document = String::from("# Authorization Matrix\n\n");
+    document.push_str("| Verb | Route | Handler |");
+    for role in &roles {
+        document.push_str(&format!(" {} |", table_cell(role)));
+    }
+    document.push_str("\n|------|-------|---------|");
+    for _ in &roles {
+        document.push_str("---|");
+    }
+    document.push('\n');
+
+    let mut unprotected = Vec::new();
+    for (scope, routes) in map.iter() {
+        for route in routes {
+            let route_label = crate::served_pattern(scope, route);
+            document.push_str(&format!(
+                "| {} | `{}` | `{}` |",
+                crate::verb_label(route),
+                table_cell(&route_label),
+                route.handler_name
+            ));
+            for role in &roles {
+                let allowed = route.roles.iter().any(|allowed| allowed == role);
+                document.push_str(if allowed { " x |" } else { "  |" });
+            }
+            document.push('\n');
+
+            if route.roles.is_empty() && !route.admin {
+                unprotected.push(format!(
+                    "- `{} {}` (`{}`, `{}:{}`)\n",
+                    route.verb, route_label, route.handler_name, route.file, route.line
+                ));
+            }
+        }
+    }
+
+    document.push_str("\n## Unprotected Routes\n\n");
+    if unprotected.is_empty() {
+        document.push_str("Every route requires a role.\n");
+    } else {
+        document.push_str(&unprotected.concat());
+    }
+
+    document
+}
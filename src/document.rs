@@ -0,0 +1,205 @@
+use crate::manifest::json_string;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+// A generated document (OpenAPI, ...) built at expansion time, rendered as JSON or YAML
+pub(crate) enum Value {
+    Bool(bool),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+    // JSON text known at expansion time (e.g., an example written by the user)
+    RawJson(String),
+    // An expression of the generated code returning JSON text (e.g., a schema)
+    RuntimeJson(TokenStream),
+}
+
+impl Value {
+    pub(crate) fn str(value: &str) -> Value {
+        Value::String(value.to_string())
+    }
+
+    pub(crate) fn object<K: Into<String>>(entries: Vec<(K, Value)>) -> Value {
+        Value::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.into(), value))
+                .collect(),
+        )
+    }
+
+    // Write the document as pretty-printed JSON
+    pub(crate) fn to_json(&self) -> Vec<TokenStream> {
+        let mut parts = Parts::default();
+        write_json(self, 0, &mut parts);
+        parts.finish()
+    }
+
+    // Write the document as block-style YAML (runtime JSON is embedded in flow style)
+    pub(crate) fn to_yaml(&self) -> Vec<TokenStream> {
+        let mut parts = Parts::default();
+        match self {
+            Value::Object(entries) if !entries.is_empty() => {
+                write_yaml_entries(entries, 0, &mut parts)
+            }
+            Value::Array(items) if !items.is_empty() => write_yaml_items(items, 0, &mut parts),
+            value => {
+                write_yaml_scalar(value, &mut parts);
+                parts.push_str("\n");
+            }
+        }
+        parts.finish()
+    }
+}
+
+// Accumulates generated text as string expressions: most of it is known at
+// expansion time, while some values (e.g., schemas) are only known at runtime
+#[derive(Default)]
+pub(crate) struct Parts {
+    parts: Vec<TokenStream>,
+    pending: String,
+}
+
+impl Parts {
+    pub(crate) fn push_str(&mut self, value: &str) {
+        self.pending.push_str(value);
+    }
+
+    pub(crate) fn push_expr(&mut self, expr: TokenStream) {
+        self.flush();
+        self.parts.push(expr);
+    }
+
+    fn flush(&mut self) {
+        if !self.pending.is_empty() {
+            let literal = std::mem::take(&mut self.pending);
+            self.parts.push(quote!(#literal.to_string()));
+        }
+    }
+
+    // Return the string expressions to concatenate in the generated code
+    pub(crate) fn finish(mut self) -> Vec<TokenStream> {
+        self.flush();
+        self.parts
+    }
+}
+
+fn write_json(value: &Value, indent: usize, parts: &mut Parts) {
+    let padding = "  ".repeat(indent + 1);
+    let closing = "  ".repeat(indent);
+    match value {
+        Value::Array(items) if !items.is_empty() => {
+            parts.push_str("[\n");
+            for (index, item) in items.iter().enumerate() {
+                parts.push_str(&padding);
+                write_json(item, indent + 1, parts);
+                parts.push_str(if index + 1 < items.len() { ",\n" } else { "\n" });
+            }
+            parts.push_str(&format!("{}]", closing));
+        }
+        Value::Object(entries) if !entries.is_empty() => {
+            parts.push_str("{\n");
+            for (index, (key, item)) in entries.iter().enumerate() {
+                parts.push_str(&format!("{}{}: ", padding, json_string(key)));
+                write_json(item, indent + 1, parts);
+                parts.push_str(if index + 1 < entries.len() {
+                    ",\n"
+                } else {
+                    "\n"
+                });
+            }
+            parts.push_str(&format!("{}}}", closing));
+        }
+        scalar => write_yaml_scalar(scalar, parts),
+    }
+}
+
+// Scalars, empty collections and embedded JSON are written the same way in
+// both formats, since JSON is valid YAML flow style
+fn write_yaml_scalar(value: &Value, parts: &mut Parts) {
+    match value {
+        Value::Bool(value) => parts.push_str(&value.to_string()),
+        Value::String(value) => parts.push_str(&json_string(value)),
+        Value::Array(_) => parts.push_str("[]"),
+        Value::Object(_) => parts.push_str("{}"),
+        // Newlines are insignificant between JSON tokens and cannot appear in its strings
+        Value::RawJson(json) => parts.push_str(&json.replace(['\n', '\r'], " ")),
+        Value::RuntimeJson(expr) => parts.push_expr(quote!(#expr.replace(['\n', '\r'], " "))),
+    }
+}
+
+// Write a mapping key plainly when YAML cannot mistake it for anything but a
+// string (e.g., `paths`, `/events/{id}`), quoted otherwise (e.g., `"200"`)
+fn yaml_key(key: &str) -> String {
+    let plain = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '/')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_/.-{}".contains(c))
+        && !["true", "false", "null", "yes", "no", "on", "off", "y", "n"]
+            .contains(&key.to_ascii_lowercase().as_str());
+
+    if plain {
+        key.to_string()
+    } else {
+        json_string(key)
+    }
+}
+
+fn is_block(value: &Value) -> bool {
+    match value {
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(entries) => !entries.is_empty(),
+        _ => false,
+    }
+}
+
+fn write_yaml_entries(entries: &[(String, Value)], indent: usize, parts: &mut Parts) {
+    let padding = "  ".repeat(indent);
+    for (index, (key, value)) in entries.iter().enumerate() {
+        // The first entry of a list item follows its "- " marker
+        if index > 0 || indent == 0 {
+            parts.push_str(&padding);
+        }
+        parts.push_str(&format!("{}:", yaml_key(key)));
+        write_yaml_value(value, indent, parts);
+    }
+}
+
+fn write_yaml_items(items: &[Value], indent: usize, parts: &mut Parts) {
+    let padding = "  ".repeat(indent);
+    for item in items {
+        parts.push_str(&format!("{}- ", padding));
+        match item {
+            Value::Object(entries) if !entries.is_empty() => {
+                write_yaml_entries(entries, indent + 1, parts)
+            }
+            Value::Array(items) if !items.is_empty() => {
+                parts.push_str("\n");
+                write_yaml_items(items, indent + 1, parts);
+            }
+            scalar => {
+                write_yaml_scalar(scalar, parts);
+                parts.push_str("\n");
+            }
+        }
+    }
+}
+
+fn write_yaml_value(value: &Value, indent: usize, parts: &mut Parts) {
+    if !is_block(value) {
+        parts.push_str(" ");
+        write_yaml_scalar(value, parts);
+        parts.push_str("\n");
+        return;
+    }
+
+    parts.push_str("\n");
+    match value {
+        Value::Object(entries) => {
+            parts.push_str(&"  ".repeat(indent + 1));
+            write_yaml_entries(entries, indent + 1, parts);
+        }
+        Value::Array(items) => write_yaml_items(items, indent + 1, parts),
+        _ => unreachable!("Only collections are written as blocks"),
+    }
+}
@@ -0,0 +1,54 @@
+use crate::Registry;
+
+// Escape a value for a quoted DOT string
+fn dot_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// Pick a fill color per HTTP verb so the graph reads at a glance
+fn verb_color(verb: &str) -> &'static str {
+    match verb {
+        "GET" => "#61affe",
+        "POST" => "#49cc90",
+        "PUT" => "#fca130",
+        "DELETE" => "#f93e3e",
+        "PATCH" => "#50e3c2",
+        "WS" => "#9012fe",
+        _ => "#d3d3d3",
+    }
+}
+
+// Render the registry as a Graphviz DOT graph: one cluster per scope, one node per route
+pub(crate) fn routes_to_dot(map: &Registry) -> String {
+    let mut lines = vec![
+        "digraph routes {".to_string(),
+        "    rankdir=LR;".to_string(),
+        "    node [shape=box, style=\"rounded,filled\", fontname=\"Helvetica\"];".to_string(),
+    ];
+
+    for (scope_index, (scope, routes)) in map.iter().enumerate() {
+        let scope_label = if scope.is_empty() {
+            "/"
+        } else {
+            scope.as_str()
+        };
+        lines.push(format!("    subgraph cluster_{} {{", scope_index));
+        lines.push(format!("        label={};", dot_string(scope_label)));
+
+        for (route_index, route) in routes.iter().enumerate() {
+            let label = format!("{} {}\n{}", route.verb, route.path, route.handler_name);
+            lines.push(format!(
+                "        scope_{}_route_{} [label={}, fillcolor={}];",
+                scope_index,
+                route_index,
+                dot_string(&label).replace('\n', "\\n"),
+                dot_string(verb_color(&route.verb))
+            ));
+        }
+
+        lines.push("    }".to_string());
+    }
+
+    lines.push("}".to_string());
+    lines.join("\n")
+}
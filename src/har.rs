@@ -0,0 +1,153 @@
+use crate::args::HarArgs;
+use crate::document::Value;
+use crate::{http_method, path_segments, request_media_type, Registry, RouteInfo, RouteKind};
+
+// A `{ "name": ..., "value": ... }` pair of a HAR header list
+fn header(name: &str, value: &str) -> Value {
+    Value::object(vec![
+        ("name", Value::str(name)),
+        ("value", Value::str(value)),
+    ])
+}
+
+// Keep the names of the dynamic segments of an Actix path pattern only, the URL
+// template replay tools fill in (e.g., "/events/{id:\d+}" -> "/events/{id}")
+fn har_url(base_url: &str, path: &str) -> String {
+    let (template, segments) = path_segments(path);
+    segments.iter().fold(
+        format!("{}{}", base_url.trim_end_matches('/'), template),
+        |url, (name, _)| url.replacen("{}", &format!("{{{}}}", name), 1),
+    )
+}
+
+// The request of an entry, with the headers its annotations require
+fn request(route: &RouteInfo, url: String) -> Value {
+    let mut headers = Vec::new();
+    let content_type = request_media_type(route);
+    if let Some(media_type) = content_type {
+        headers.push(header("Content-Type", media_type));
+    }
+    if let Some(media_type) = &route.produces {
+        headers.push(header("Accept", media_type));
+    }
+    if let Some(name) = &route.api_key {
+        headers.push(header(name, ""));
+    }
+    if !route.jwt_scopes.is_empty() {
+        headers.push(header("Authorization", "Bearer "));
+    }
+    if route.idempotent {
+        headers.push(header("Idempotency-Key", ""));
+    }
+
+    let mut request = vec![
+        ("method", Value::str(http_method(&route.verb))),
+        ("url", Value::str(&url)),
+        ("httpVersion", Value::str("HTTP/1.1")),
+        ("cookies", Value::Array(Vec::new())),
+        ("headers", Value::Array(headers)),
+        ("queryString", Value::Array(Vec::new())),
+    ];
+    if let Some(media_type) = content_type {
+        request.push((
+            "postData",
+            Value::object(vec![
+                ("mimeType", Value::str(media_type)),
+                (
+                    "text",
+                    Value::str(route.example_request.as_deref().unwrap_or_default()),
+                ),
+            ]),
+        ));
+    }
+    request.push(("headersSize", Value::RawJson("-1".to_string())));
+    request.push(("bodySize", Value::RawJson("-1".to_string())));
+    Value::object(request)
+}
+
+// The response of an entry: its success, with the example of the route, if any
+fn response(route: &RouteInfo) -> Value {
+    let (status, status_text) = if route.verb.as_ref() == "WS" {
+        (101, "Switching Protocols")
+    } else {
+        (200, "OK")
+    };
+    let media_type = route.produces.as_deref().unwrap_or_default();
+    let mut headers = Vec::new();
+    if !media_type.is_empty() {
+        headers.push(header("Content-Type", media_type));
+    }
+    let text = route.example_response.as_deref().unwrap_or_default();
+
+    Value::object(vec![
+        ("status", Value::RawJson(status.to_string())),
+        ("statusText", Value::str(status_text)),
+        ("httpVersion", Value::str("HTTP/1.1")),
+        ("cookies", Value::Array(Vec::new())),
+        ("headers", Value::Array(headers)),
+        (
+            "content",
+            Value::object(vec![
+                ("size", Value::RawJson(text.len().to_string())),
+                ("mimeType", Value::str(media_type)),
+                ("text", Value::str(text)),
+            ]),
+        ),
+        ("redirectURL", Value::str("")),
+        ("headersSize", Value::RawJson("-1".to_string())),
+        ("bodySize", Value::RawJson("-1".to_string())),
+    ])
+}
+
+// Build a HAR (1.2) log with an entry per route, for proxy and replay tools to
+// be seeded with
+pub(crate) fn routes_to_har(map: &Registry, args: &HarArgs) -> Value {
+    let entries = map
+        .iter()
+        .flat_map(|(scope, routes)| {
+            routes
+                .iter()
+                .filter(|route| route.kind != RouteKind::Static)
+                .map(move |route| {
+                    let path = crate::served_pattern(scope, route);
+                    let comment = format!(
+                        "{} {} ({})",
+                        http_method(&route.verb),
+                        path,
+                        route.handler_name
+                    );
+                    Value::object(vec![
+                        ("startedDateTime", Value::str("1970-01-01T00:00:00.000Z")),
+                        ("time", Value::RawJson("0".to_string())),
+                        ("request", request(route, har_url(&args.base_url, &path))),
+                        ("response", response(route)),
+                        ("cache", Value::object(Vec::<(String, Value)>::new())),
+                        (
+                            "timings",
+                            Value::object(vec![
+                                ("send", Value::RawJson("0".to_string())),
+                                ("wait", Value::RawJson("0".to_string())),
+                                ("receive", Value::RawJson("0".to_string())),
+                            ]),
+                        ),
+                        ("comment", Value::str(&comment)),
+                    ])
+                })
+        })
+        .collect();
+
+    Value::object(vec![(
+        "log",
+        Value::object(vec![
+            ("version", Value::str("1.2")),
+            (
+                "creator",
+                Value::object(vec![
+                    ("name", Value::str(env!("CARGO_PKG_NAME"))),
+                    ("version", Value::str(env!("CARGO_PKG_VERSION"))),
+                ]),
+            ),
+            ("entries", Value::Array(entries)),
+        ]),
+    )])
+}
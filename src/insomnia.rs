@@ -0,0 +1,140 @@
+use crate::args::InsomniaArgs;
+use crate::document::Value;
+use crate::{http_method, path_segments, request_media_type, Registry, RouteInfo, RouteKind};
+use std::collections::BTreeSet;
+
+// Replace the dynamic segments of an Actix path pattern with Insomnia template tags
+// of the environment (e.g., "/events/{id:\d+}" -> "/events/{{ _.id }}")
+fn insomnia_url(path: &str, variables: &mut BTreeSet<String>) -> String {
+    let (template, segments) = path_segments(path);
+    segments.into_iter().fold(
+        format!("{{{{ _.base_url }}}}{}", template),
+        |url, (name, _)| {
+            let url = url.replacen("{}", &format!("{{{{ _.{} }}}}", name), 1);
+            variables.insert(name);
+            url
+        },
+    )
+}
+
+// A request of the workspace, in the folder of its key
+fn request(id: usize, folder: &str, path: &str, route: &RouteInfo, url: String) -> Value {
+    let mut headers = Vec::new();
+    if let Some(media_type) = &route.produces {
+        headers.push(Value::object(vec![
+            ("name", Value::str("Accept")),
+            ("value", Value::str(media_type)),
+        ]));
+    }
+    if route.idempotent {
+        headers.push(Value::object(vec![
+            ("name", Value::str("Idempotency-Key")),
+            ("value", Value::str("{% uuid 'v4' %}")),
+        ]));
+    }
+
+    // Requests extracting a body get their example request, or an empty body of
+    // their media type to fill in
+    let body = match request_media_type(route) {
+        Some(media_type) => {
+            headers.push(Value::object(vec![
+                ("name", Value::str("Content-Type")),
+                ("value", Value::str(media_type)),
+            ]));
+            let text = match &route.example_request {
+                Some(example) => example.as_str(),
+                None if media_type == "application/json" => "{}",
+                None => "",
+            };
+            Value::object(vec![
+                ("mimeType", Value::str(media_type)),
+                ("text", Value::str(text)),
+            ])
+        }
+        None => Value::object(Vec::<(String, Value)>::new()),
+    };
+
+    let description = if route.docs.is_empty() {
+        format!("`{} {}`", http_method(&route.verb), path)
+    } else {
+        route.docs.clone()
+    };
+    Value::object(vec![
+        ("_id", Value::str(&format!("req_{}", id))),
+        ("_type", Value::str("request")),
+        ("parentId", Value::str(folder)),
+        ("name", Value::str(&route.handler_name)),
+        ("description", Value::str(&description)),
+        ("method", Value::str(http_method(&route.verb))),
+        ("url", Value::str(&url)),
+        ("headers", Value::Array(headers)),
+        ("body", body),
+    ])
+}
+
+// Build an Insomnia (export format 4) workspace with a folder per module key and a
+// request per route, their base URL and path parameters in the base environment
+pub(crate) fn routes_to_insomnia(map: &Registry, args: &InsomniaArgs) -> Value {
+    let mut resources = vec![Value::object(vec![
+        ("_id", Value::str("wrk_routes")),
+        ("_type", Value::str("workspace")),
+        ("parentId", Value::RawJson("null".to_string())),
+        ("name", Value::str(&args.name)),
+        ("scope", Value::str("collection")),
+    ])];
+
+    let mut variables = BTreeSet::new();
+    let mut requests = 0;
+    for (index, (scope, routes)) in map.iter().enumerate() {
+        let routes = routes
+            .iter()
+            .filter(|route| route.kind != RouteKind::Static)
+            .collect::<Vec<_>>();
+        if routes.is_empty() {
+            continue;
+        }
+
+        let folder = format!("fld_{}", index);
+        resources.push(Value::object(vec![
+            ("_id", Value::str(&folder)),
+            ("_type", Value::str("request_group")),
+            ("parentId", Value::str("wrk_routes")),
+            (
+                "name",
+                Value::str(if scope.is_empty() { "/" } else { scope }),
+            ),
+        ]));
+        for route in routes {
+            let path = crate::served_pattern(scope, route);
+            let url = insomnia_url(&path, &mut variables);
+            requests += 1;
+            resources.push(request(requests, &folder, &path, route, url));
+        }
+    }
+
+    // Path parameters default to an example value
+    let mut data = vec![("base_url".to_string(), Value::str(&args.base_url))];
+    data.extend(
+        variables
+            .into_iter()
+            .filter(|name| name != "base_url")
+            .map(|name| (name, Value::str("1"))),
+    );
+    resources.insert(
+        1,
+        Value::object(vec![
+            ("_id", Value::str("env_routes")),
+            ("_type", Value::str("environment")),
+            ("parentId", Value::str("wrk_routes")),
+            ("name", Value::str("Base Environment")),
+            ("data", Value::Object(data)),
+        ]),
+    );
+
+    Value::object(vec![
+        ("_type", Value::str("export")),
+        ("__export_format", Value::RawJson("4".to_string())),
+        ("__export_source", Value::str("register_actix_routes")),
+        ("resources", Value::Array(resources)),
+    ])
+}
@@ -0,0 +1,119 @@
+use crate::args::K6Args;
+use crate::{
+    example_path, http_method, request_media_type, route_path_const_name, Registry, RouteKind,
+};
+
+// Quote a value as a JavaScript string literal
+fn js_string(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+// Render the registry as a k6 script: one scenario per module key, requesting its
+// routes in turn at a constant rate, with the base URL, the rates and the duration
+// overridable with environment variables (e.g., `k6 run -e RATE_EVENTS=50`)
+pub(crate) fn routes_to_k6(map: &Registry, args: &K6Args) -> String {
+    let mut scenarios = Vec::new();
+    let mut functions = Vec::new();
+
+    for (scope, routes) in map.iter() {
+        // Static files, WebSocket upgrades and Server-Sent Events streams aren't
+        // plain request-response routes
+        let requests = routes
+            .iter()
+            .filter(|route| route.kind != RouteKind::Static && route.verb.as_ref() != "WS")
+            .filter(|route| !route.sse)
+            .map(|route| {
+                let path = crate::served_pattern(scope, route);
+                let name = format!("{} {}", http_method(&route.verb), path);
+                // Routes with an example request send it, with its media type
+                let body = match (&route.example_request, request_media_type(route)) {
+                    (Some(example), Some(media_type)) => {
+                        format!(", {}, {}", js_string(example), js_string(media_type))
+                    }
+                    _ => String::new(),
+                };
+                format!(
+                    "  [{}, {}, {}{}],",
+                    js_string(http_method(&route.verb)),
+                    js_string(&example_path(&path)),
+                    js_string(&name),
+                    body
+                )
+            })
+            .collect::<Vec<_>>();
+        if requests.is_empty() {
+            continue;
+        }
+
+        // Scenarios are named after their key (e.g., "/admin/users" -> `admin_users`)
+        let name = match route_path_const_name(scope, "").to_lowercase() {
+            name if name.is_empty() => "root".to_string(),
+            name => name,
+        };
+        scenarios.push(format!(
+            "    {name}: {{\n      executor: \"constant-arrival-rate\",\n      rate: Number(__ENV.RATE_{upper} || RATE),\n      timeUnit: \"1s\",\n      duration: DURATION,\n      preAllocatedVUs: PRE_ALLOCATED_VUS,\n      exec: \"{name}\",\n    }},",
+            name = name,
+            upper = name.to_uppercase()
+        ));
+        functions.push(format!(
+            "// Routes under {}\nconst {}_ROUTES = [\n{}\n];\n\nexport function {}() {{\n  request({}_ROUTES);\n}}",
+            js_string(if scope.is_empty() { "/" } else { scope }),
+            name.to_uppercase(),
+            requests.join("\n"),
+            name,
+            name.to_uppercase()
+        ));
+    }
+
+    let mut lines = vec![
+        "import http from \"k6/http\";".to_string(),
+        "import exec from \"k6/execution\";".to_string(),
+        "import { check } from \"k6\";".to_string(),
+        String::new(),
+        format!(
+            "const BASE_URL = __ENV.BASE_URL || {};",
+            js_string(&args.base_url)
+        ),
+        format!("const RATE = Number(__ENV.RATE || {});", args.rate),
+        format!(
+            "const DURATION = __ENV.DURATION || {};",
+            js_string(&args.duration)
+        ),
+        "const PRE_ALLOCATED_VUS = Number(__ENV.PRE_ALLOCATED_VUS || 10);".to_string(),
+        String::new(),
+        "export const options = {".to_string(),
+        "  scenarios: {".to_string(),
+    ];
+    lines.extend(scenarios);
+    lines.push("  },".to_string());
+    lines.push("};".to_string());
+    lines.push(String::new());
+    lines
+        .push("// Request the routes of a scenario in turn, tagged with their pattern".to_string());
+    lines.push("function request(routes) {".to_string());
+    lines.push(
+        "  const [method, path, name, body, contentType] = routes[exec.scenario.iterationInTest % routes.length];"
+            .to_string(),
+    );
+    lines.push(
+        "  const headers = contentType ? { \"Content-Type\": contentType } : {};".to_string(),
+    );
+    lines.push(
+        "  const response = http.request(method, BASE_URL + path, body || null, { headers, tags: { name } });"
+            .to_string(),
+    );
+    lines.push("  check(response, { \"no server error\": (r) => r.status < 500 });".to_string());
+    lines.push("}".to_string());
+    for function in functions {
+        lines.push(String::new());
+        lines.push(function);
+    }
+
+    lines.join("\n") + "\n"
+}
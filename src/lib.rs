@@ -1,176 +1,4330 @@
 extern crate proc_macro;
-extern crate tabled;
+mod args;
+mod asyncapi;
+mod authz;
+mod document;
+mod dot;
+mod har;
+mod insomnia;
+mod k6;
+mod manifest;
+mod markdown;
+mod mermaid;
+mod middleware;
+mod openapi;
+mod pact;
+mod scan;
+
 use once_cell::sync::Lazy;
 use proc_macro::TokenStream;
 use quote::quote;
-use std::sync::RwLock;
-use syn::{parse_macro_input, ItemFn, LitStr};
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use syn::ext::IdentExt;
+use syn::{ItemFn, LitStr};
+
+// How the generated code registers a handler
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RouteKind {
+    Service, // An Actix route macro (e.g., `#[get("/search")]`) turned the handler into a service
+    Resource, // A plain function, wrapped in `web::resource(path).route(web::get().to(handler))`
+    Static,  // A directory served by `actix_files::Files`, named by `handler_name`
+}
 
 #[derive(Debug, Clone)]
 struct RouteInfo {
-    prefix: String,       // The scope or module key (e.g., "/events")
-    handler_name: String, // The name of the handler function
-    path: String,         // The route path (e.g., "/search")
-    verb: String,         // The HTTP method (e.g., "GET")
+    prefix: Arc<str>,                    // The scope or module key (e.g., "/events")
+    handler_name: Arc<str>, // The name of the handler function without any `r#`, or the directory of a static mount
+    path: Arc<str>,         // The route path (e.g., "/search")
+    verb: Arc<str>,         // The HTTP method (e.g., "GET")
+    file: Arc<str>,         // The source file of the handler (e.g., "src/api/events.rs")
+    line: usize,            // The line of the handler function name in `file`
+    module_path: Arc<str>,  // The module deduced from `file` (e.g., "crate::api::events")
+    docs: String,           // The doc comments of the handler, one line per `///`
+    body_type: Option<String>, // The `T` of a `Json<T>` extractor, if any
+    query_type: Option<String>, // The `T` of a `Query<T>` extractor, if any
+    example_response: Option<String>, // A sample response body declared on the route
+    example_request: Option<String>, // A sample request body declared on the route
+    operation_id: Option<String>, // The OpenAPI operationId replacing the derived one
+    errors: Vec<u16>,       // The error status codes the route may answer with
+    generics: Option<String>, // The generic arguments to register the handler with (e.g., "<PgStore>")
+    kind: RouteKind,          // Whether the handler is a service or a plain function
+    sse: bool,                // Whether the handler streams Server-Sent Events
+    multipart: bool,          // Whether the handler receives a multipart upload
+    multipart_limit: Option<String>, // The total size limit of the upload (e.g., "25MB")
+    warn_slower_than_ms: Option<u64>, // Log the requests slower than this many milliseconds
+    circuit_breaker: Option<(u32, u64)>, // Failures opening the circuit, and its cooldown in milliseconds
+    canary: Option<u8>, // Percentage of the traffic of the path sent to this canary handler
+    variant: Option<(String, u32)>, // Name of the A/B variant and its weight among the path's variants
+    shadow_to: Option<String>,      // Handler replaying the route's requests in the background
+    flag: Option<String>,           // Feature flag the route is served behind
+    roles: Vec<String>,             // Roles allowed to call the route, none for unprotected routes
+    api_key: Option<String>,        // Header carrying the API key the route requires
+    csrf_exempt: bool,              // Whether CSRF middlewares should let the route through
+    session_required: bool,         // Whether the route rejects requests without a session
+    jwt_scopes: Vec<String>,        // Scopes the bearer token must grant, none when not required
+    audit: bool,                    // Whether the route's requests are reported to the audit sink
+    idempotent: bool,               // Whether the route requires an `Idempotency-Key` header
+    body_limit: Option<String>,     // The size limit of the request body (e.g., "1MB")
+    consumes: Option<String>,       // The media type the request body must have (e.g., "text/csv")
+    produces: Option<String>,       // The media type negotiated with `Accept` (e.g., "text/html")
+    etag: bool,                     // Whether responses get an ETag and 304 on `If-None-Match`
+    headers: Vec<(String, String)>, // Headers added to every response (e.g., ("X-Robots-Tag", "noindex"))
+    admin: bool,                    // Whether the route is behind the `admin` guard of its key
+    log_format: Option<String>,     // The format of the route's own `Logger` (e.g., "%a %r %s")
+    log: bool,                      // Whether the app's `Logger` logs the route
+    handler_path: Option<String>,   // The path registered instead of the name, if any
+    reexported: bool,               // Whether `auto_register` annotates a `pub use` of the handler
+    requires: Vec<String>,          // Types extracted as `web::Data<T>` (e.g., "crate::AppState")
+    deprecated: bool,               // Whether the handler is marked `#[deprecated]`
+    doc_hidden: bool,               // Whether the handler is marked `#[doc(hidden)]`
+    cfg: Vec<String>, // The predicates of the handler's `#[cfg]` (e.g., "feature = \"beta\"")
+}
+
+// A route with every option off, for the macros to fill in with struct update syntax
+impl Default for RouteInfo {
+    fn default() -> Self {
+        RouteInfo {
+            prefix: Arc::from(""),
+            handler_name: Arc::from(""),
+            path: Arc::from(""),
+            verb: Arc::from(""),
+            file: Arc::from(""),
+            line: 0,
+            module_path: Arc::from(""),
+            docs: String::new(),
+            body_type: None,
+            query_type: None,
+            example_response: None,
+            example_request: None,
+            operation_id: None,
+            errors: Vec::new(),
+            generics: None,
+            kind: RouteKind::Service,
+            sse: false,
+            multipart: false,
+            multipart_limit: None,
+            warn_slower_than_ms: None,
+            circuit_breaker: None,
+            canary: None,
+            variant: None,
+            shadow_to: None,
+            flag: None,
+            roles: Vec::new(),
+            api_key: None,
+            csrf_exempt: false,
+            session_required: false,
+            jwt_scopes: Vec::new(),
+            audit: false,
+            idempotent: false,
+            body_limit: None,
+            consumes: None,
+            produces: None,
+            etag: false,
+            headers: Vec::new(),
+            admin: false,
+            log_format: None,
+            log: true,
+            handler_path: None,
+            reexported: false,
+            requires: Vec::new(),
+            deprecated: false,
+            doc_hidden: false,
+            cfg: Vec::new(),
+        }
+    }
+}
+
+// Prefix of the handlers of a module when `auto_register` is given none, also the
+// key `generate_register_service!` registers them under (e.g., "crate::api::events"
+// -> "/api/events", and "" for the crate root)
+fn module_prefix(segments: &[&str]) -> String {
+    segments
+        .iter()
+        .map(|segment| format!("/{}", segment))
+        .collect()
+}
+
+// The module prefix of the file a macro is invoked in
+fn call_site_module_prefix() -> String {
+    let file = proc_macro::Span::call_site().file();
+    module_prefix(&module_segments(&file))
+}
+
+// The modules of a source file following the usual Cargo layout, from the crate
+// root of its target (e.g., "src/api/events.rs" or "src/api/events/mod.rs" ->
+// ["api", "events"], and "src/bin/my-server.rs" or "examples/demo/main.rs" -> [])
+fn module_segments(file: &str) -> Vec<&str> {
+    // The innermost target directory, with binaries, examples, tests and benches
+    // being the crate roots of their own target, in a file or in a directory
+    let (relative, is_target) = ["src/", "src/bin/", "examples/", "tests/", "benches/"]
+        .iter()
+        .filter_map(|dir| {
+            let index = file.rfind(dir)?;
+            Some((index, dir.len(), &file[index + dir.len()..], *dir != "src/"))
+        })
+        .max_by_key(|(index, len, _, _)| (*index, *len))
+        .map_or((file, false), |(_, _, relative, is_target)| {
+            (relative, is_target)
+        });
+
+    let mut segments: Vec<&str> = relative
+        .trim_end_matches(".rs")
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    if is_target {
+        segments.remove(0);
+    }
+    if matches!(segments.last(), Some(&"mod")) {
+        segments.pop();
+    }
+    if segments.len() == 1 && matches!(segments[0], "lib" | "main") {
+        segments.pop();
+    }
+    segments
+}
+
+// Deduce the module path of a source file following the usual Cargo layout
+// (e.g., "src/api/events.rs" or "src/api/events/mod.rs" -> "crate::api::events"),
+// or `None` when a file isn't named like a module (e.g., "src/my-module.rs" included
+// with `#[path]`)
+fn module_path_from_file(file: &str) -> Option<String> {
+    let file = file.replace('\\', "/");
+    std::iter::once(Some("crate".to_string()))
+        .chain(
+            module_segments(&file)
+                .into_iter()
+                .map(|segment| handler_ident(segment).map(|ident| ident.to_string())),
+        )
+        .collect::<Option<Vec<_>>>()
+        .map(|segments| segments.join("::"))
+}
+
+// The module path of the file an item is declared in, falling back to the crate
+// root for files not named like a module, whose items are then registered by name
+fn item_module_path(file: &str) -> String {
+    module_path_from_file(file).unwrap_or_else(|| "crate".to_string())
+}
+
+// Render tokens the way they were written, without the spaces `to_string` puts
+// between every token (e.g., "Vec < models :: Event >" -> "Vec<models::Event>")
+fn tokens_to_string(tokens: &proc_macro2::TokenStream) -> String {
+    let spaced = tokens.to_string();
+    let chars: Vec<char> = spaced.chars().collect();
+    let is_word = |c: Option<&char>| c.is_some_and(|c| c.is_alphanumeric() || *c == '_');
+
+    let mut rendered = String::with_capacity(spaced.len());
+    for (index, c) in chars.iter().enumerate() {
+        if *c == ' '
+            && !(is_word(chars.get(index.wrapping_sub(1))) && is_word(chars.get(index + 1)))
+        {
+            continue;
+        }
+        rendered.push(*c);
+    }
+    rendered
+}
+
+// Return the `T` of the first `Extractor<T>` (e.g., `Json<T>` or `web::Query<T>`) argument of a handler
+fn extractor_type(sig: &syn::Signature, extractor: &str) -> Option<syn::Type> {
+    sig.inputs.iter().find_map(|input| {
+        let syn::FnArg::Typed(pat_type) = input else {
+            return None;
+        };
+        let syn::Type::Path(type_path) = &*pat_type.ty else {
+            return None;
+        };
+        let segment = type_path.path.segments.last()?;
+        if segment.ident != extractor {
+            return None;
+        }
+        let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+            return None;
+        };
+        arguments.args.iter().find_map(|argument| match argument {
+            syn::GenericArgument::Type(ty) => Some(ty.clone()),
+            _ => None,
+        })
+    })
+}
+
+// Build the identifier of a registered item from its stored name, escaping
+// keywords as raw identifiers (e.g., "match" -> `r#match`). The call site span
+// makes it resolve where the generated code is expanded, i.e. in the module
+// invoking the generator, exactly like a name written there by hand. Names
+// that can't be identifiers (e.g., "my-server", "01_demo" or "self") give `None`.
+fn handler_ident(name: &str) -> Option<syn::Ident> {
+    match syn::parse_str::<syn::Ident>(name) {
+        Ok(ident) => Some(syn::Ident::new(
+            &ident.to_string(),
+            proc_macro2::Span::call_site(),
+        )),
+        Err(_) => syn::parse_str::<syn::Ident>(&format!("r#{}", name))
+            .ok()
+            .map(|_| syn::Ident::new_raw(name, proc_macro2::Span::call_site())),
+    }
+}
+
+// Name of the hidden function returning the JSON Schema of a handler's body or query
+// (`part` is either "body" or "query")
+fn schema_fn_ident(handler_name: &str, part: &str) -> syn::Ident {
+    quote::format_ident!("__{}_{}_schema", handler_name, part)
+}
+
+// Path of a route's schema function, for generated code living outside the handler's module
+fn schema_fn_path(route: &RouteInfo, part: &str) -> syn::Path {
+    syn::parse_str(&format!(
+        "{}::{}",
+        route.module_path,
+        schema_fn_ident(&route.handler_name, part)
+    ))
+    .expect("Failed to build the schema function path")
+}
+
+// Name of the guard function routing part of a path's traffic to a handler
+// (`kind` is either "canary", "variant" or "produces")
+fn guard_fn_ident(handler_name: &str, kind: &str) -> syn::Ident {
+    quote::format_ident!("__{}_{}_guard", handler_name, kind)
+}
+
+// Name of the slot `register_service` stores the A/B variant or negotiation guard
+// of a handler in (`kind` is either "variant" or "produces")
+fn guard_slot_ident(handler_name: &str, kind: &str) -> syn::Ident {
+    quote::format_ident!(
+        "__{}_{}_GUARD",
+        handler_name.to_uppercase(),
+        kind.to_uppercase()
+    )
+}
+
+// The guard a route macro names, defined next to its handler, deferring to the
+// guard `register_service` builds from every handler of the path and stores in
+// the slot. Requests are declined until the slot is filled.
+fn slot_guard(actix: &syn::Path, handler_name: &str, kind: &str) -> proc_macro2::TokenStream {
+    let guard = guard_fn_ident(handler_name, kind);
+    let slot = guard_slot_ident(handler_name, kind);
+    quote! {
+        #[doc(hidden)]
+        pub static #slot: ::std::sync::OnceLock<fn(&#actix::guard::GuardContext<'_>) -> bool> =
+            ::std::sync::OnceLock::new();
+
+        #[doc(hidden)]
+        pub fn #guard(ctx: &#actix::guard::GuardContext<'_>) -> bool {
+            #slot.get().is_some_and(|guard| guard(ctx))
+        }
+    }
+}
+
+// The module of the items `auto_register` defines next to a handler (e.g., its
+// guards), to reach them from the generator: the module of its `handler_path`
+// when given, none when it is the generator's module
+fn handler_items_module(
+    route: &RouteInfo,
+    generator_module: &str,
+) -> Option<proc_macro2::TokenStream> {
+    if let Some(handler_path) = &route.handler_path {
+        return handler_path
+            .rsplit_once("::")
+            .filter(|(module, _)| !module.is_empty())
+            .map(|(module, _)| {
+                let module: syn::Path =
+                    syn::parse_str(module).expect("Handler paths are validated by auto_register");
+                quote!(#module::)
+            });
+    }
+    (route.module_path.as_ref() != generator_module).then(|| {
+        let module: syn::Path =
+            syn::parse_str(&route.module_path).expect("Module paths are built from identifiers");
+        quote!(#module::)
+    })
+}
+
+// Add a `guard = "..."` or `wrap = "..."` argument to the Actix route macro of a handler
+// (e.g., `#[get("/search")]` -> `#[get("/search", guard = "__search_canary_guard")]`)
+fn add_route_macro_arg(input_fn: &mut ItemFn, name: &str, value: &str) {
+    let name = syn::Ident::new(name, proc_macro2::Span::call_site());
+    for attr in &mut input_fn.attrs {
+        let is_route_macro = attr.path().segments.last().is_some_and(|segment| {
+            ["get", "post", "put", "delete", "patch"].contains(&segment.ident.to_string().as_str())
+        });
+        if let (true, syn::Meta::List(list)) = (is_route_macro, &mut attr.meta) {
+            let tokens = &list.tokens;
+            list.tokens = quote!(#tokens, #name = #value);
+        }
+    }
+}
+
+// Read the dynamic segment following a `{` of a path pattern, up to its closing
+// brace, and whether it is a `{tail}*` segment (e.g., "id:\d+}/..." -> "id:\d+")
+fn dynamic_segment(chars: &mut std::iter::Peekable<std::str::Chars>) -> (String, bool) {
+    // Custom expressions may contain braces, such as `{year:\d{4}}`
+    let mut segment = String::new();
+    let mut depth = 1;
+    for c in chars.by_ref() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ => {}
+        }
+        if depth == 0 {
+            break;
+        }
+        segment.push(c);
+    }
+    let tail = chars.next_if_eq(&'*').is_some();
+    (segment, tail)
+}
+
+// Regular expression matching a dynamic segment: up to the next slash for `{id}`,
+// its own expression for `{id:\d+}`, and the rest of the path for `{tail}*`
+fn segment_regex(segment: &str, tail: bool) -> String {
+    if tail {
+        ".*".to_string()
+    } else if let Some((_, custom)) = segment.split_once(':') {
+        format!("(?:{})", custom)
+    } else {
+        "[^/]+".to_string()
+    }
+}
+
+// Regular expression matching the paths of an Actix path pattern
+// (e.g., "/events/{id:\d+}/{tail}*" -> r"^/events/(?:\d+)/.*$")
+fn pattern_regex(pattern: &str) -> String {
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '{' {
+            if "\\.+*?()|[]{}^$".contains(c) {
+                regex.push('\\');
+            }
+            regex.push(c);
+            continue;
+        }
+        let (segment, tail) = dynamic_segment(&mut chars);
+        regex.push_str(&segment_regex(&segment, tail));
+    }
+    regex.push('$');
+    regex
+}
+
+// Split an Actix path pattern into a `format!` string and the names of its dynamic
+// segments, in order (e.g., "/events/{id:\d+}/{tail}*" -> ("/events/{}/{}", ["id", "tail"]))
+fn url_template(pattern: &str) -> (String, Vec<String>) {
+    let (template, segments) = path_segments(pattern);
+    let params = segments.into_iter().map(|(name, _)| name).collect();
+    (template, params)
+}
+
+// Split an Actix path pattern into a `format!` string and the names and regular
+// expressions of its dynamic segments, in order
+// (e.g., "/events/{id:\d+}" -> ("/events/{}", [("id", r"(?:\d+)")]))
+fn path_segments(pattern: &str) -> (String, Vec<(String, String)>) {
+    let mut template = String::new();
+    let mut segments = Vec::new();
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '{' {
+            template.push(c);
+            continue;
+        }
+        let (segment, tail) = dynamic_segment(&mut chars);
+        template.push_str("{}");
+        let name = segment.split_once(':').map_or(&*segment, |(name, _)| name);
+        segments.push((name.to_string(), segment_regex(&segment, tail)));
+    }
+    (template, segments)
+}
+
+// The media type of the body a route receives: the one it `consumes`, or JSON for
+// `Json<T>` extractors and example request bodies
+fn request_media_type(route: &RouteInfo) -> Option<&str> {
+    route.consumes.as_deref().or(
+        (route.body_type.is_some() || route.example_request.is_some())
+            .then_some("application/json"),
+    )
+}
+
+// Fill the dynamic segments of an Actix path pattern with an example value, the
+// path a client would request (e.g., "/events/{id:\d+}" -> "/events/1")
+fn example_path(path: &str) -> String {
+    let (template, parameters) = url_template(path);
+    parameters
+        .iter()
+        .fold(template, |path, _| path.replacen("{}", "1", 1))
+}
+
+// Pluralize a number of routes (e.g., "1 route", "3 routes")
+fn route_count(count: usize) -> String {
+    match count {
+        1 => "1 route".to_string(),
+        count => format!("{} routes", count),
+    }
+}
+
+// The routes (by file and line) sharing their verb and full path with another
+// route of the registry, of which Actix only ever serves the first. Routes set
+// apart by a guard (`canary`, `variant`, `produces`) are expected to share them,
+// as are the registrations IDEs re-expand at the same location.
+fn duplicate_routes(map: &Registry) -> HashSet<(Arc<str>, usize)> {
+    let mut routes_by_path = BTreeMap::<(String, &str), BTreeSet<(Arc<str>, usize)>>::new();
+    for (scope, routes) in map.iter() {
+        for route in routes {
+            if route.kind == RouteKind::Static
+                || route.canary.is_some()
+                || route.variant.is_some()
+                || route.produces.is_some()
+            {
+                continue;
+            }
+            routes_by_path
+                .entry((full_path(scope, route), &route.verb))
+                .or_default()
+                .insert((route.file.clone(), route.line));
+        }
+    }
+    routes_by_path
+        .into_values()
+        .filter(|routes| routes.len() > 1)
+        .flatten()
+        .collect()
+}
+
+// One-line summary of the registry for startup logs, with the most used verbs
+// first (e.g., "42 routes across 6 scopes (GET 25, POST 10, DELETE 7)")
+fn routes_summary(map: &Registry) -> String {
+    let mut verbs = BTreeMap::<&str, usize>::new();
+    for route in map.values().flatten() {
+        *verbs.entry(&route.verb).or_default() += 1;
+    }
+    let mut verbs = verbs.into_iter().collect::<Vec<_>>();
+    verbs.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+    let mut summary = format!(
+        "{} across {} {}",
+        route_count(map.values().map(Vec::len).sum()),
+        map.len(),
+        if map.len() == 1 { "scope" } else { "scopes" }
+    );
+    if !verbs.is_empty() {
+        let verbs = verbs
+            .iter()
+            .map(|(verb, count)| format!("{} {}", verb, count))
+            .collect::<Vec<_>>();
+        summary.push_str(&format!(" ({})", verbs.join(", ")));
+    }
+    summary
+}
+
+// Doc comment of a generated function: a summary followed by the routes it covers
+// (e.g., "Registers 2 routes under `/events`:\n\n- `GET /search` (`search`)")
+fn routes_doc<'a>(summary: &str, routes: impl Iterator<Item = &'a RouteInfo>) -> String {
+    let routes = routes
+        .map(|route| {
+            format!(
+                "- `{} {}{}` (`{}`)",
+                route.verb, route.prefix, route.path, route.handler_name
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if routes.is_empty() {
+        return format!("{}.", summary);
+    }
+    format!("{}:\n\n{}", summary, routes.join("\n"))
+}
+
+// `#[doc(hidden)]` for generated items that shouldn't appear in the crate's rustdoc
+fn doc_hidden_attr(doc_hidden: bool) -> proc_macro2::TokenStream {
+    if doc_hidden {
+        quote!(#[doc(hidden)])
+    } else {
+        quote!()
+    }
+}
+
+// `MultipartFormConfig` enforcing an upload limit (e.g., "25MB")
+fn multipart_config(limit: &str) -> proc_macro2::TokenStream {
+    let bytes = args::parse_size(limit).expect("Multipart limits are validated by auto_register");
+    quote! {
+        ::actix_multipart::form::MultipartFormConfig::default().total_limit(#bytes)
+    }
+}
+
+// `PayloadConfig` and `JsonConfig` enforcing a request body limit (e.g., "1MB")
+fn body_limit_configs(actix: &syn::Path, limit: &str) -> [proc_macro2::TokenStream; 2] {
+    let bytes = args::parse_size(limit).expect("Body limits are validated by auto_register");
+    [
+        quote!(#actix::web::PayloadConfig::new(#bytes)),
+        quote!(#actix::web::JsonConfig::default().limit(#bytes)),
+    ]
+}
+
+// The limit the services of a scope share, or the message reporting that they
+// disagree. Resources get their own limit and aren't considered.
+fn shared_scope_limit<'a>(
+    prefix: &str,
+    routes: &[&'a RouteInfo],
+    name: &str,
+    limit: fn(&'a RouteInfo) -> Option<&'a str>,
+) -> Result<Option<&'a str>, String> {
+    let mut limits = routes
+        .iter()
+        .filter(|route| route.kind == RouteKind::Service)
+        .filter_map(|route| limit(route))
+        .collect::<Vec<_>>();
+    limits.sort_unstable();
+    limits.dedup();
+    if limits.len() > 1 {
+        return Err(format!(
+            "The routes of `{}` declare different {} limits ({}), register them with the `method` and `path` options of auto_register so each resource gets its own limit.",
+            prefix,
+            name,
+            limits.join(", ")
+        ));
+    }
+    Ok(limits.first().copied())
+}
+
+// Verb of a route in listings, flagging event streams, canaries, A/B variants
+// and protected routes (e.g., "GET (SSE)")
+fn verb_label(route: &RouteInfo) -> String {
+    let mut label = route.verb.to_string();
+    if route.sse {
+        label.push_str(" (SSE)");
+    }
+    if let Some(percent) = route.canary {
+        label.push_str(&format!(" (canary {}%)", percent));
+    }
+    if let Some((variant, weight)) = &route.variant {
+        label.push_str(&format!(" (variant {}, weight {})", variant, weight));
+    }
+    if let Some(media_type) = &route.produces {
+        label.push_str(&format!(" (produces {})", media_type));
+    }
+    if route.admin {
+        label.push_str(" (admin)");
+    }
+    if route.api_key.is_some() {
+        label.push_str(" (API key)");
+    }
+    if route.session_required {
+        label.push_str(" (session)");
+    }
+    if !route.jwt_scopes.is_empty() {
+        label.push_str(&format!(" (JWT {})", route.jwt_scopes.join(", ")));
+    }
+    if route.idempotent {
+        label.push_str(" (idempotent)");
+    }
+    label
+}
+
+// Render error status codes for listings (e.g., "400, 404")
+fn error_codes_to_string(errors: &[u16]) -> String {
+    errors
+        .iter()
+        .map(|code| code.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// HTTP method of a registered verb: WebSocket upgrades (`WS`) are GET requests
+fn http_method(verb: &str) -> &str {
+    match verb {
+        "WS" => "GET",
+        verb => verb,
+    }
+}
+
+// Check that a function has the shape of an Actix handler: an async free
+// function returning a `Responder`
+fn validate_handler(input_fn: &ItemFn) -> syn::Result<()> {
+    let sig = &input_fn.sig;
+    let mut errors: Vec<syn::Error> = Vec::new();
+
+    if sig.asyncness.is_none() {
+        errors.push(syn::Error::new_spanned(
+            sig.fn_token,
+            format!(
+                "auto_register expects an async handler, declare it as `async fn {}`.",
+                sig.ident
+            ),
+        ));
+    }
+
+    if let Some(receiver) = sig.receiver() {
+        errors.push(syn::Error::new_spanned(
+            receiver,
+            "auto_register handlers cannot take `self`, Actix calls them as free functions. \
+            Put auto_register on the impl block instead to register its methods through the \
+            `web::Data` of the controller (e.g., #[auto_register(\"/scope\")] impl Controller { ... }).",
+        ));
+    }
+
+    if let syn::ReturnType::Default = sig.output {
+        errors.push(syn::Error::new_spanned(
+            sig,
+            "auto_register handlers must return a type implementing `Responder` \
+            (e.g., `-> impl Responder` or `-> HttpResponse`).",
+        ));
+    }
+
+    match combine_errors(errors) {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+// Whether to trace what the macros parse and store, either for one invocation
+// (`debug = true`) or for the whole build (`RAR_DEBUG=1`)
+fn debug_enabled(debug: bool) -> bool {
+    debug || std::env::var("RAR_DEBUG").is_ok_and(|value| value == "1")
+}
+
+// Merge errors so they are all reported by a single expansion
+fn combine_errors(errors: Vec<syn::Error>) -> Option<syn::Error> {
+    errors.into_iter().reduce(|mut combined, error| {
+        combined.combine(error);
+        combined
+    })
+}
+
+// Whether a handler has type or const parameters that `generics` does not provide
+fn args_generics_missing(sig: &syn::Signature, generics: &Option<String>) -> bool {
+    generics.is_none()
+        && sig
+            .generics
+            .params
+            .iter()
+            .any(|param| !matches!(param, syn::GenericParam::Lifetime(_)))
+}
+
+// Move every canary right before the first other route with the same verb and
+// path, keeping the order of everything else
+fn canaries_first(routes: Vec<(usize, &RouteInfo)>) -> Vec<(usize, &RouteInfo)> {
+    let has_stable = |canary: &RouteInfo| {
+        routes
+            .iter()
+            .any(|(_, route)| route.canary.is_none() && same_route(route, canary))
+    };
+
+    let mut ordered: Vec<(usize, &RouteInfo)> = Vec::with_capacity(routes.len());
+    for &(index, route) in &routes {
+        if route.canary.is_some() {
+            // Placed with their stable route, or kept in place without one
+            if !has_stable(route) {
+                ordered.push((index, route));
+            }
+            continue;
+        }
+        let placed_before = ordered.iter().any(|(_, other)| same_route(other, route));
+        if !placed_before {
+            ordered.extend(
+                routes
+                    .iter()
+                    .filter(|(_, canary)| canary.canary.is_some() && same_route(canary, route)),
+            );
+        }
+        ordered.push((index, route));
+    }
+    ordered
+}
+
+// Whether two routes answer the same verb and path
+fn same_route(a: &RouteInfo, b: &RouteInfo) -> bool {
+    a.verb == b.verb && a.path == b.path
+}
+
+// Keep the A/B variants of each path together, ordered by name, where the first
+// one was registered
+fn group_variants(routes: Vec<(usize, &RouteInfo)>) -> Vec<(usize, &RouteInfo)> {
+    let mut ordered: Vec<(usize, &RouteInfo)> = Vec::with_capacity(routes.len());
+    for &(index, route) in &routes {
+        if route.variant.is_none() {
+            ordered.push((index, route));
+            continue;
+        }
+        let grouped = ordered
+            .iter()
+            .any(|(_, other)| other.variant.is_some() && same_route(other, route));
+        if !grouped {
+            let mut variants = routes
+                .iter()
+                .filter(|(_, other)| other.variant.is_some() && same_route(other, route))
+                .copied()
+                .collect::<Vec<_>>();
+            variants.sort_by(|(_, a), (_, b)| a.variant.cmp(&b.variant));
+            ordered.extend(variants);
+        }
+    }
+    ordered
+}
+
+// Guard functions of the A/B variants, grouped and ordered by `group_variants`,
+// with the route each one guards. Each variant takes its weight out of the
+// requests the previous ones declined, counting them in turn, and the last one
+// takes the rest; paths whose variants are misconfigured are returned as error
+// messages instead.
+fn variant_guards<'a>(
+    actix: &syn::Path,
+    prefix: &str,
+    routes: &[(usize, &'a RouteInfo)],
+) -> (Vec<(&'a RouteInfo, proc_macro2::TokenStream)>, Vec<String>) {
+    let mut guards = Vec::new();
+    let mut errors = Vec::new();
+
+    let mut remaining = routes;
+    while let Some(((_, first), rest)) = remaining.split_first() {
+        if first.variant.is_none() {
+            remaining = rest;
+            continue;
+        }
+        let count = remaining
+            .iter()
+            .take_while(|(_, route)| route.variant.is_some() && same_route(route, first))
+            .count();
+        let (variants, rest) = remaining.split_at(count);
+        remaining = rest;
+
+        let route_label = format!("{} {}{}", first.verb, prefix, first.path);
+        let names = variants
+            .iter()
+            .filter_map(|(_, route)| route.variant.as_ref().map(|(name, _)| name.as_str()))
+            .collect::<Vec<_>>();
+        if names.len() < 2 {
+            errors.push(format!(
+                "`{}` has a single A/B variant `{}`, register another variant of it or remove the `variant` option.",
+                route_label, names[0]
+            ));
+            continue;
+        }
+        if let Some(duplicate) = names.windows(2).find(|pair| pair[0] == pair[1]) {
+            errors.push(format!(
+                "`{}` registers the A/B variant `{}` twice, give each variant its own name.",
+                route_label, duplicate[0]
+            ));
+            continue;
+        }
+        if routes.iter().any(|(_, route)| {
+            route.variant.is_none() && route.canary.is_none() && same_route(route, first)
+        }) {
+            errors.push(format!(
+                "`{}` has both A/B variants and a handler without `variant`, which would never be reached.",
+                route_label
+            ));
+            continue;
+        }
+
+        let mut weight_left: u64 = variants
+            .iter()
+            .filter_map(|(_, route)| route.variant.as_ref())
+            .map(|(_, weight)| u64::from(*weight))
+            .sum();
+        for (position, (_, route)) in variants.iter().enumerate() {
+            let guard = guard_fn_ident(&route.handler_name, "variant");
+            let weight = route
+                .variant
+                .as_ref()
+                .map_or(1, |(_, weight)| u64::from(*weight));
+            let accept = if position + 1 == variants.len() {
+                quote!(true)
+            } else {
+                quote! {
+                    static REQUESTS: ::std::sync::atomic::AtomicU64 = ::std::sync::atomic::AtomicU64::new(0);
+                    REQUESTS.fetch_add(1, ::std::sync::atomic::Ordering::Relaxed) % #weight_left < #weight
+                }
+            };
+            guards.push((
+                *route,
+                quote! {
+                    fn #guard(_ctx: &#actix::guard::GuardContext<'_>) -> bool {
+                        #accept
+                    }
+                },
+            ));
+            weight_left -= weight;
+        }
+    }
+
+    (guards, errors)
+}
+
+// Guard functions of the negotiated handlers, with the route each one guards:
+// each one accepts the requests whose `Accept` header prefers its media type
+// over the ones of the other handlers of the path; paths whose handlers are
+// misconfigured are returned as error messages instead.
+fn produces_guards<'a>(
+    actix: &syn::Path,
+    prefix: &str,
+    routes: &[(usize, &'a RouteInfo)],
+) -> (Vec<(&'a RouteInfo, proc_macro2::TokenStream)>, Vec<String>) {
+    let mut guards = Vec::new();
+    let mut errors = Vec::new();
+
+    for (position, (_, first)) in routes.iter().enumerate() {
+        let is_first = !routes[..position]
+            .iter()
+            .any(|(_, route)| route.produces.is_some() && same_route(route, first));
+        if first.produces.is_none() || !is_first {
+            continue;
+        }
+
+        let route_label = format!("{} {}{}", first.verb, prefix, first.path);
+        let handlers = routes
+            .iter()
+            .filter(|(_, route)| route.produces.is_some() && same_route(route, first))
+            .map(|(_, route)| *route)
+            .collect::<Vec<_>>();
+        let media_types = handlers
+            .iter()
+            .filter_map(|route| route.produces.as_deref())
+            .collect::<Vec<_>>();
+        if let Some(duplicate) = media_types
+            .iter()
+            .enumerate()
+            .find(|(index, media_type)| media_types[..*index].contains(media_type))
+            .map(|(_, media_type)| media_type)
+        {
+            errors.push(format!(
+                "`{}` has two handlers producing `{}`, give each handler its own media type.",
+                route_label, duplicate
+            ));
+            continue;
+        }
+        if routes
+            .iter()
+            .any(|(_, route)| route.produces.is_none() && same_route(route, first))
+        {
+            errors.push(format!(
+                "`{}` has both negotiated handlers and a handler without `produces`, add `produces` to it.",
+                route_label
+            ));
+            continue;
+        }
+
+        for (position, route) in handlers.iter().enumerate() {
+            let guard = guard_fn_ident(&route.handler_name, "produces");
+            guards.push((
+                *route,
+                quote! {
+                    fn #guard(ctx: &#actix::guard::GuardContext<'_>) -> bool {
+                        let accept = ctx
+                            .head()
+                            .headers()
+                            .get("accept")
+                            .and_then(|accept| accept.to_str().ok());
+                        __route_negotiate(accept, &[#(#media_types),*]) == Some(#position)
+                    }
+                },
+            ));
+        }
+    }
+
+    (guards, errors)
+}
+
+// The function picking which of a path's media types answers an `Accept` header:
+// the one with the highest quality, as given by the most specific range matching
+// it, or the first registered one on a tie or without a header
+fn negotiate_fn() -> proc_macro2::TokenStream {
+    quote! {
+        fn __route_negotiate(accept: Option<&str>, media_types: &[&str]) -> Option<usize> {
+            let Some(accept) = accept.filter(|accept| !accept.trim().is_empty()) else {
+                return Some(0);
+            };
+            let quality = |media_type: &str| {
+                let kind = media_type.split('/').next().unwrap_or_default();
+                accept
+                    .split(',')
+                    .filter_map(|range| {
+                        let mut params = range.split(';');
+                        let range = params.next()?.trim();
+                        let specificity = if range.eq_ignore_ascii_case(media_type) {
+                            2
+                        } else if range
+                            .strip_suffix("/*")
+                            .is_some_and(|range| range.eq_ignore_ascii_case(kind))
+                        {
+                            1
+                        } else if range == "*/*" {
+                            0
+                        } else {
+                            return None;
+                        };
+                        let quality = params
+                            .find_map(|param| param.trim().strip_prefix("q="))
+                            .map_or(Some(1.0), |quality| quality.trim().parse::<f32>().ok())?;
+                        Some((specificity, quality))
+                    })
+                    .max_by_key(|(specificity, _)| *specificity)
+                    .map_or(0.0, |(_, quality)| quality)
+            };
+            let mut best: Option<(usize, f32)> = None;
+            for (index, media_type) in media_types.iter().enumerate() {
+                let quality = quality(media_type);
+                let better = match best {
+                    Some((_, best)) => quality > best,
+                    None => true,
+                };
+                if quality > 0.0 && better {
+                    best = Some((index, quality));
+                }
+            }
+            best.map(|(index, _)| index)
+        }
+    }
+}
+
+// Maximum number of `.service()` calls chained in a single generated statement
+const SERVICES_PER_STATEMENT: usize = 64;
+
+// Registrations per unique module key. Keys are kept sorted so every generated
+// listing and export is byte-identical across builds; routes keep their
+// expansion (source) order, which Actix relies on to match overlapping paths.
+type Registry = BTreeMap<String, Vec<RouteInfo>>;
+
+// Use a global RwLock map for storing registrations per unique module key
+static REGISTRATION_MAP: Lazy<RwLock<Registry>> = Lazy::new(|| RwLock::new(BTreeMap::new()));
+
+// Keys whose `register_service` was generated, with the location of the generator.
+// Macros expand in source order, so a handler declared below the generator (e.g.,
+// in a module declared after it) would be silently missing from its service.
+static GENERATED_KEYS: Lazy<Mutex<BTreeMap<String, String>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+fn generated_keys() -> MutexGuard<'static, BTreeMap<String, String>> {
+    GENERATED_KEYS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+// The path the routes of each generated key are served under, for the full
+// paths of the listings (e.g., "/{tenant}/events", or "" without `use_scope`)
+static SERVED_SCOPES: Lazy<Mutex<BTreeMap<String, String>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+fn served_scopes() -> MutexGuard<'static, BTreeMap<String, String>> {
+    SERVED_SCOPES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+// The path of the scope `register_service` creates for a prefix, before any base
+// path read at runtime: the tenant segment, the locales, then the prefix itself
+// with `use_scope`
+fn scope_path(args: &args::RegisterServiceArgs, prefix: &str) -> String {
+    // Every locale matches a single `{locale}` segment, so the handlers are
+    // registered once and read the locale with `web::Path`
+    let locale_segment = if args.locales.is_empty() {
+        String::new()
+    } else {
+        format!("/{{locale:{}}}", args.locales.join("|"))
+    };
+    format!(
+        "{}{}{}",
+        args.tenant_segment.as_deref().unwrap_or_default(),
+        locale_segment,
+        if args.use_scope { prefix } else { "" }
+    )
+}
+
+// The path clients call for a route: the scope its key is served under, when
+// its generator already expanded, or the key otherwise. The generators reading it
+// fail the build on such keys with `unserved_scope_errors`.
+fn full_path(scope: &str, route: &RouteInfo) -> String {
+    match served_scopes().get(scope) {
+        Some(served) => format!("{}{}", served, route.path),
+        None => format!("{}{}", scope, route.path),
+    }
+}
+
+// The path Actix matches the scope of a key on, relative to where `register_service`
+// is configured: the served scope without the base path read at runtime (e.g., "$APP_BASE")
+fn served_scope(scope: &str) -> String {
+    let served = served_scopes()
+        .get(scope)
+        .cloned()
+        .unwrap_or_else(|| scope.to_string());
+    match served.strip_prefix('$') {
+        Some(marked) => marked[marked.find('/').unwrap_or(marked.len())..].to_string(),
+        None => served,
+    }
+}
+
+// The pattern Actix matches a route on, relative to where `register_service` is
+// configured
+fn served_pattern(scope: &str, route: &RouteInfo) -> String {
+    format!("{}{}", served_scope(scope), route.path)
+}
+
+// Fail the build on the keys whose routes a generator reads the served paths of
+// before their `generate_register_service!` expanded, as they are only known then
+fn unserved_scope_errors<'a>(
+    generator: &str,
+    scopes: impl IntoIterator<Item = &'a str>,
+) -> Vec<proc_macro2::TokenStream> {
+    let served = served_scopes();
+    scopes
+        .into_iter()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter(|scope| !served.contains_key(*scope))
+        .map(|scope| {
+            let message = format!(
+                "`{}` expanded before the `generate_register_service!` of `{}`, invoke it after for the paths of its routes to be known.",
+                generator, scope
+            );
+            quote!(::core::compile_error!(#message);)
+        })
+        .collect()
+}
+
+// Fail the build on a route stored after the service of its key was generated.
+// IDEs re-expand handlers that were already registered, which are let through.
+fn late_route_error(registry: &Registry, route: &RouteInfo) -> Option<proc_macro2::TokenStream> {
+    let generator = generated_keys().get(route.prefix.as_ref())?.clone();
+    let known = registry.get(route.prefix.as_ref()).is_some_and(|routes| {
+        routes
+            .iter()
+            .any(|known| known.handler_name == route.handler_name && known.file == route.file)
+    });
+    if known {
+        return None;
+    }
+    let message = format!(
+        "`{} {}` ({}) is registered under `{}` after `generate_register_service!` ({}) generated its `register_service`, which would miss it. Invoke the generator below the declaration of this handler's module (e.g., at the end of the file).",
+        route.verb, route.path, route.handler_name, route.prefix, generator
+    );
+    Some(quote!(::core::compile_error!(#message);))
+}
+
+// Fail the build on a handler registered twice under its key (e.g., a copy-pasted
+// handler), which would register its service twice. Handlers of other modules
+// sharing the name are registered through their module, and IDEs re-expand the
+// same registration at the same location, so both are let through.
+fn duplicate_route_error(
+    registry: &Registry,
+    route: &RouteInfo,
+) -> Option<proc_macro2::TokenStream> {
+    let first = registry.get(route.prefix.as_ref())?.iter().find(|known| {
+        known.kind != RouteKind::Static
+            && known.handler_name == route.handler_name
+            && known.module_path == route.module_path
+            && (known.file != route.file || known.line != route.line)
+    })?;
+    let message = format!(
+        "Handler '{}' is registered twice under `{}`, at {}:{} and {}:{}. Remove one of the registrations.",
+        route.handler_name, route.prefix, first.file, first.line, route.file, route.line
+    );
+    Some(quote!(::core::compile_error!(#message);))
+}
+
+// Fail the build on an `operation_id` another route already declared, which
+// client generators would turn into clashing methods
+fn duplicate_operation_id_error(
+    registry: &Registry,
+    route: &RouteInfo,
+) -> Option<proc_macro2::TokenStream> {
+    let operation_id = route.operation_id.as_deref()?;
+    let first = registry.values().flatten().find(|known| {
+        known.operation_id.as_deref() == Some(operation_id)
+            && (known.file != route.file || known.line != route.line)
+    })?;
+    let message = format!(
+        "The operation_id \"{}\" of '{}' is already used by '{}' at {}:{}. Operation ids must be unique across the API.",
+        operation_id, route.handler_name, first.handler_name, first.file, first.line
+    );
+    Some(quote!(::core::compile_error!(#message);))
+}
+
+// With `out-dir-manifest`, append the route to the file of `OUT_DIR` read by
+// `load_manifest!`, reporting the failures at the route
+fn out_dir_error(prefix: &str, route: &RouteInfo) -> Option<proc_macro2::TokenStream> {
+    if !cfg!(feature = "out-dir-manifest") {
+        return None;
+    }
+    let message = manifest::append_to_out_dir(prefix, route).err()?;
+    Some(quote!(::core::compile_error!(#message);))
+}
+
+// Share the strings repeated across routes (prefixes, verbs, files, ...) so
+// large registries store each of them once and clone routes cheaply
+fn intern(value: &str) -> Arc<str> {
+    static INTERNED: Lazy<Mutex<HashSet<Arc<str>>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+    let mut interned = INTERNED
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(existing) = interned.get(value) {
+        return existing.clone();
+    }
+    let value: Arc<str> = Arc::from(value);
+    interned.insert(value.clone());
+    value
+}
+
+// Access the registry even if an expansion panicked while holding the lock:
+// IDEs keep the macro server alive, and a poisoned lock would otherwise break
+// every later expansion. Registrations are pushed whole, so the map stays consistent.
+fn read_registry() -> RwLockReadGuard<'static, Registry> {
+    REGISTRATION_MAP
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn write_registry() -> RwLockWriteGuard<'static, Registry> {
+    REGISTRATION_MAP
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+// The doc comments of an item, one line per `///`
+fn doc_comments(attrs: &[syn::Attribute]) -> String {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(doc),
+                        ..
+                    }),
+                ..
+            }) => Some(doc.value().trim().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Whether an item is marked `#[deprecated]`, with or without a note
+fn is_deprecated(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident("deprecated"))
+}
+
+// The predicates of the `#[cfg]` attributes of an item, which still carries them
+// when expanded by an outer attribute (e.g., the methods of a controller)
+fn cfg_predicates(attrs: &[syn::Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .filter_map(|attr| attr.meta.require_list().ok())
+        .map(|list| tokens_to_string(&list.tokens))
+        .collect()
+}
+
+// The `#[cfg]` attributes of a route, to gate the code referencing its handler alike
+fn cfg_attrs(route: &RouteInfo) -> proc_macro2::TokenStream {
+    let predicates = route.cfg.iter().map(|predicate| {
+        predicate
+            .parse::<proc_macro2::TokenStream>()
+            .expect("Predicates are read from attributes")
+    });
+    quote!(#(#[cfg(#predicates)])*)
+}
+
+// Whether an item is marked `#[doc(hidden)]`
+fn is_doc_hidden(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path().is_ident("doc")
+            && matches!(&attr.meta, syn::Meta::List(list) if list.tokens.to_string() == "hidden")
+    })
+}
+
+// Check the options of a route against its verb, and against actix-web 3
+fn check_route_options(
+    args: &args::AutoRegisterArgs,
+    verb: &mut Option<String>,
+    name: &str,
+    span: &dyn quote::ToTokens,
+    errors: &mut Vec<syn::Error>,
+) {
+    // WebSocket upgrades are GET requests, listed with the `WS` verb
+    if args.ws {
+        match verb.as_deref() {
+            Some("GET") => *verb = Some("WS".to_string()),
+            Some(other) => errors.push(syn::Error::new_spanned(
+                span,
+                format!(
+                    "Handler '{}' is a WebSocket upgrade (`ws = true`) and must be a GET route, not {}.",
+                    name, other
+                ),
+            )),
+            None => {}
+        }
+    }
+
+    // Idempotency keys dedupe the retries of requests creating something
+    if args.idempotent {
+        if let Some(other) = verb.as_deref().filter(|verb| *verb != "POST") {
+            errors.push(syn::Error::new_spanned(
+                span,
+                format!(
+                    "Handler '{}' is idempotent (`idempotent = true`) and must be a POST route, not {}.",
+                    name, other
+                ),
+            ));
+        }
+    }
+
+    // Conditional requests only apply to reads
+    if args.etag {
+        if let Some(other) = verb.as_deref().filter(|verb| *verb != "GET") {
+            errors.push(syn::Error::new_spanned(
+                span,
+                format!(
+                    "Handler '{}' is tagged (`etag = true`) and must be a GET route, not {}.",
+                    name, other
+                ),
+            ));
+        }
+    }
+
+    // Upload limits are configured through actix-multipart's `MultipartFormConfig`,
+    // which only exists for actix-web 4
+    if cfg!(feature = "actix3") && args.multipart_limit.is_some() {
+        errors.push(syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "`multipart(limit = ...)` needs actix-web 4, declare the route as `multipart` with the `actix3` feature.",
+        ));
+    }
+
+    // Slow request warnings, circuit breakers, access checks and audits run in a middleware
+    // built with `middleware::from_fn`, and canary and variant guards read a
+    // `GuardContext`, which only exist for actix-web 4
+    if cfg!(feature = "actix3") {
+        for (option, enabled) in [
+            ("warn_slower_than_ms", args.warn_slower_than_ms.is_some()),
+            ("circuit_breaker", args.circuit_breaker.is_some()),
+            ("flag", args.flag.is_some()),
+            ("api_key", args.api_key.is_some()),
+            ("session", args.session_required),
+            ("jwt_scopes", !args.jwt_scopes.is_empty()),
+            ("audit", args.audit),
+            ("idempotent", args.idempotent),
+            ("consumes", args.consumes.is_some()),
+            ("produces", args.produces.is_some()),
+            ("etag", args.etag),
+            ("headers", !args.headers.is_empty()),
+            ("canary", args.canary.is_some()),
+            ("variant", args.variant.is_some()),
+            ("requires", !args.requires.is_empty()),
+        ] {
+            if enabled {
+                errors.push(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    format!(
+                        "`{}` needs actix-web 4 and is not available with the `actix3` feature.",
+                        option
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+#[proc_macro_attribute]
+pub fn auto_register(attr: TokenStream, item: TokenStream) -> TokenStream {
+    // Re-exported handlers are registered through the path of the re-export
+    if let Ok(item_use) = syn::parse::<syn::ItemUse>(item.clone()) {
+        return auto_register_reexport(attr, item_use);
+    }
+    // Methods of a controller are registered through free functions delegating to them
+    if let Ok(item_impl) = syn::parse::<syn::ItemImpl>(item.clone()) {
+        return auto_register_controller(attr, item_impl);
+    }
+
+    // Parse the input function, pointing at the item when it isn't one
+    let input_fn = match syn::parse::<ItemFn>(item.clone()) {
+        Ok(input_fn) => input_fn,
+        Err(_) => {
+            let item = proc_macro2::TokenStream::from(item);
+            let error = syn::Error::new_spanned(
+                &item,
+                "auto_register can only be applied to handler functions \
+                (e.g., `pub async fn search() -> impl Responder`), to their re-export \
+                (e.g., `pub use crate::internal::search;`), or to the impl block of a controller.",
+            )
+            .to_compile_error();
+            return TokenStream::from(quote! { #error #item });
+        }
+    };
+    // Raw identifiers (e.g., `r#match`) are stored by name and re-escaped when emitted
+    let fn_name = input_fn.sig.ident.unraw().to_string();
+
+    // Collect every problem of the registration to report them all at once
+    let mut errors: Vec<syn::Error> = Vec::new();
+
+    // Reject handlers Actix could not call before generating code around them
+    if let Err(error) = validate_handler(&input_fn) {
+        errors.push(error);
+    }
+
+    // Parse the prefix as a string literal, or derive it from the module, followed
+    // by the route options
+    let args = match syn::parse::<args::AutoRegisterArgs>(attr) {
+        Ok(args) => Some(args),
+        Err(error) => {
+            errors.push(error);
+            None
+        }
+    };
+
+    // A second `auto_register` with the same prefix (e.g., a copy-pasted line) would
+    // register the handler twice under its key
+    if let Some(args) = &args {
+        for attr in &input_fn.attrs {
+            let is_auto_register = attr
+                .path()
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "auto_register");
+            if !is_auto_register {
+                continue;
+            }
+            let prefix = match &attr.meta {
+                syn::Meta::Path(_) => Some(call_site_module_prefix()),
+                _ => attr
+                    .parse_args::<args::AutoRegisterArgs>()
+                    .ok()
+                    .map(|args| args.prefix),
+            };
+            if prefix.as_deref() == Some(args.prefix.as_str()) {
+                errors.push(syn::Error::new_spanned(
+                    attr,
+                    format!(
+                        "Handler '{}' is registered twice under `{}`, remove the duplicated auto_register.",
+                        fn_name, args.prefix
+                    ),
+                ));
+            }
+        }
+    }
+
+    // Extract the route path and HTTP verb from the function attributes
+    let mut route_path = None;
+    let mut verb = None;
+    let mut route_macro = None;
+
+    for attr in &input_fn.attrs {
+        if let Some(segment) = attr.path().segments.last() {
+            if ["get", "post", "put", "delete", "patch"]
+                .contains(&segment.ident.to_string().as_str())
+            {
+                verb = Some(segment.ident.to_string().to_uppercase());
+                route_macro = Some(attr);
+                if let Ok(route_literal) = attr.parse_args::<LitStr>() {
+                    route_path = Some(route_literal.value());
+                }
+            }
+        }
+    }
+
+    // Without an Actix route macro, the verb and path come from the `method`
+    // and `path` options and the handler is registered as a resource
+    let mut kind = RouteKind::Service;
+    let mut resource_misused = false;
+    if let Some(args) = args
+        .as_ref()
+        .filter(|args| args.method.is_some() || args.path.is_some())
+    {
+        if let Some(route_macro) = route_macro {
+            resource_misused = true;
+            errors.push(syn::Error::new_spanned(
+                route_macro,
+                format!(
+                    "Handler '{}' has both an Actix route macro and the `method`/`path` options of auto_register, keep only one of them.",
+                    fn_name
+                ),
+            ));
+        } else if args.method.is_none() || args.path.is_none() {
+            resource_misused = true;
+            errors.push(syn::Error::new_spanned(
+                &input_fn.sig.ident,
+                format!(
+                    "Handler '{}' needs both `method` and `path` to be registered without an Actix route macro \
+                    (e.g., #[auto_register(\"/scope\", method = \"GET\", path = \"/search\")]).",
+                    fn_name
+                ),
+            ));
+        } else {
+            kind = RouteKind::Resource;
+            verb = args.method.clone();
+            route_path = args.path.clone();
+        }
+    }
+
+    // Actix route macros replace the handler with a unit struct, which takes no
+    // generic arguments, so generic handlers are registered as resources
+    if let Some(route_macro) =
+        route_macro.filter(|_| args.as_ref().is_some_and(|args| args.generics.is_some()))
+    {
+        errors.push(syn::Error::new_spanned(
+            route_macro,
+            format!(
+                "Handler '{}' can't be registered with `generics` through an Actix route macro, \
+                use the `method` and `path` options of auto_register instead \
+                (e.g., #[auto_register(\"/scope\", method = \"GET\", path = \"/search\", generics = \"<PgStore>\")]).",
+                fn_name
+            ),
+        ));
+    }
+
+    if let Some(args) = &args {
+        check_route_options(args, &mut verb, &fn_name, &input_fn.sig.ident, &mut errors);
+    }
+
+    // Capture the handler's doc comments for the generated documentation
+    let docs = doc_comments(&input_fn.attrs);
+
+    // Find the types deserialized from the request body and query string
+    // through `Json<T>` and `Query<T>` extractors
+    let body_type = extractor_type(&input_fn.sig, "Json");
+    let query_type = extractor_type(&input_fn.sig, "Query");
+
+    // A generic handler can only be registered once its generic arguments are known
+    let route_generics = args.as_ref().and_then(|args| args.generics.clone());
+    if args.is_some() && args_generics_missing(&input_fn.sig, &route_generics) {
+        errors.push(syn::Error::new_spanned(
+            &input_fn.sig.generics,
+            format!(
+                "Handler '{}' is generic, add the arguments to register it with to auto_register \
+                (e.g., #[auto_register(\"/scope\", generics = \"<PgStore>\")]).",
+                fn_name
+            ),
+        ));
+    }
+
+    // Shadowed requests are replayed with copies of the handler's arguments, so
+    // each one must be bound to a name
+    if args.as_ref().is_some_and(|args| args.shadow_to.is_some()) {
+        for arg in &input_fn.sig.inputs {
+            if let syn::FnArg::Typed(arg) = arg {
+                if !matches!(&*arg.pat, syn::Pat::Ident(_)) {
+                    errors.push(syn::Error::new_spanned(
+                        &arg.pat,
+                        "Handlers with `shadow_to` must bind each argument to a name (e.g., `path: web::Path<u32>`) to replay it to the shadow handler.",
+                    ));
+                }
+            }
+        }
+    }
+
+    // Validate the extracted route path and HTTP verb, unless the `method` and
+    // `path` options were already reported as misused
+    if (route_path.is_none() || verb.is_none()) && !resource_misused {
+        errors.push(syn::Error::new_spanned(
+            &input_fn.sig.ident,
+            format!(
+                "Could not extract the route path or verb from attributes on function '{}'. Ensure it has a valid Actix route macro like \
+                #[get(\"/path\")], or the `method` and `path` options of auto_register.",
+                fn_name
+            ),
+        ));
+    }
+
+    // Report all the problems together, keeping the handler so the rest of the
+    // module still compiles against it
+    if let Some(error) = combine_errors(errors) {
+        let error = error.to_compile_error();
+        return TokenStream::from(quote! { #error #input_fn });
+    }
+    let args = args.expect("Arguments are parsed when no error was reported");
+    let prefix = args.prefix;
+
+    // A canary handler only takes the requests whose header hashes below its
+    // percentage, the others fall through to the stable handler of the path
+    let mut input_fn = input_fn;
+    let mut canary_guard = None;
+    let actix = &args.actix_crate;
+    if let Some(percent) = args.canary {
+        let guard = guard_fn_ident(&fn_name, "canary");
+        let header = args.canary_header.as_deref().unwrap_or("x-request-id");
+        let percent = u64::from(percent);
+        if kind == RouteKind::Service {
+            add_route_macro_arg(&mut input_fn, "guard", &guard.to_string());
+        }
+        canary_guard = Some(quote! {
+            #[doc(hidden)]
+            pub fn #guard(ctx: &#actix::guard::GuardContext<'_>) -> bool {
+                use ::std::hash::{Hash, Hasher};
+                let Some(key) = ctx.head().headers().get(#header) else {
+                    return false;
+                };
+                let mut hasher = ::std::collections::hash_map::DefaultHasher::new();
+                key.as_bytes().hash(&mut hasher);
+                hasher.finish() % 100 < #percent
+            }
+        });
+    }
+
+    // A/B variants and negotiated handlers are guarded by `register_service`, which
+    // knows every handler of the path, through a guard defined next to the handler
+    // for its route macro to name
+    let mut slot_guards = Vec::new();
+    for (guard_kind, enabled) in [
+        ("variant", args.variant.is_some()),
+        ("produces", args.produces.is_some()),
+    ] {
+        if kind == RouteKind::Service && enabled {
+            add_route_macro_arg(
+                &mut input_fn,
+                "guard",
+                &guard_fn_ident(&fn_name, guard_kind).to_string(),
+            );
+            slot_guards.push(slot_guard(actix, &fn_name, guard_kind));
+        }
+    }
+
+    // Services run the per-route behaviors of `register_service` in a middleware
+    // their route macro wraps them with, innermost so their own `Logger` sees the
+    // responses it answers early
+    let mut service_slot = None;
+    if kind == RouteKind::Service && !cfg!(feature = "actix3") {
+        let (_, route_middleware) = middleware::slot_idents(&fn_name);
+        let wrap = quote!(#actix::middleware::from_fn(#route_middleware)).to_string();
+        add_route_macro_arg(&mut input_fn, "wrap", &wrap);
+        service_slot = Some(middleware::service_slot(actix, &fn_name));
+    }
+
+    // Routes with their own log format are wrapped with an Actix `Logger`
+    if let (RouteKind::Service, Some(format)) = (kind, &args.log_format) {
+        let logger = quote!(#actix::middleware::Logger::new(#format)).to_string();
+        add_route_macro_arg(&mut input_fn, "wrap", &logger);
+    }
+
+    // Replay the request to the shadow handler in the background, with copies of
+    // the extracted arguments, before running the handler itself
+    if let Some(shadow) = &args.shadow_to {
+        let arg_names = input_fn
+            .sig
+            .inputs
+            .iter()
+            .filter_map(|arg| match arg {
+                syn::FnArg::Typed(arg) => match &*arg.pat {
+                    syn::Pat::Ident(pat) => Some(pat.ident.clone()),
+                    _ => None,
+                },
+                syn::FnArg::Receiver(_) => None,
+            })
+            .collect::<Vec<_>>();
+        let block = &input_fn.block;
+        input_fn.block = syn::parse_quote!({
+            {
+                #(let #arg_names = ::core::clone::Clone::clone(&#arg_names);)*
+                #actix::rt::spawn(async move {
+                    let _ = #shadow(#(#arg_names),*).await;
+                });
+            }
+            #block
+        });
+    }
+
+    // Locate the handler in the user's sources so listings can point back to it
+    let fn_span = input_fn.sig.ident.span().unwrap();
+
+    // Use empty route path if valid (e.g., `""`)
+    let route_info = RouteInfo {
+        prefix: intern(&prefix),
+        handler_name: intern(&fn_name),
+        path: intern(&route_path.unwrap_or_default()),
+        verb: intern(&verb.unwrap()),
+        module_path: intern(&item_module_path(&fn_span.file())),
+        file: intern(&fn_span.file()),
+        line: fn_span.line(),
+        docs,
+        body_type: body_type.as_ref().map(|ty| tokens_to_string(&quote!(#ty))),
+        query_type: query_type.as_ref().map(|ty| tokens_to_string(&quote!(#ty))),
+        example_response: args.example_response,
+        example_request: args.example_request,
+        operation_id: args.operation_id,
+        errors: args.errors,
+        generics: args.generics,
+        kind,
+        sse: args.sse,
+        multipart: args.multipart,
+        multipart_limit: args.multipart_limit,
+        warn_slower_than_ms: args.warn_slower_than_ms,
+        circuit_breaker: args.circuit_breaker,
+        canary: args.canary,
+        variant: args
+            .variant
+            .map(|variant| (variant, args.weight.unwrap_or(1))),
+        shadow_to: args
+            .shadow_to
+            .as_ref()
+            .map(|shadow| tokens_to_string(&quote!(#shadow))),
+        flag: args.flag,
+        roles: args.roles,
+        api_key: args.api_key,
+        csrf_exempt: args.csrf_exempt,
+        session_required: args.session_required,
+        jwt_scopes: args.jwt_scopes,
+        audit: args.audit,
+        idempotent: args.idempotent,
+        body_limit: args.body_limit,
+        consumes: args.consumes,
+        produces: args.produces,
+        etag: args.etag,
+        headers: args.headers,
+        log_format: args.log_format,
+        log: args.log,
+        handler_path: args.handler_path,
+        requires: args
+            .requires
+            .iter()
+            .map(|ty| tokens_to_string(&quote!(#ty)))
+            .collect(),
+        deprecated: is_deprecated(&input_fn.attrs),
+        doc_hidden: is_doc_hidden(&input_fn.attrs),
+        cfg: cfg_predicates(&input_fn.attrs),
+        ..RouteInfo::default()
+    };
+
+    if debug_enabled(args.debug) {
+        eprintln!(
+            "[register_actix_routes] auto_register: stored {} {} -> {} ({}:{}) under key {:?}",
+            route_info.verb,
+            route_info.path,
+            route_info.handler_name,
+            route_info.file,
+            route_info.line,
+            prefix
+        );
+    }
+
+    // Safely store the route information
+    let mut map = write_registry();
+    let late_error = late_route_error(&map, &route_info);
+    let duplicate_error = duplicate_route_error(&map, &route_info);
+    let operation_id_error = duplicate_operation_id_error(&map, &route_info);
+    let out_dir_error = out_dir_error(&prefix, &route_info);
+    map.entry(prefix.clone()).or_default().push(route_info);
+
+    // With the `schemars` feature, expose the body and query schemas next to
+    // the handler, where `T` resolves, so the manifest can embed them
+    let mut schema_fns = Vec::new();
+    if cfg!(feature = "schemars") {
+        for (part, ty) in [("body", &body_type), ("query", &query_type)] {
+            if let Some(ty) = ty {
+                let schema_fn = schema_fn_ident(&fn_name, part);
+                schema_fns.push(quote! {
+                    #[doc(hidden)]
+                    pub fn #schema_fn() -> String {
+                        ::serde_json::to_string(&::schemars::schema_for!(#ty))
+                            .expect("Failed to serialize the schema")
+                    }
+                });
+            }
+        }
+    }
+
+    // Generate the original function definition
+    let expanded = quote! {
+        #late_error
+        #duplicate_error
+        #operation_id_error
+        #out_dir_error
+        #input_fn
+        #(#schema_fns)*
+        #canary_guard
+        #(#slot_guards)*
+        #service_slot
+    };
+
+    TokenStream::from(expanded)
+}
+
+// Register a handler re-exported with `pub use` (e.g., by a facade module) through
+// the path of the re-export. The attribute doesn't see the Actix route macro of the
+// handler, so its verb and path come from the `method` and `path` options.
+fn auto_register_reexport(attr: TokenStream, item_use: syn::ItemUse) -> TokenStream {
+    let mut errors = Vec::new();
+    let args = match syn::parse::<args::AutoRegisterArgs>(attr) {
+        Ok(args) => Some(args),
+        Err(error) => {
+            errors.push(error);
+            None
+        }
+    };
+
+    let name = reexported_name(&item_use.tree);
+    if name.is_none() {
+        errors.push(syn::Error::new_spanned(
+            &item_use.tree,
+            "auto_register registers one re-exported handler per `pub use` (e.g., `pub use crate::internal::search;`), so each gets its own `method` and `path`.",
+        ));
+    }
+
+    let mut verb = args.as_ref().and_then(|args| args.method.clone());
+    if let (Some(args), Some(name)) = (&args, name) {
+        let handler_name = name.unraw().to_string();
+        if args.method.is_none() || args.path.is_none() {
+            errors.push(syn::Error::new_spanned(
+                name,
+                format!(
+                    "Re-exported handler '{}' needs the `method` and `path` of its Actix route macro \
+                    (e.g., #[auto_register(\"/scope\", method = \"GET\", path = \"/search\")]).",
+                    handler_name
+                ),
+            ));
+        }
+        // These options rewrite the handler function, which isn't at hand
+        for (option, enabled) in [
+            ("generics", args.generics.is_some()),
+            ("canary", args.canary.is_some()),
+            ("variant", args.variant.is_some()),
+            ("produces", args.produces.is_some()),
+            ("shadow_to", args.shadow_to.is_some()),
+            ("log_format", args.log_format.is_some()),
+        ] {
+            if enabled {
+                errors.push(syn::Error::new_spanned(
+                    name,
+                    format!(
+                        "`{}` changes the handler function and can't be set on its re-export.",
+                        option
+                    ),
+                ));
+            }
+        }
+        // These ones run in the middleware the handler's route macro wraps it with
+        for (option, enabled) in [
+            ("warn_slower_than_ms", args.warn_slower_than_ms.is_some()),
+            ("circuit_breaker", args.circuit_breaker.is_some()),
+            ("flag", args.flag.is_some()),
+            ("api_key", args.api_key.is_some()),
+            ("session", args.session_required),
+            ("jwt_scopes", !args.jwt_scopes.is_empty()),
+            ("audit", args.audit),
+            ("idempotent", args.idempotent),
+            ("consumes", args.consumes.is_some()),
+            ("etag", args.etag),
+            ("headers", !args.headers.is_empty()),
+        ] {
+            if enabled {
+                errors.push(syn::Error::new_spanned(
+                    name,
+                    format!(
+                        "`{}` runs in a middleware wrapped around the handler by `auto_register`, annotate the handler instead of its re-export.",
+                        option
+                    ),
+                ));
+            }
+        }
+        check_route_options(args, &mut verb, &handler_name, name, &mut errors);
+    }
+
+    if let Some(error) = combine_errors(errors) {
+        let error = error.to_compile_error();
+        return TokenStream::from(quote! { #error #item_use });
+    }
+    let args = args.expect("Arguments are parsed when no error was reported");
+    let name = name.expect("The re-exported name is found when no error was reported");
+    let prefix = args.prefix;
+
+    // Locate the re-export in the user's sources, whose module the handler is
+    // registered through (e.g., `crate::api::search`)
+    let span = name.span().unwrap();
+    let module_path = item_module_path(&span.file());
+    let handler_path = args
+        .handler_path
+        .unwrap_or_else(|| format!("{}::{}", module_path, name));
+
+    let route_info = RouteInfo {
+        prefix: intern(&prefix),
+        handler_name: intern(&name.unraw().to_string()),
+        path: intern(&args.path.unwrap_or_default()),
+        verb: intern(&verb.unwrap_or_default()),
+        module_path: intern(&module_path),
+        file: intern(&span.file()),
+        line: span.line(),
+        docs: doc_comments(&item_use.attrs),
+        example_response: args.example_response,
+        example_request: args.example_request,
+        operation_id: args.operation_id,
+        errors: args.errors,
+        sse: args.sse,
+        multipart: args.multipart,
+        multipart_limit: args.multipart_limit,
+        warn_slower_than_ms: args.warn_slower_than_ms,
+        circuit_breaker: args.circuit_breaker,
+        flag: args.flag,
+        roles: args.roles,
+        api_key: args.api_key,
+        csrf_exempt: args.csrf_exempt,
+        session_required: args.session_required,
+        jwt_scopes: args.jwt_scopes,
+        audit: args.audit,
+        idempotent: args.idempotent,
+        body_limit: args.body_limit,
+        consumes: args.consumes,
+        etag: args.etag,
+        headers: args.headers,
+        log: args.log,
+        handler_path: Some(handler_path),
+        reexported: true,
+        requires: args
+            .requires
+            .iter()
+            .map(|ty| tokens_to_string(&quote!(#ty)))
+            .collect(),
+        deprecated: is_deprecated(&item_use.attrs),
+        doc_hidden: is_doc_hidden(&item_use.attrs),
+        cfg: cfg_predicates(&item_use.attrs),
+        ..RouteInfo::default()
+    };
+
+    if debug_enabled(args.debug) {
+        eprintln!(
+            "[register_actix_routes] auto_register: stored {} {} -> {} ({}:{}) under key {:?}",
+            route_info.verb,
+            route_info.path,
+            route_info.handler_path.as_deref().unwrap_or_default(),
+            route_info.file,
+            route_info.line,
+            prefix
+        );
+    }
+
+    // Safely store the route information
+    let mut map = write_registry();
+    let late_error = late_route_error(&map, &route_info);
+    let duplicate_error = duplicate_route_error(&map, &route_info);
+    let operation_id_error = duplicate_operation_id_error(&map, &route_info);
+    let out_dir_error = out_dir_error(&prefix, &route_info);
+    map.entry(prefix).or_default().push(route_info);
+
+    TokenStream::from(quote! {
+        #late_error
+        #duplicate_error
+        #operation_id_error
+        #out_dir_error
+        #item_use
+    })
+}
+
+// Register the methods of a controller annotated with an Actix route macro (e.g.,
+// `#[get("/search")] async fn search(&self) -> impl Responder`). The route macro
+// can't expand inside the impl block, so it moves to a free function named after
+// the controller and the method (e.g., `events_controller_search`), which takes the
+// controller from the app data and delegates to the method. Each of these functions
+// is then registered like a handler, with the options of the impl block.
+fn auto_register_controller(attr: TokenStream, mut item_impl: syn::ItemImpl) -> TokenStream {
+    let mut errors = Vec::new();
+    let actix = match syn::parse::<args::AutoRegisterArgs>(attr.clone()) {
+        Ok(args) => args.actix_crate,
+        Err(error) => {
+            errors.push(error);
+            syn::parse_quote!(actix_web)
+        }
+    };
+    if !item_impl.generics.params.is_empty() || item_impl.trait_.is_some() {
+        errors.push(syn::Error::new_spanned(
+            &item_impl.self_ty,
+            "auto_register can only register the methods of an inherent impl block without generics \
+            (e.g., `impl EventsController { ... }`).",
+        ));
+    }
+    let controller = match &*item_impl.self_ty {
+        syn::Type::Path(ty) if ty.qself.is_none() => ty.path.segments.last().map(|segment| {
+            (
+                ty.path.clone(),
+                snake_case(&segment.ident.unraw().to_string()),
+            )
+        }),
+        _ => None,
+    };
+    let Some((controller, controller_name)) = controller else {
+        errors.push(syn::Error::new_spanned(
+            &item_impl.self_ty,
+            "auto_register expects a controller type (e.g., `impl EventsController { ... }`).",
+        ));
+        let error = combine_errors(errors).unwrap().to_compile_error();
+        return TokenStream::from(quote! { #error #item_impl });
+    };
+
+    let mut shims = Vec::new();
+    for item in &mut item_impl.items {
+        let syn::ImplItem::Fn(method) = item else {
+            continue;
+        };
+        let Some(index) = method.attrs.iter().position(|attr| {
+            attr.path().segments.last().is_some_and(|segment| {
+                ["get", "post", "put", "delete", "patch"]
+                    .contains(&segment.ident.to_string().as_str())
+            })
+        }) else {
+            continue;
+        };
+        let route_macro = method.attrs.remove(index);
+        let sig = &method.sig;
+        let method_name = sig.ident.unraw().to_string();
+
+        // The controller is shared by the app data, so methods can only borrow it
+        let by_reference = matches!(
+            sig.receiver(),
+            Some(receiver) if receiver.reference.is_some() && receiver.mutability.is_none()
+        );
+        if !by_reference {
+            errors.push(syn::Error::new_spanned(
+                sig,
+                format!(
+                    "Method '{}' must take `&self` to be registered, the controller is shared through `web::Data`.",
+                    method_name
+                ),
+            ));
+            continue;
+        }
+        if sig.asyncness.is_none() {
+            errors.push(syn::Error::new_spanned(
+                sig.fn_token,
+                format!(
+                    "auto_register expects an async handler, declare it as `async fn {}`.",
+                    sig.ident
+                ),
+            ));
+            continue;
+        }
+
+        // The function takes the controller, then the extractors of the method
+        let mut inputs = vec![quote!(__controller: #actix::web::Data<#controller>)];
+        let mut arg_names = Vec::new();
+        for (position, arg) in sig.inputs.iter().enumerate() {
+            let syn::FnArg::Typed(arg) = arg else {
+                continue;
+            };
+            let name = match &*arg.pat {
+                syn::Pat::Ident(pat) => pat.ident.clone(),
+                _ => quote::format_ident!("__arg{}", position),
+            };
+            let ty = &arg.ty;
+            inputs.push(quote!(#name: #ty));
+            arg_names.push(name);
+        }
+        // The shim keeps the docs, conditions and lint levels of the method
+        let attrs = method.attrs.iter().filter(|attr| {
+            ["doc", "cfg", "cfg_attr", "deprecated", "allow"]
+                .iter()
+                .any(|name| attr.path().is_ident(name))
+        });
+        // Delegating to a deprecated method is no use of it
+        let allow_deprecated = is_deprecated(&method.attrs).then(|| quote!(#[allow(deprecated)]));
+        let vis = &method.vis;
+        let output = &sig.output;
+        let method_ident = &sig.ident;
+        let shim_ident = syn::Ident::new(
+            &format!("{}_{}", controller_name, method_name),
+            sig.ident.span(),
+        );
+        let shim = quote! {
+            #(#attrs)*
+            #allow_deprecated
+            #route_macro
+            #vis async fn #shim_ident(#(#inputs),*) #output {
+                __controller.#method_ident(#(#arg_names),*).await
+            }
+        };
+        shims.push(auto_register(attr.clone(), TokenStream::from(shim)));
+    }
+
+    if shims.is_empty() && errors.is_empty() {
+        errors.push(syn::Error::new_spanned(
+            &item_impl.self_ty,
+            "auto_register found no method with an Actix route macro in this impl block \
+            (e.g., `#[get(\"/search\")] async fn search(&self) -> impl Responder`).",
+        ));
+    }
+    let error = combine_errors(errors).map(|error| error.to_compile_error());
+    let shims = shims
+        .into_iter()
+        .map(proc_macro2::TokenStream::from)
+        .collect::<Vec<_>>();
+    TokenStream::from(quote! {
+        #error
+        #item_impl
+        #(#shims)*
+    })
+}
+
+// Snake case of a type name, for the functions generated after it
+// (e.g., "EventsController" -> "events_controller", "HTTPClient" -> "http_client")
+fn snake_case(name: &str) -> String {
+    let characters = name.chars().collect::<Vec<_>>();
+    let mut snake = String::new();
+    for (index, character) in characters.iter().enumerate() {
+        if character.is_uppercase() {
+            // A word starts after a lowercase letter, or at the last capital of an acronym
+            let previous = index.checked_sub(1).map(|index| characters[index]);
+            let next = characters.get(index + 1);
+            let starts_word = previous.is_some_and(|previous| {
+                previous.is_lowercase()
+                    || previous.is_uppercase() && next.is_some_and(|next| next.is_lowercase())
+            });
+            if starts_word && !snake.ends_with('_') {
+                snake.push('_');
+            }
+            snake.extend(character.to_lowercase());
+        } else {
+            snake.push(*character);
+        }
+    }
+    snake
+}
+
+// The single name a `use` item brings in (e.g., `crate::internal::{search as find}`
+// -> `find`), none for globs and groups of several names
+fn reexported_name(tree: &syn::UseTree) -> Option<&syn::Ident> {
+    match tree {
+        syn::UseTree::Path(path) => reexported_name(&path.tree),
+        syn::UseTree::Name(name) => Some(&name.ident),
+        syn::UseTree::Rename(rename) => Some(&rename.rename),
+        syn::UseTree::Group(group) if group.items.len() == 1 => reexported_name(&group.items[0]),
+        syn::UseTree::Glob(_) | syn::UseTree::Group(_) => None,
+    }
+}
+
+#[proc_macro]
+pub fn auto_register_static(input: TokenStream) -> TokenStream {
+    let args = match syn::parse::<args::StaticArgs>(input) {
+        Ok(args) => args,
+        Err(error) => return TokenStream::from(error.to_compile_error()),
+    };
+    let prefix = args.prefix.value();
+
+    // Locate the mount in the user's sources so listings can point back to it
+    let span = args.prefix.span().unwrap();
+
+    let route_info = RouteInfo {
+        prefix: intern(&prefix),
+        handler_name: intern(&args.dir),
+        path: intern(&args.path),
+        verb: intern("STATIC"),
+        module_path: intern(&item_module_path(&span.file())),
+        file: intern(&span.file()),
+        line: span.line(),
+        kind: RouteKind::Static,
+        ..RouteInfo::default()
+    };
+
+    if debug_enabled(args.debug) {
+        eprintln!(
+            "[register_actix_routes] auto_register_static: stored {} {} -> {} ({}:{}) under key {:?}",
+            route_info.verb,
+            route_info.path,
+            route_info.handler_name,
+            route_info.file,
+            route_info.line,
+            prefix
+        );
+    }
+
+    // Safely store the mount next to the routes of the same key
+    let mut map = write_registry();
+    let late_error = late_route_error(&map, &route_info);
+    let out_dir_error = out_dir_error(&prefix, &route_info);
+    map.entry(prefix).or_default().push(route_info);
+
+    TokenStream::from(quote!(#late_error #out_dir_error))
+}
+
+#[proc_macro]
+pub fn generate_register_service(input: TokenStream) -> TokenStream {
+    // Parse the macro arguments (prefix followed by options such as use_scope).
+    // On error, still define `register_service` so callers keep resolving while
+    // the invocation is being typed.
+    let args = match syn::parse::<args::RegisterServiceArgs>(input) {
+        Ok(args) => args,
+        Err(error) => {
+            let error = error.to_compile_error();
+            return TokenStream::from(quote! {
+                #error
+                pub fn register_service(_cfg: &mut actix_web::web::ServiceConfig) {}
+            });
+        }
+    };
+    let actix = &args.actix_crate;
+
+    if let Some(path) = &args.scan {
+        return TokenStream::from(scanned_register_service(&args, path));
+    }
+
+    // Remember the key, to report the routes stored for it from now on
+    let span = proc_macro::Span::call_site();
+    generated_keys()
+        .entry(args.module_key.clone())
+        .or_insert_with(|| format!("{}:{}", span.file(), span.line()));
+    let base_path = args
+        .base_path_env
+        .as_deref()
+        .map(|name| format!("${}", name))
+        .unwrap_or_default();
+    served_scopes().insert(
+        args.module_key.clone(),
+        format!("{}{}", base_path, scope_path(&args, &args.module_key)),
+    );
+
+    // Mark the routes of an admin key before reading them, so the listings and
+    // exports expanded afterwards show them as admin-only
+    if args.admin.is_some() {
+        if let Some(routes) = write_registry().get_mut(&args.module_key) {
+            for route in routes {
+                route.admin = true;
+            }
+        }
+    }
+
+    // Safely read handler registrations for the specified module key
+    let map = read_registry();
+    let registrations = map
+        .get(&args.module_key)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    if debug_enabled(args.debug) {
+        let handlers = registrations
+            .iter()
+            .map(|route| format!("{} {} -> {}", route.verb, route.path, route.handler_name))
+            .collect::<Vec<_>>();
+        let keys = map.keys().collect::<Vec<_>>();
+        eprintln!(
+            "[register_actix_routes] generate_register_service: key {:?} has {} route(s) {:?}, registered keys so far: {:?}",
+            args.module_key,
+            handlers.len(),
+            handlers,
+            keys
+        );
+    }
+
+    // Names of the handlers declared in several modules (e.g., an `index` per
+    // module), which can't be registered by their name alone
+    let generator_module = item_module_path(&proc_macro::Span::call_site().file());
+    let mut modules_by_name: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for route in map.values().flatten() {
+        if route.kind != RouteKind::Static {
+            modules_by_name
+                .entry(&route.handler_name)
+                .or_default()
+                .insert(&route.module_path);
+        }
+    }
+    let colliding_names = modules_by_name
+        .into_iter()
+        .filter(|(_, modules)| modules.len() > 1)
+        .map(|(name, _)| name)
+        .collect::<BTreeSet<_>>();
+
+    // Group functions by their prefixes, keeping their index in the key for the
+    // per-route state of the middleware
+    let mut grouped_by_prefix: BTreeMap<&str, Vec<(usize, &RouteInfo)>> = BTreeMap::new();
+    for (index, route) in registrations.iter().enumerate() {
+        grouped_by_prefix
+            .entry(&route.prefix)
+            .or_default()
+            .push((index, route));
+    }
+
+    // Collect the per-route behaviors and the items they share across scopes
+    let mut route_middleware = middleware::RouteMiddleware::default();
+    let mut items = Vec::new();
+    if cfg!(feature = "otel") {
+        route_middleware.before.push(middleware::otel_route());
+    }
+    // The app data is completed and checked before the behaviors reading it
+    let mut required_defaults = None;
+    if let Some(required) =
+        middleware::required_data(actix, &args.module_key, &args.requires, registrations)
+    {
+        items.push(required.items);
+        required_defaults = Some(required.defaults);
+        if let Some(check) = required.check {
+            route_middleware.before.push(check);
+        }
+    }
+    if args.toggles {
+        let (toggles, check) = middleware::route_toggles(actix, registrations);
+        items.push(toggles);
+        route_middleware.before.push(check);
+    }
+    if args.hit_counts {
+        let (hit_counts, count) = middleware::route_hit_counts(registrations);
+        items.push(hit_counts);
+        route_middleware.before.push(count);
+    }
+    if let Some((thresholds, warning)) =
+        middleware::slow_request_warnings(registrations, args.warn_slower_than_ms)
+    {
+        items.push(thresholds);
+        route_middleware.after.push(warning);
+    }
+    if let Some((breakers, check, record)) = middleware::circuit_breakers(actix, registrations) {
+        items.push(breakers);
+        route_middleware.before.push(check);
+        route_middleware.after.push(record);
+    }
+    if let Some((flags, check)) = middleware::feature_flags(actix, registrations) {
+        items.push(flags);
+        route_middleware.before.push(check);
+    }
+    if let Some((api_keys, check)) = middleware::api_keys(actix, registrations) {
+        items.push(api_keys);
+        route_middleware.before.push(check);
+    }
+    if let Some((sessions, check)) = middleware::required_sessions(actix, registrations) {
+        items.push(sessions);
+        route_middleware.before.push(check);
+    }
+    if let Some((scopes, check)) = middleware::jwt_scopes(actix, registrations) {
+        items.push(scopes);
+        route_middleware.before.push(check);
+    }
+    if let Some((media_types, check)) = middleware::content_types(actix, registrations) {
+        items.push(media_types);
+        route_middleware.before.push(check);
+    }
+    if let Some((headers, insert)) = middleware::response_headers(actix, registrations) {
+        items.push(headers);
+        route_middleware.after.push(insert);
+    }
+    if let Some((etags, capture, tag)) = middleware::etags(actix, registrations) {
+        items.push(etags);
+        route_middleware.before.push(capture);
+        route_middleware.after.push(tag);
+    }
+    if let Some((audited, start, record)) = middleware::audits(actix, registrations) {
+        items.push(audited);
+        route_middleware.before.push(start);
+        route_middleware.after.push(record);
+    }
+    if let Some((idempotent, claim, release)) = middleware::idempotency_keys(actix, registrations) {
+        items.push(idempotent);
+        route_middleware.before.push(claim);
+        route_middleware.after.push(release);
+    }
+    if !items.is_empty() {
+        items.push(middleware::route_handlers(registrations));
+    }
+    // Each resource is wrapped with the middleware and the index of its route
+    let route_indexes = middleware::route_indexes(registrations);
+    let mut middleware_slots = Vec::new();
+    if !route_middleware.is_empty() {
+        items.push(route_middleware.items(actix));
+    }
+
+    // The guards of the A/B variants and negotiated handlers, defined and handed
+    // to the handlers' slots by `register_service`
+    let mut guard_items = Vec::new();
+    if registrations.iter().any(|route| route.produces.is_some()) {
+        guard_items.push(negotiate_fn());
+    }
+
+    // Generate the registration function code
+    let mut registration_functions = Vec::new();
+    let mut errors = Vec::new();
+
+    // A placeholder missing from every prefix is most likely a typo
+    for name in &args.prefix_vars {
+        let placeholder = format!("{{{}}}", name);
+        if !grouped_by_prefix
+            .keys()
+            .any(|prefix| prefix.contains(&placeholder))
+        {
+            let message = format!(
+                "No prefix of `{}` contains the `{}` placeholder listed in `prefix_vars`.",
+                args.module_key, placeholder
+            );
+            errors.push(quote!(::core::compile_error!(#message);));
+        }
+    }
+    for (prefix, mut indexed_routes) in grouped_by_prefix {
+        // A `Files` mount matches every path below it and would shadow the
+        // handlers registered after it, so mounts go last (the sort is stable)
+        indexed_routes.sort_by_key(|(_, route)| route.kind == RouteKind::Static);
+        // Variants of a path are tried in turn, and canaries are guarded and must be
+        // tried before the stable handler of their path
+        let indexed_routes = canaries_first(group_variants(indexed_routes));
+        let (variants, variant_errors) = variant_guards(actix, prefix, &indexed_routes);
+        let (negotiated, produces_errors) = produces_guards(actix, prefix, &indexed_routes);
+        for (route, guard) in variants.into_iter().chain(negotiated) {
+            guard_items.push(guard);
+            // Resources are guarded here, services through the guard next to them
+            if route.kind == RouteKind::Service {
+                let guard_kind = if route.variant.is_some() {
+                    "variant"
+                } else {
+                    "produces"
+                };
+                let guard = guard_fn_ident(&route.handler_name, guard_kind);
+                let slot = guard_slot_ident(&route.handler_name, guard_kind);
+                let module = handler_items_module(route, &generator_module);
+                guard_items.push(quote!(let _ = #module #slot.set(#guard);));
+            }
+        }
+        errors.extend(
+            variant_errors
+                .iter()
+                .chain(&produces_errors)
+                .map(|message| quote!(::core::compile_error!(#message);)),
+        );
+        let routes = indexed_routes
+            .iter()
+            .map(|(_, route)| *route)
+            .collect::<Vec<_>>();
+
+        // Services are wrapped by their route macro, with the middleware handed to
+        // the slot next to them (re-exports can't be wrapped, so they have none)
+        if !route_middleware.is_empty() {
+            for (index, route) in &indexed_routes {
+                if route.kind == RouteKind::Service && !route.reexported {
+                    let (slot, _) = middleware::slot_idents(&route.handler_name);
+                    let module = handler_items_module(route, &generator_module);
+                    let index = match route_indexes[*index] {
+                        Some(index) => quote!(Some(#index)),
+                        None => quote!(None),
+                    };
+                    middleware_slots
+                        .push(quote!(let _ = #module #slot.set((#index, __route_middleware));));
+                }
+            }
+        }
+
+        let fn_calls = indexed_routes.iter().map(|(index, route)| {
+            // Generic handlers are registered through a turbofish (e.g., `search::<PgStore>`)
+            // Handlers with a path, given or of their re-export, are referenced by it
+            // verbatim, and the items defined next to them through its module.
+            // Otherwise, handlers sharing their name with a handler of another module
+            // are qualified with their module, unless declared next to the generator.
+            let qualifier = match &route.handler_path {
+                Some(_) => handler_items_module(route, &generator_module),
+                None => (colliding_names.contains(route.handler_name.as_ref())
+                    && route.module_path.as_ref() != generator_module)
+                    .then(|| {
+                        let module: syn::Path = syn::parse_str(&route.module_path)
+                            .expect("Module paths are built from identifiers");
+                        quote!(#module::)
+                    }),
+            };
+            let handler = || {
+                let turbofish = route.generics.as_ref().map(|generics| {
+                    let generics: syn::AngleBracketedGenericArguments = syn::parse_str(generics)
+                        .expect("Generic arguments are validated by auto_register");
+                    quote!(::#generics)
+                });
+                if let Some(handler_path) = &route.handler_path {
+                    let path: syn::Path = syn::parse_str(handler_path)
+                        .expect("Handler paths are validated by auto_register");
+                    return quote!(#path #turbofish);
+                }
+                let fn_ident = handler_ident(&route.handler_name)
+                    .expect("Handler names are identifiers");
+                quote!(#qualifier #fn_ident #turbofish)
+            };
+            let path = route.path.as_ref();
+            // Resources carry their own upload and body limits
+            let app_data = route
+                .multipart_limit
+                .as_deref()
+                .map(multipart_config)
+                .into_iter()
+                .chain(
+                    route
+                        .body_limit
+                        .as_deref()
+                        .into_iter()
+                        .flat_map(|limit| body_limit_configs(actix, limit)),
+                )
+                .map(|config| quote!(.app_data(#config)));
+            let logger = route
+                .log_format
+                .as_deref()
+                .map(|format| quote!(.wrap(#actix::middleware::Logger::new(#format))));
+            let route_middleware = (!route_middleware.is_empty())
+                .then(|| middleware::RouteMiddleware::wrap_resource(actix, route_indexes[*index]));
+            match route.kind {
+                RouteKind::Service => {
+                    let handler = handler();
+                    quote! {
+                        .service(#handler)
+                    }
+                }
+                RouteKind::Resource => {
+                    let handler = handler();
+                    // Canary guards are defined next to their handler
+                    let guard = if route.canary.is_some() {
+                        let guard = guard_fn_ident(&route.handler_name, "canary");
+                        let module = handler_items_module(route, &generator_module);
+                        Some(quote!(#module #guard))
+                    } else if route.variant.is_some() {
+                        let guard = guard_fn_ident(&route.handler_name, "variant");
+                        Some(quote!(#guard))
+                    } else if route.produces.is_some() {
+                        let guard = guard_fn_ident(&route.handler_name, "produces");
+                        Some(quote!(#guard))
+                    } else {
+                        None
+                    };
+                    let guard = guard.map(|guard| quote!(.guard(#actix::guard::fn_guard(#guard))));
+                    let method_name = http_method(&route.verb).to_lowercase();
+                    let method = syn::Ident::new(&method_name, proc_macro2::Span::call_site());
+                    // Like the resources of the route macros, each one is guarded by
+                    // its method, so requests for another method fall through to the
+                    // other handlers of the path (e.g., `guard::Post()` after a GET)
+                    let method_guard = quote::format_ident!(
+                        "{}{}",
+                        method_name[..1].to_uppercase(),
+                        &method_name[1..]
+                    );
+                    quote! {
+                        .service(#actix::web::resource(#path).guard(#actix::guard::#method_guard()) #guard #(#app_data)* #route_middleware #logger .route(#actix::web::#method().to(#handler)))
+                    }
+                }
+                // Static mounts serve a directory instead of calling a handler
+                RouteKind::Static => {
+                    let dir = route.handler_name.as_ref();
+                    quote! {
+                        .service(::actix_files::Files::new(#path, #dir))
+                    }
+                }
+            }
+        });
+
+        // Split the services into statements of bounded chains: a single chain of
+        // thousands of calls slows type checking down and hits the recursion limit.
+        // Handlers behind a `#[cfg]` get a statement of their own, gated alike.
+        let fn_calls = fn_calls.collect::<Vec<_>>();
+        let mut chunks = Vec::new();
+        let mut chain = Vec::new();
+        for ((_, route), fn_call) in indexed_routes.iter().zip(fn_calls) {
+            let gated = !route.cfg.is_empty();
+            if gated && !chain.is_empty() || chain.len() == SERVICES_PER_STATEMENT {
+                chunks.push(quote!(let scope = scope #(#chain)*;));
+                chain.clear();
+            }
+            if gated {
+                let cfg = cfg_attrs(route);
+                chunks.push(quote!(#cfg let scope = scope #fn_call;));
+            } else {
+                chain.push(fn_call);
+            }
+        }
+        if !chain.is_empty() {
+            chunks.push(quote!(let scope = scope #(#chain)*;));
+        }
+
+        // Services share the scope's upload and body limits, so they must agree on them
+        let multipart_limit = shared_scope_limit(prefix, &routes, "multipart", |route| {
+            route.multipart_limit.as_deref()
+        });
+        let body_limit =
+            shared_scope_limit(prefix, &routes, "body", |route| route.body_limit.as_deref());
+        let mut scope_configs = Vec::new();
+        for limit in [&multipart_limit, &body_limit] {
+            if let Err(message) = limit {
+                errors.push(quote!(::core::compile_error!(#message);));
+            }
+        }
+        if let Ok(Some(limit)) = multipart_limit {
+            scope_configs.push(multipart_config(limit));
+        }
+        if let Ok(Some(limit)) = body_limit {
+            scope_configs.extend(body_limit_configs(actix, limit));
+        }
+        let scope_app_data = scope_configs
+            .iter()
+            .map(|config| quote!(let scope = scope.app_data(#config);));
+
+        let scope_path = scope_path(&args, prefix);
+        // Only answer requests for the configured host (virtual hosting)
+        let host_guard = args
+            .host
+            .as_deref()
+            .map(|host| quote!(let scope = scope.guard(#actix::guard::Host(#host));));
+        // Only let admins in, through the guard given to the key
+        let admin_guard = args
+            .admin
+            .as_ref()
+            .map(|guard| quote!(let scope = scope.guard(#guard);));
+
+        // With a base path read or placeholders resolved at runtime, the scope path
+        // is only known then
+        let scope_path = if args.prefix_vars.is_empty() {
+            quote!(#scope_path)
+        } else {
+            quote!(resolve_prefix(#scope_path))
+        };
+        let scope_path = if args.base_path_env.is_some() {
+            quote!(&format!("{}{}", base_path, #scope_path))
+        } else if args.prefix_vars.is_empty() {
+            scope_path
+        } else {
+            quote!(&#scope_path)
+        };
+
+        // While the maintenance flag is set, answer 503 without reaching the handlers
+        let maintenance = args.maintenance.as_ref().map(|flag| {
+            let retry_after = args.retry_after.to_string();
+            quote! {
+                let scope = scope.wrap(#actix::middleware::from_fn(
+                    |req: #actix::dev::ServiceRequest, next: #actix::middleware::Next<_>| async move {
+                        if #flag.load(::std::sync::atomic::Ordering::Relaxed) {
+                            let response = #actix::HttpResponse::ServiceUnavailable()
+                                .insert_header(("Retry-After", #retry_after))
+                                .finish();
+                            return Ok(req.into_response(response));
+                        }
+                        next.call(req).await.map(|res| res.map_into_boxed_body())
+                    },
+                ));
+            }
+        });
+
+        // Plaintext requests never reach the handlers: reads are redirected to the
+        // same URL over HTTPS, and other requests rejected since their body was
+        // already sent. Wrapped last so it runs before the other middlewares.
+        let require_https = args.require_https.then(|| {
+            quote! {
+                let scope = scope.wrap(#actix::middleware::from_fn(
+                    |req: #actix::dev::ServiceRequest, next: #actix::middleware::Next<_>| async move {
+                        let location = {
+                            let info = req.connection_info();
+                            (info.scheme() != "https").then(|| {
+                                let path = req
+                                    .uri()
+                                    .path_and_query()
+                                    .map_or("/", |path| path.as_str());
+                                format!("https://{}{}", info.host(), path)
+                            })
+                        };
+                        if let Some(location) = location {
+                            let response = if *req.method() == #actix::http::Method::GET
+                                || *req.method() == #actix::http::Method::HEAD
+                            {
+                                #actix::HttpResponse::PermanentRedirect()
+                                    .insert_header((#actix::http::header::LOCATION, location))
+                                    .finish()
+                            } else {
+                                #actix::HttpResponse::Forbidden().finish()
+                            };
+                            return Ok(req.into_response(response));
+                        }
+                        next.call(req).await.map(|res| res.map_into_boxed_body())
+                    },
+                ));
+            }
+        });
+
+        let scope_block = quote! {
+            {
+                let scope = #actix::web::scope(#scope_path);
+                #host_guard
+                #admin_guard
+                #maintenance
+                #require_https
+                #(#scope_app_data)*
+                #(#chunks)*
+                cfg.service(scope);
+            }
+        };
+        // Skip the scope when the predicate turns it off at configure time
+        let scope_block = if args.enabled_if.is_some() {
+            quote! {
+                if enabled_if(#prefix) #scope_block
+            }
+        } else {
+            scope_block
+        };
+
+        registration_functions.push(scope_block);
+    }
+
+    // Describe what the function wires up, shown when hovering it in IDEs
+    let doc = routes_doc(
+        &format!(
+            "Registers {} under `{}`",
+            route_count(registrations.len()),
+            args.module_key
+        ),
+        registrations.iter(),
+    );
+
+    let doc_hidden = doc_hidden_attr(args.doc_hidden);
+
+    // Read the base path once per registration, so the same binary can be
+    // served at `/` or behind a gateway (e.g., `APP_BASE_PATH=/service-x`)
+    let base_path = args.base_path_env.as_deref().map(|name| {
+        quote! {
+            let base_path = match ::std::env::var(#name) {
+                Ok(path) if !path.trim_matches('/').is_empty() => {
+                    format!("/{}", path.trim_matches('/'))
+                }
+                _ => String::new(),
+            };
+        }
+    });
+
+    // Accept both functions and closures as the predicate
+    let enabled_if = args.enabled_if.as_ref().map(|predicate| {
+        quote! {
+            let enabled_if: &dyn Fn(&str) -> bool = &#predicate;
+        }
+    });
+
+    // Replace the placeholders of the prefixes with the values given at registration
+    // time, so the same handlers can be mounted once per value
+    let (vars_param, resolve_prefix) = if args.prefix_vars.is_empty() {
+        (None, None)
+    } else {
+        let names = &args.prefix_vars;
+        let resolve_prefix = quote! {
+            let resolve_prefix = |path: &str| -> String {
+                let mut path = path.to_string();
+                for name in [#(#names),*] {
+                    let value = vars
+                        .iter()
+                        .find(|(var, _)| *var == name)
+                        .map(|(_, value)| *value)
+                        .unwrap_or_else(|| {
+                            panic!("register_service: no value given for the `{{{}}}` prefix placeholder", name)
+                        });
+                    path = path.replace(&format!("{{{}}}", name), value);
+                }
+                path
+            };
+        };
+        (Some(quote!(, vars: &[(&str, &str)])), Some(resolve_prefix))
+    };
+
+    let registrable = registrable_impl(&args);
+    let expanded = quote! {
+        #(#errors)*
+        #(#items)*
+
+        #[doc = #doc]
+        #doc_hidden
+        pub fn register_service(cfg: &mut #actix::web::ServiceConfig #vars_param) {
+            #(#guard_items)*
+            #(#middleware_slots)*
+            #base_path
+            #resolve_prefix
+            #enabled_if
+            #required_defaults
+            #(#registration_functions)*
+        }
+
+        #registrable
+    };
+
+    TokenStream::from(expanded)
+}
+
+// Define `register_service` from the handlers found in the sources, without the
+// routes stored by `auto_register`, so it doesn't depend on the expansion order
+fn scanned_register_service(
+    args: &args::RegisterServiceArgs,
+    path: &str,
+) -> proc_macro2::TokenStream {
+    let actix = &args.actix_crate;
+    let doc_hidden = doc_hidden_attr(args.doc_hidden);
+
+    let routes = match scan::scan(path) {
+        Ok(routes) => routes,
+        Err(message) => {
+            return quote! {
+                ::core::compile_error!(#message);
+                #doc_hidden
+                pub fn register_service(_cfg: &mut #actix::web::ServiceConfig) {}
+            };
+        }
+    };
+
+    let labels = routes
+        .iter()
+        .map(|route| format!("{} {} -> {}", route.verb, route.path, route.handler_name))
+        .collect::<Vec<_>>();
+    if debug_enabled(args.debug) {
+        eprintln!(
+            "[register_actix_routes] generate_register_service: scanned {:?} and found {} route(s) {:?}",
+            path,
+            labels.len(),
+            labels
+        );
+    }
+
+    // Handlers are registered through their path from the crate root, so they
+    // must be visible from the module of the generator
+    let services = routes
+        .iter()
+        .map(|route| {
+            let module: syn::Path = syn::parse_str(&route.module_path)
+                .expect("Module paths are built from identifiers");
+            let handler =
+                handler_ident(&route.handler_name).expect("Handler names are identifiers");
+            quote!(.service(#module::#handler))
+        })
+        .collect::<Vec<_>>();
+    let chunks = services
+        .chunks(SERVICES_PER_STATEMENT)
+        .map(|chunk| quote!(cfg #(#chunk)*;));
+
+    let summary = format!(
+        "Registers {} found in `{}`",
+        route_count(routes.len()),
+        path
+    );
+    let doc = if routes.is_empty() {
+        format!("{}.", summary)
+    } else {
+        let routes = routes
+            .iter()
+            .map(|route| {
+                format!(
+                    "- `{} {}` (`{}`)",
+                    route.verb, route.path, route.handler_name
+                )
+            })
+            .collect::<Vec<_>>();
+        format!("{}:\n\n{}", summary, routes.join("\n"))
+    };
+
+    let registrable = registrable_impl(args);
+    quote! {
+        #[doc = #doc]
+        #doc_hidden
+        pub fn register_service(cfg: &mut #actix::web::ServiceConfig) {
+            #(#chunks)*
+        }
+
+        #registrable
+    }
+}
+
+// Define the struct named by the `registrable` option, implementing the runtime
+// crate's `Registrable` through the generated `register_service`
+fn registrable_impl(args: &args::RegisterServiceArgs) -> Option<proc_macro2::TokenStream> {
+    let name = args.registrable.as_ref()?;
+    let actix = &args.actix_crate;
+    let key = &args.module_key;
+    let doc = format!("Registers the routes of `{}` as a `Registrable`", key);
+    Some(quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone, Copy, Default)]
+        pub struct #name;
+
+        impl ::register_actix_routes_runtime::Registrable<#actix::web::ServiceConfig> for #name {
+            fn key(&self) -> &'static str {
+                #key
+            }
+
+            fn register(&self, cfg: &mut #actix::web::ServiceConfig) {
+                register_service(cfg)
+            }
+        }
+    })
+}
+
+// Implement `RouterModule` for a marker struct from the routes of a key, given by
+// `#[router_module(...)]` with the options of `generate_register_service!`, or of
+// the struct's module by default. The `register_service` of the key is generated
+// in an anonymous constant, so the module can still declare its own.
+#[proc_macro_derive(RouterModule, attributes(router_module))]
+pub fn derive_router_module(input: TokenStream) -> TokenStream {
+    let input = match syn::parse::<syn::DeriveInput>(input) {
+        Ok(input) => input,
+        Err(error) => return TokenStream::from(error.to_compile_error()),
+    };
+    let mut errors = Vec::new();
+    if !input.generics.params.is_empty() {
+        errors.push(syn::Error::new_spanned(
+            &input.generics,
+            "RouterModule can only be derived for a marker struct without generics (e.g., `pub struct EventsModule;`).",
+        ));
+    }
+
+    // The generator options, none to register the handlers of the struct's module
+    let options = input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("router_module"))
+        .map(|attr| match &attr.meta {
+            syn::Meta::List(list) => list.tokens.clone(),
+            _ => proc_macro2::TokenStream::new(),
+        })
+        .next()
+        .unwrap_or_default();
+    let args = match syn::parse2::<args::RegisterServiceArgs>(options.clone()) {
+        Ok(args) => Some(args),
+        Err(error) => {
+            errors.push(error);
+            None
+        }
+    };
+    // These options change the signature of `register_service` or define items
+    // the rest of the crate would need to reach
+    if let Some(args) = &args {
+        for (option, enabled) in [
+            ("prefix_vars", !args.prefix_vars.is_empty()),
+            ("toggles", args.toggles),
+            ("hit_counts", args.hit_counts),
+            ("registrable", args.registrable.is_some()),
+        ] {
+            if enabled {
+                errors.push(syn::Error::new_spanned(
+                    &options,
+                    format!(
+                        "`{}` isn't available on a RouterModule, use generate_register_service! instead.",
+                        option
+                    ),
+                ));
+            }
+        }
+    }
+    if let Some(error) = combine_errors(errors) {
+        return TokenStream::from(error.to_compile_error());
+    }
+    let args = args.expect("Arguments are parsed when no error was reported");
+    let actix = &args.actix_crate;
+
+    // Generate `register_service` first, recording the key like the generator does
+    let register_service =
+        proc_macro2::TokenStream::from(generate_register_service(TokenStream::from(options)));
+
+    // The routes of the key, with their verb alone (e.g., "GET", or "STATIC" for mounts)
+    let map = read_registry();
+    let entries = map
+        .get(&args.module_key)
+        .into_iter()
+        .flatten()
+        .map(route_entry);
+
+    let name = &input.ident;
+    let expanded = quote! {
+        const _: () = {
+            #register_service
+
+            impl ::register_actix_routes_runtime::RouterModule<#actix::web::ServiceConfig> for #name {
+                fn configure(&self, cfg: &mut #actix::web::ServiceConfig) {
+                    register_service(cfg)
+                }
+
+                fn routes(&self) -> &'static [::register_actix_routes_runtime::RouteEntry] {
+                    const ROUTES: &[::register_actix_routes_runtime::RouteEntry] = &[#(#entries),*];
+                    ROUTES
+                }
+            }
+        };
+    };
+
+    TokenStream::from(expanded)
+}
+
+// Whether an annotation of the route (`admin`, `api_key`, `session` or
+// `jwt_scopes`) rejects the requests of unauthenticated clients. `roles` are
+// only documented, nothing rejects the requests of other roles.
+fn requires_auth(route: &RouteInfo) -> bool {
+    route.admin || route.api_key.is_some() || route.session_required || !route.jwt_scopes.is_empty()
+}
+
+// Whether the route changes state, so that leaving it unprotected deserves a look
+fn is_mutation(route: &RouteInfo) -> bool {
+    matches!(route.verb.as_ref(), "POST" | "PUT" | "PATCH" | "DELETE")
+}
+
+// The `Auth` cell of `list_routes`, flagging the unprotected mutations and the
+// routes whose roles could be mistaken for a protection
+fn auth_label(route: &RouteInfo) -> &'static str {
+    match (
+        requires_auth(route),
+        is_mutation(route),
+        route.roles.is_empty(),
+    ) {
+        (true, _, _) => "yes",
+        (false, true, true) => "no ⚠",
+        (false, true, false) => "no ⚠ (roles not enforced)",
+        (false, false, true) => "no",
+        (false, false, false) => "no (roles not enforced)",
+    }
+}
+
+// Badges of a route for the audit view of `list_routes` (e.g., "🔒 📡")
+fn status_badges(route: &RouteInfo) -> String {
+    [
+        (route.deprecated, "⛔"),
+        (requires_auth(route) || !route.roles.is_empty(), "🔒"),
+        (route.doc_hidden, "🙈"),
+        (route.sse || route.verb.as_ref() == "WS", "📡"),
+    ]
+    .into_iter()
+    .filter(|(shown, _)| *shown)
+    .map(|(_, badge)| badge)
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+// The legend printed under the table when `list_routes` shows badges
+const BADGES_LEGEND: &str = "⛔ deprecated  🔒 auth required  🙈 hidden from docs  📡 streaming";
+
+// Columns of a terminal a cell takes, with emoji taking two
+fn display_width(cell: &str) -> usize {
+    cell.chars()
+        .map(|c| match c as u32 {
+            0x2600..=0x27BF | 0x1F300..=0x1FAFF => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+// Generate the statements fitting the `table` of `list_routes_string` to the
+// width of the terminal, read at run time from `COLUMNS` (or `max_width`).
+// `tabled` shrinks the widest columns first, usually the paths; the plain table
+// has its lines cut or broken instead.
+fn fit_table_width(args: &args::ListRoutesArgs, plain: bool) -> proc_macro2::TokenStream {
+    let Some(overflow) = args.overflow else {
+        return quote!();
+    };
+    let fallback = match args.max_width {
+        Some(width) => quote!(Some(#width)),
+        None => quote!(None),
+    };
+    let fit = match (overflow, plain) {
+        (args::TableOverflow::Truncate, false) => quote! {
+            table.with(
+                tabled::settings::Width::truncate(width)
+                    .suffix("…")
+                    .priority(tabled::settings::peaker::PriorityMax::right()),
+            );
+        },
+        (args::TableOverflow::Wrap, false) => quote! {
+            table.with(
+                tabled::settings::Width::wrap(width)
+                    .priority(tabled::settings::peaker::PriorityMax::right()),
+            );
+        },
+        (args::TableOverflow::Truncate, true) => quote! {
+            table = table
+                .lines()
+                .map(|line| {
+                    if line.chars().count() <= width {
+                        return line.to_string();
+                    }
+                    let mut line = line.chars().take(width - 1).collect::<String>();
+                    line.push('…');
+                    line
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+        },
+        (args::TableOverflow::Wrap, true) => quote! {
+            table = table
+                .lines()
+                .flat_map(|line| {
+                    let chars = line.chars().collect::<Vec<_>>();
+                    chars
+                        .chunks(width)
+                        .map(|chunk| chunk.iter().collect::<String>())
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+        },
+    };
+
+    quote! {
+        let width = std::env::var("COLUMNS")
+            .ok()
+            .and_then(|columns| columns.trim().parse::<usize>().ok())
+            .or(#fallback);
+        if let Some(width) = width.filter(|width| *width > 1) {
+            #fit
+        }
+    }
+}
+
+// Format the rows of `list_routes` as fixed-width columns under their headers,
+// separated by two spaces. Rows of a single cell are the spanning headers of
+// the grouped layout, printed as they are.
+fn plain_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths = vec![0; headers.len()];
+    let headers = headers.iter().map(|header| header.to_string()).collect();
+    for row in std::iter::once(&headers).chain(rows.iter().filter(|row| row.len() > 1)) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(display_width(cell));
+        }
+    }
+    let separators = widths.iter().map(|width| "-".repeat(*width)).collect();
+
+    let line = |cells: &Vec<String>| {
+        if let [header] = cells.as_slice() {
+            return header.clone();
+        }
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - display_width(cell))))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    let mut lines = vec![line(&headers), line(&separators)];
+    lines.extend(rows.iter().map(line));
+    lines.join("\n")
+}
+
+#[proc_macro]
+pub fn generate_list_routes(input: TokenStream) -> TokenStream {
+    // Parse the options, still defining the functions on error
+    let args = match syn::parse::<args::ListRoutesArgs>(input) {
+        Ok(args) => args,
+        Err(error) => {
+            let error = error.to_compile_error();
+            return TokenStream::from(quote! {
+                #error
+                pub fn list_routes() {}
+                pub fn list_routes_string() -> String {
+                    String::new()
+                }
+                pub fn routes_summary() -> String {
+                    String::new()
+                }
+            });
+        }
+    };
+    let doc_hidden = doc_hidden_attr(args.doc_hidden);
+
+    // With `minimal-codegen`, keep callers compiling without generating the table
+    if cfg!(feature = "minimal-codegen") {
+        let startup = args.startup.map(|_| {
+            quote! {
+                #doc_hidden
+                pub fn on_startup() {}
+            }
+        });
+        return TokenStream::from(quote! {
+            #doc_hidden
+            pub fn list_routes() {}
+            #doc_hidden
+            pub fn list_routes_string() -> String {
+                String::new()
+            }
+            #doc_hidden
+            pub fn routes_summary() -> String {
+                String::new()
+            }
+            #startup
+        });
+    }
+
+    // Safely read all handler registrations from the REGISTRATION_MAP
+    let map = read_registry();
+
+    // Summarize the routes in a line, for structured startup logs
+    let summary = routes_summary(&map);
+    let summary = quote! {
+        /// A one-line summary of the registered routes, counting them per verb
+        #doc_hidden
+        pub fn routes_summary() -> String {
+            #summary.to_string()
+        }
+    };
+
+    // Print the table, or the summary line, the first time the server calls
+    // `on_startup` (e.g., from the `HttpServer::new` closure, run by each worker)
+    let startup = args.startup.map(|startup| {
+        let banner = match startup {
+            args::StartupBanner::Table => quote!(list_routes_string()),
+            args::StartupBanner::Summary => quote!(routes_summary()),
+        };
+        quote! {
+            /// Prints the registered routes once, however many workers call it
+            #doc_hidden
+            pub fn on_startup() {
+                static BANNER: ::std::sync::Once = ::std::sync::Once::new();
+                BANNER.call_once(|| println!("{}", #banner));
+            }
+        }
+    });
+
+    // Collect all routes into their cells: scope, path, the full path clients call,
+    // handler, verb, whether it requires authentication, the badges of the audit
+    // view, errors and source. The grouped layout shows each scope once, in a row
+    // of its own spanning the table, above its routes.
+    let mut headers = if args.grouped {
+        vec!["Path", "Full path", "Handler", "Verb", "Auth"]
+    } else {
+        vec!["Scope", "Path", "Full path", "Handler", "Verb", "Auth"]
+    };
+    if args.badges {
+        headers.push("Status");
+    }
+    headers.extend(["Errors", "Source"]);
+    let errors = unserved_scope_errors("generate_list_routes!()", map.keys().map(String::as_str));
+    let duplicates = &duplicate_routes(&map);
+    let cells = map
+        .iter()
+        .flat_map(|(scope, routes)| {
+            let header = args.grouped.then(|| {
+                let scope = if scope.is_empty() { "/" } else { scope };
+                vec![scope.to_string()]
+            });
+            header.into_iter().chain(routes.iter().map(move |route| {
+                let mut cells = if args.grouped {
+                    Vec::new()
+                } else {
+                    vec![scope.clone()]
+                };
+                // Flag the routes another route shadows or is shadowed by
+                let mut verb = verb_label(route);
+                if duplicates.contains(&(route.file.clone(), route.line)) {
+                    verb.push_str(" ⚠ duplicate");
+                }
+                cells.extend([
+                    route.path.to_string(),
+                    full_path(scope, route),
+                    route.handler_name.to_string(),
+                    verb,
+                    auth_label(route).to_string(),
+                ]);
+                if args.badges {
+                    cells.push(status_badges(route));
+                }
+                cells.extend([
+                    error_codes_to_string(&route.errors),
+                    format!("{}:{}", route.file, route.line),
+                ]);
+                cells
+            }))
+        })
+        .collect::<Vec<_>>();
+    let mut legend = if args.badges {
+        format!("\n{}", BADGES_LEGEND)
+    } else {
+        String::new()
+    };
+    let unprotected = map
+        .values()
+        .flatten()
+        .filter(|route| {
+            route.kind != RouteKind::Static && is_mutation(route) && !requires_auth(route)
+        })
+        .count();
+    if unprotected > 0 {
+        legend.push_str(&format!(
+            "\n⚠ {} {} state without requiring authentication",
+            route_count(unprotected),
+            if unprotected == 1 {
+                "changes"
+            } else {
+                "change"
+            }
+        ));
+    }
+    if !duplicates.is_empty() {
+        legend.push_str(&format!(
+            "\n⚠ {} share their verb and path with another route, Actix only serves the first registered",
+            route_count(duplicates.len())
+        ));
+    }
+
+    // Describe what the function prints, shown when hovering it in IDEs
+    let doc = routes_doc(
+        &format!(
+            "Prints the {} registered automatically",
+            route_count(map.values().map(Vec::len).sum())
+        ),
+        map.values().flatten(),
+    );
+
+    // Fit the table to the terminal, when it is too narrow
+    let fit = fit_table_width(&args, cfg!(feature = "plain-table"));
+
+    // With `plain-table`, the table is formatted at compile time, without `tabled`
+    if cfg!(feature = "plain-table") {
+        let table = plain_table(&headers, &cells);
+        let list = if args.overflow.is_some() {
+            quote! {
+                let mut table = #table.to_string();
+                #fit
+                format!("List of the automatically registered routes:\n{}{}", table, #legend)
+            }
+        } else {
+            let table = format!(
+                "List of the automatically registered routes:\n{}{}",
+                table, legend
+            );
+            quote!(#table.to_string())
+        };
+        return TokenStream::from(quote! {
+            #(#errors)*
+
+            #[doc = #doc]
+            #doc_hidden
+            pub fn list_routes() {
+                println!("{}", list_routes_string());
+            }
+
+            /// The table `list_routes` prints, preceded by its heading line
+            #doc_hidden
+            pub fn list_routes_string() -> String {
+                #list
+            }
+
+            #summary
+            #startup
+        });
+    }
+
+    // The scope rows of the grouped layout span the table, so it is built row
+    // by row rather than derived
+    if args.grouped {
+        let columns = headers.len();
+        let spanning_rows = cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cells)| cells.len() == 1)
+            .map(|(index, _)| index + 1);
+        let records = cells.iter().map(|cells| {
+            let padding = vec![""; columns - cells.len()];
+            quote!([#(#cells,)* #(#padding),*])
+        });
+        return TokenStream::from(quote! {
+            #(#errors)*
+
+            #[doc = #doc]
+            #doc_hidden
+            pub fn list_routes() {
+                println!("{}", list_routes_string());
+            }
+
+            /// The table `list_routes` prints, preceded by its heading line
+            #doc_hidden
+            pub fn list_routes_string() -> String {
+                let mut builder = tabled::builder::Builder::default();
+                builder.push_record([#(#headers),*]);
+                #(builder.push_record(#records);)*
+
+                let mut table = builder.build();
+                table.with(tabled::settings::Style::modern());
+                for row in [#(#spanning_rows),*] {
+                    table.modify((row, 0), tabled::settings::Span::column(#columns));
+                }
+                #fit
+
+                format!("List of the automatically registered routes:\n{}{}", table, #legend)
+            }
+
+            #summary
+            #startup
+        });
+    }
+
+    // One field per column, named after its header (e.g., `full_path`)
+    let fields = headers
+        .iter()
+        .map(|header| {
+            syn::Ident::new(
+                &header.to_lowercase().replace(' ', "_"),
+                proc_macro2::Span::call_site(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let rows = cells.iter().map(|cells| {
+        quote! {
+            Route {
+                #(#fields: #cells.to_string()),*
+            }
+        }
+    });
+
+    // Generate code for the `list_routes` function, printing the table that
+    // `list_routes_string` returns for logging frameworks
+    let expanded = quote! {
+        #(#errors)*
+
+        #[doc = #doc]
+        #doc_hidden
+        pub fn list_routes() {
+            println!("{}", list_routes_string());
+        }
+
+        /// The table `list_routes` prints, preceded by its heading line
+        #doc_hidden
+        pub fn list_routes_string() -> String {
+            use tabled::{Table, Tabled};
+
+            #[derive(Tabled)]
+            struct Route {
+                #(#[tabled(rename = #headers)] #fields: String,)*
+            }
+
+            let routes = vec![
+                #(#rows),*
+            ];
+
+            let mut table = Table::new(routes);
+            table.with(tabled::settings::Style::modern());
+            #fit
+
+            format!("List of the automatically registered routes:\n{}{}", table, #legend)
+        }
+
+        #summary
+        #startup
+    };
+
+    TokenStream::from(expanded)
+}
+
+#[proc_macro]
+pub fn generate_csrf_exemptions(_input: TokenStream) -> TokenStream {
+    // Safely read all handler registrations from the REGISTRATION_MAP
+    let map = read_registry();
+
+    // Routes are matched on the exact pattern they are served under, so every
+    // route is listed to tell the protected ones sharing an exempt pattern
+    let routes = map
+        .iter()
+        .flat_map(|(scope, routes)| routes.iter().map(move |route| (scope, route)))
+        .filter(|(_, route)| route.kind != RouteKind::Static)
+        .map(|(scope, route)| {
+            (
+                http_method(&route.verb),
+                served_pattern(scope, route),
+                route.csrf_exempt,
+            )
+        })
+        .collect::<Vec<_>>();
+    let errors = unserved_scope_errors(
+        "generate_csrf_exemptions!()",
+        map.iter()
+            .filter(|(_, routes)| routes.iter().any(|route| route.kind != RouteKind::Static))
+            .map(|(scope, _)| scope.as_str()),
+    );
+    let exemptions = routes
+        .iter()
+        .filter(|(_, _, exempt)| *exempt)
+        .map(|(verb, pattern, _)| quote!((#verb, #pattern)));
+    let served = routes
+        .iter()
+        .map(|(verb, pattern, exempt)| quote!((#verb, #pattern, #exempt)));
+
+    // Generate the exempted routes and their lookup
+    let expanded = quote! {
+        #(#errors)*
+
+        /// The `(method, path pattern)` of the routes registered with `csrf_exempt = true`,
+        /// relative to where `register_service` is configured
+        pub static CSRF_EXEMPT_ROUTES: &[(&str, &str)] = &[#(#exemptions),*];
+
+        /// Whether a CSRF middleware should let a request through, from its
+        /// method and `match_pattern()`, relative to where `register_service` is
+        /// configured. A pattern is only exempted when every route served under
+        /// it is exempt.
+        pub fn is_csrf_exempt(method: &str, pattern: &str) -> bool {
+            const ROUTES: &[(&str, &str, bool)] = &[#(#served),*];
+            let mut matches = ROUTES
+                .iter()
+                .filter(|(route_method, route, _)| {
+                    route_method.eq_ignore_ascii_case(method) && *route == pattern
+                })
+                .peekable();
+            matches.peek().is_some() && matches.all(|(_, _, exempt)| *exempt)
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+#[proc_macro]
+pub fn generate_log_exclusions(input: TokenStream) -> TokenStream {
+    let args = match syn::parse::<args::CrateArgs>(input) {
+        Ok(args) => args,
+        Err(error) => return TokenStream::from(error.to_compile_error()),
+    };
+    let actix = &args.actix_crate;
+
+    // Safely read all handler registrations from the REGISTRATION_MAP
+    let map = read_registry();
+
+    // Routes silenced with `log = false`, and the ones logged by their own
+    // `Logger`, matched on the pattern they are served under, which is only known
+    // once their generator expanded
+    let excluded = map
+        .iter()
+        .flat_map(|(scope, routes)| routes.iter().map(move |route| (scope, route)))
+        .filter(|(_, route)| !route.log || route.log_format.is_some())
+        .collect::<Vec<_>>();
+    let errors = unserved_scope_errors(
+        "generate_log_exclusions!()",
+        excluded.iter().map(|(scope, _)| scope.as_str()),
+    );
+    let patterns = excluded
+        .iter()
+        .map(|(scope, route)| pattern_regex(&served_pattern(scope, route)))
+        .collect::<BTreeSet<_>>()
+        .into_iter();
+
+    // Generate the excluded patterns and the function applying them
+    let expanded = quote! {
+        #(#errors)*
+
+        /// Regular expressions matching the paths of the routes the app's `Logger`
+        /// should skip: the ones with `log = false` or their own `log_format`,
+        /// relative to where `register_service` is configured
+        pub static LOG_EXCLUDED_PATHS: &[&str] = &[#(#patterns),*];
+
+        /// Exclude these routes from the app's `Logger`, for services configured
+        /// under `mount` (e.g., `""` at the root of the app, or `"/api"`)
+        pub fn exclude_route_logging(
+            logger: #actix::middleware::Logger,
+            mount: &str,
+        ) -> #actix::middleware::Logger {
+            let mut escaped = String::new();
+            for c in mount.trim_end_matches('/').chars() {
+                if "\\.+*?()|[]{}^$".contains(c) {
+                    escaped.push('\\');
+                }
+                escaped.push(c);
+            }
+            LOG_EXCLUDED_PATHS.iter().fold(logger, |logger, pattern| {
+                logger.exclude_regex(format!("^{}{}", escaped, &pattern[1..]))
+            })
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+// Name every handler after its key and its name (e.g., "/events" and `search` ->
+// `EVENTS_SEARCH`), with the pattern the route is served under. Handlers of the same
+// name under a key are told apart by their module, and the remaining collisions
+// are returned as compile errors, with the keys `generator` reads before they are served.
+fn route_path_constants<'a>(
+    map: &'a Registry,
+    generator: &str,
+) -> (
+    BTreeMap<String, (String, &'a RouteInfo)>,
+    Vec<proc_macro2::TokenStream>,
+) {
+    let mut constants: BTreeMap<String, (String, &RouteInfo)> = BTreeMap::new();
+    let mut errors = unserved_scope_errors(
+        generator,
+        map.iter()
+            .filter(|(_, routes)| routes.iter().any(|route| route.kind != RouteKind::Static))
+            .map(|(scope, _)| scope.as_str()),
+    );
+    for (scope, routes) in map.iter() {
+        let routes = routes
+            .iter()
+            .filter(|route| route.kind != RouteKind::Static)
+            .collect::<Vec<_>>();
+        for route in &routes {
+            let shared_name = routes.iter().any(|other| {
+                other.handler_name == route.handler_name && other.module_path != route.module_path
+            });
+            let name = if shared_name {
+                let module = route.module_path.rsplit("::").next().unwrap_or_default();
+                route_path_const_name(scope, &format!("{}_{}", module, route.handler_name))
+            } else {
+                route_path_const_name(scope, &route.handler_name)
+            };
+            let path = served_pattern(scope, route);
+            if let Some((_, other)) = constants.get(&name) {
+                let message = format!(
+                    "`{}` ({}:{}) and `{}` ({}:{}) both define the route path constant `{}`, rename one of the handlers.",
+                    other.handler_name, other.file, other.line, route.handler_name, route.file, route.line, name
+                );
+                errors.push(quote!(::core::compile_error!(#message);));
+                continue;
+            }
+            constants.insert(name, (path, route));
+        }
+    }
+    (constants, errors)
+}
+
+#[proc_macro]
+pub fn generate_route_paths(_input: TokenStream) -> TokenStream {
+    // Safely read all handler registrations from the REGISTRATION_MAP
+    let map = read_registry();
+
+    // One constant per handler, holding the pattern its route is served under
+    let (constants, errors) = route_path_constants(&map, "generate_route_paths!()");
+
+    // The routes as an enum, named after their constant (e.g., `EventsSearch`)
+    let variants = constants
+        .iter()
+        .map(|(name, (path, route))| {
+            let variant = name
+                .split('_')
+                .filter(|word| !word.is_empty())
+                .map(|word| word[..1].to_string() + &word[1..].to_lowercase())
+                .collect::<String>();
+            let variant = if variant.starts_with(|c: char| c.is_ascii_digit()) {
+                format!("Route{}", variant)
+            } else {
+                variant
+            };
+            let variant = syn::Ident::new(&variant, proc_macro2::Span::call_site());
+            let constant = syn::Ident::new(name, proc_macro2::Span::call_site());
+            let method = http_method(&route.verb);
+            let doc = format!("`{} {}`", method, path);
+            (variant, constant, method, doc)
+        })
+        .collect::<Vec<_>>();
+    let variant_names = variants
+        .iter()
+        .map(|(variant, ..)| variant)
+        .collect::<Vec<_>>();
+    let variant_constants = variants.iter().map(|(_, constant, ..)| constant);
+    let variant_methods = variants.iter().map(|(_, _, method, _)| method);
+    let variant_docs = variants.iter().map(|(.., doc)| doc);
+    let routes_enum = quote! {
+        /// A route registered automatically, displayed and parsed as its method and
+        /// path pattern (e.g., "GET /events/search")
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Route {
+            #(#[doc = #variant_docs] #variant_names,)*
+        }
+
+        impl Route {
+            /// Every route, in the order of their constants
+            pub const ALL: &'static [Route] = &[#(Route::#variant_names),*];
+
+            /// The HTTP method of the route (e.g., "GET")
+            pub fn method(self) -> &'static str {
+                match self {
+                    #(Route::#variant_names => #variant_methods,)*
+                }
+            }
+
+            /// The path pattern of the route (e.g., "/events/{id}")
+            pub fn path(self) -> &'static str {
+                match self {
+                    #(Route::#variant_names => #variant_constants,)*
+                }
+            }
+        }
+
+        impl ::std::fmt::Display for Route {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                write!(f, "{} {}", self.method(), self.path())
+            }
+        }
+
+        /// The error of parsing a method and path which isn't a registered route
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct UnknownRoute(pub String);
+
+        impl ::std::fmt::Display for UnknownRoute {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                write!(f, "no route is registered for `{}`", self.0)
+            }
+        }
+
+        impl ::std::error::Error for UnknownRoute {}
+
+        /// Finds the route of a method, in any case, and a path pattern. Routes sharing
+        /// both (e.g., canaries) resolve to the first one.
+        impl<'a> ::std::convert::TryFrom<(&'a str, &'a str)> for Route {
+            type Error = UnknownRoute;
+
+            fn try_from((method, path): (&'a str, &'a str)) -> Result<Self, Self::Error> {
+                Route::ALL
+                    .iter()
+                    .copied()
+                    .find(|route| route.method().eq_ignore_ascii_case(method) && route.path() == path)
+                    .ok_or_else(|| UnknownRoute(format!("{} {}", method, path)))
+            }
+        }
+
+        /// Parses a route as displayed, its method and path separated by a space
+        impl ::std::str::FromStr for Route {
+            type Err = UnknownRoute;
+
+            fn from_str(route: &str) -> Result<Self, Self::Err> {
+                let route = route.trim();
+                let (method, path) = route
+                    .split_once(' ')
+                    .ok_or_else(|| UnknownRoute(route.to_string()))?;
+                Route::try_from((method, path.trim()))
+            }
+        }
+    };
+
+    let constants = constants.iter().map(|(name, (path, route))| {
+        let doc = format!(
+            "`{} {}`, handled by `{}`",
+            http_method(&route.verb),
+            path,
+            route.handler_name
+        );
+        // Paths with dynamic segments also get a function filling them in order,
+        // percent-encoded, unless a segment's name can't be an argument
+        let (template, segments) = path_segments(path);
+        let params = segments
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        let url_fn = (!params.is_empty()
+            && params.iter().all(|param| {
+                param.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                    && param.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                    && handler_ident(param).is_some()
+            })
+            && params.iter().collect::<BTreeSet<_>>().len() == params.len())
+        .then(|| {
+            let url_fn = quote::format_ident!("{}_url", name.to_lowercase());
+            let params = params
+                .iter()
+                .filter_map(|param| handler_ident(param))
+                .collect::<Vec<_>>();
+            // Plain segments stop at the next slash, while the others may span
+            // several (e.g., `{tail}*`), so their slashes are kept
+            let values = segments.iter().zip(&params).map(|((_, regex), param)| {
+                let unreserved = if regex == "[^/]+" {
+                    quote!(b"-._~")
+                } else {
+                    quote!(b"-._~/")
+                };
+                quote!(self::encode(#param.to_string().as_bytes(), #unreserved))
+            });
+            let doc = format!(
+                "The URL of `{}`, with its dynamic segments filled in and percent-encoded",
+                path
+            );
+            quote! {
+                #[doc = #doc]
+                pub fn #url_fn(#(#params: impl ::std::fmt::Display),*) -> String {
+                    format!(#template, #(#values),*)
+                }
+            }
+        });
+        let name = syn::Ident::new(name, proc_macro2::Span::call_site());
+        let has_url_fn = url_fn.is_some();
+        let constant = quote! {
+            #[doc = #doc]
+            pub const #name: &str = #path;
+
+            #url_fn
+        };
+        (constant, has_url_fn)
+    });
+    let (constants, url_fns): (Vec<_>, Vec<_>) = constants.unzip();
+    let encode = url_fns.contains(&true).then(percent_encode_fn);
+
+    // Generate the module of the constants
+    let expanded = quote! {
+        #(#errors)*
+
+        /// The paths of the routes registered automatically, as served relative to
+        /// where `register_service` is configured, the functions building the URLs of
+        /// the dynamic ones, and the `Route` enum naming them
+        pub mod route_paths {
+            #(#constants)*
+
+            #encode
+
+            #routes_enum
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+#[proc_macro]
+pub fn generate_route_entries(_input: TokenStream) -> TokenStream {
+    // Safely read all handler registrations from the REGISTRATION_MAP
+    let map = read_registry();
+
+    // The routes as the runtime crate's `RouteEntry`, with their verb alone
+    // (e.g., "GET", or "STATIC" for mounts)
+    let entries = map.values().flatten().map(route_entry);
+    // The patterns the routes are served under, in the order of `ROUTES`
+    let served = map
+        .iter()
+        .flat_map(|(scope, routes)| routes.iter().map(move |route| served_pattern(scope, route)));
+    let count = map.values().flatten().count();
+    let errors = unserved_scope_errors("generate_route_entries!()", map.keys().map(String::as_str));
+
+    // Generate the registry, built at compile time, and the helpers tests check
+    // the wiring with
+    let expanded = quote! {
+        #(#errors)*
+
+        /// The routes registered automatically, sorted by module key
+        pub static ROUTES: &[::register_actix_routes_runtime::RouteEntry] = &[#(#entries),*];
+
+        /// The routes of `ROUTES` grouped by module key
+        pub fn routes_by_scope(
+        ) -> ::std::collections::BTreeMap<
+            &'static str,
+            Vec<&'static ::register_actix_routes_runtime::RouteEntry>,
+        > {
+            let mut scopes = ::std::collections::BTreeMap::<_, Vec<_>>::new();
+            for route in ROUTES {
+                scopes.entry(route.scope.as_ref()).or_default().push(route);
+            }
+            scopes
+        }
+
+        static __SERVED_PATHS: [&str; #count] = [#(#served),*];
+
+        /// Panics unless a route answers the method, in any case, and the path
+        /// pattern it is served under, relative to where `register_service` is
+        /// configured (e.g., "/events/search" with `use_scope = true`)
+        #[track_caller]
+        pub fn assert_route_exists(method: &str, path: &str) {
+            let exists = ROUTES.iter().zip(__SERVED_PATHS).any(|(route, served)| {
+                // WebSocket upgrades are GET requests
+                let verb = if route.verb == "WS" { "GET" } else { route.verb.as_ref() };
+                verb.eq_ignore_ascii_case(method) && served == path
+            });
+            if !exists {
+                let routes = ROUTES
+                    .iter()
+                    .zip(__SERVED_PATHS)
+                    .map(|(route, served)| format!("{} {}", route.verb, served))
+                    .collect::<Vec<_>>();
+                panic!(
+                    "No route is registered for `{} {}`, the registered routes are:\n{}",
+                    method,
+                    path,
+                    routes.join("\n")
+                );
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+// The app `generate_fuzz_targets!` and `generate_path_param_tests!` call a handler
+// through: the handler alone under the scope `register_service` serves it in (e.g.,
+// with its tenant segment, or none without `use_scope`), after the app data of `configure`
+fn test_app(route: &RouteInfo, args: &args::TestAppArgs) -> proc_macro2::TokenStream {
+    let actix = &args.actix_crate;
+    // Handlers are referenced through their path from the crate root, so they
+    // must be visible from the module of this macro, and their generic
+    // arguments are resolved next to it
+    let turbofish = route.generics.as_ref().map(|generics| {
+        let generics: syn::AngleBracketedGenericArguments =
+            syn::parse_str(generics).expect("Generic arguments are validated by auto_register");
+        quote!(::#generics)
+    });
+    let handler: syn::Path = match &route.handler_path {
+        Some(handler_path) => {
+            syn::parse_str(handler_path).expect("Handler paths are validated by auto_register")
+        }
+        None => {
+            let module: syn::Path = syn::parse_str(&route.module_path)
+                .expect("Module paths are built from identifiers");
+            let handler =
+                handler_ident(&route.handler_name).expect("Handler names are identifiers");
+            syn::parse_quote!(#module::#handler)
+        }
+    };
+    let route_path = route.path.as_ref();
+    let method = syn::Ident::new(
+        &http_method(&route.verb).to_lowercase(),
+        proc_macro2::Span::call_site(),
+    );
+    let service = match route.kind {
+        RouteKind::Resource => quote! {
+            #actix::web::resource(#route_path)
+                .route(#actix::web::#method().to(#handler #turbofish))
+        },
+        _ => quote!(#handler #turbofish),
+    };
+    let scope = served_scope(&route.prefix);
+    let configure = args
+        .configure
+        .as_ref()
+        .map(|configure| quote!(.configure(#configure)));
+    quote! {
+        #actix::App::new()
+            #configure
+            .service(#actix::web::scope(#scope).service(#service))
+    }
+}
+
+// How the test app is started and called: actix-rt 1 (actix-web 3) names its
+// systems and its services take `&mut self`
+fn test_runtime(actix: &syn::Path) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    if cfg!(feature = "actix3") {
+        (quote!(#actix::rt::System::new("test")), quote!(&mut app))
+    } else {
+        (quote!(#actix::rt::System::new()), quote!(&app))
+    }
+}
+
+// Percent-encode every byte but the ASCII alphanumerics and `unreserved`, defined
+// in the modules of the generated tests
+fn percent_encode_fn() -> proc_macro2::TokenStream {
+    quote! {
+        fn encode(bytes: &[u8], unreserved: &[u8]) -> String {
+            bytes
+                .iter()
+                .map(|byte| {
+                    if byte.is_ascii_alphanumeric() || unreserved.contains(byte) {
+                        char::from(*byte).to_string()
+                    } else {
+                        format!("%{:02X}", byte)
+                    }
+                })
+                .collect()
+        }
+    }
+}
+
+#[proc_macro]
+pub fn generate_fuzz_targets(input: TokenStream) -> TokenStream {
+    let args = match syn::parse::<args::TestAppArgs>(input) {
+        Ok(args) => args,
+        Err(error) => return TokenStream::from(error.to_compile_error()),
+    };
+    let actix = &args.actix_crate;
+
+    // Safely read all handler registrations from the REGISTRATION_MAP
+    let map = read_registry();
+
+    // One target per handler, named after its route path constant
+    let (constants, errors) = route_path_constants(&map, "generate_fuzz_targets!()");
+    let encode = percent_encode_fn();
+    let targets = constants
+        .iter()
+        .map(|(name, (path, route))| {
+            let fn_ident = quote::format_ident!("fuzz_{}", name.to_lowercase());
+            let doc = format!(
+                "Calls `{}` on `{} {}` with `data`",
+                route.handler_name,
+                http_method(&route.verb),
+                path
+            );
+            let app = test_app(route, &args);
+
+            // The path parameters are filled with the first parts of `data`, then
+            // comes the query string, when the route extracts one, and the body
+            let (template, parameters) = url_template(path);
+            let uri = if parameters.is_empty() {
+                quote!(String::from(#path))
+            } else {
+                let indexes = 0..parameters.len();
+                quote!(format!(#template, #(encode(parts[#indexes], b"-._~")),*))
+            };
+            let body = parameters.len() + usize::from(route.query_type.is_some());
+            let parts = body + 1;
+            let query = route.query_type.is_some().then(|| {
+                let index = parameters.len();
+                quote! {
+                    uri.push('?');
+                    uri.push_str(&encode(parts[#index], b"-._~=&"));
+                }
+            });
+            let verb = http_method(&route.verb);
+            let method = syn::Ident::new(verb, proc_macro2::Span::call_site());
+            // Server-Sent Events streams may never end
+            let read_body = (!route.sse).then(|| {
+                let to_bytes = if cfg!(feature = "actix3") {
+                    quote!(#actix::test::read_body(response))
+                } else {
+                    quote!(#actix::body::to_bytes(response.into_body()))
+                };
+                quote! {
+                    if let Ok(response) = response {
+                        let _ = #to_bytes.await;
+                    }
+                }
+            });
+            let (system, app_ref) = test_runtime(actix);
+            let cfg = cfg_attrs(route);
+
+            quote! {
+                #cfg
+                #[doc = #doc]
+                pub fn #fn_ident(data: &[u8]) {
+                    let parts = split(data, #parts);
+                    #[allow(unused_mut)]
+                    let mut uri = #uri;
+                    #query
+                    let body = parts[#body].to_vec();
+                    #system.block_on(async move {
+                        #[allow(unused_mut)]
+                        let mut app = #actix::test::init_service(#app).await;
+                        let request = #actix::test::TestRequest::default()
+                            .method(#actix::http::Method::#method)
+                            .uri(&uri)
+                            .set_payload(body)
+                            .to_request();
+                        #[allow(unused_variables)]
+                        let response = #actix::dev::Service::call(#app_ref, request).await;
+                        #read_body
+                    });
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // The targets of the handlers behind a `#[cfg]` are gated alike
+    let names = constants.values().map(|(path, route)| {
+        let cfg = cfg_attrs(route);
+        let name = format!("{} {}", http_method(&route.verb), path);
+        quote!(#cfg #name)
+    });
+    let fn_idents = constants.iter().map(|(name, (_, route))| {
+        let cfg = cfg_attrs(route);
+        let fn_ident = quote::format_ident!("fuzz_{}", name.to_lowercase());
+        quote!(#cfg #fn_ident)
+    });
+
+    // Generate the targets, for `cargo fuzz` targets to call (e.g.,
+    // `fuzz_target!(|data: &[u8]| app::fuzz_targets::fuzz_events_search(data));`)
+    let expanded = quote! {
+        #(#errors)*
+
+        /// Fuzz targets calling each handler registered automatically through
+        /// `actix_web::test`, to find the panics of its extractors and its body
+        pub mod fuzz_targets {
+            #[allow(unused_imports)]
+            use super::*;
+
+            /// The routes of the targets, as their method and path pattern, in the
+            /// order `fuzz_route` picks them in
+            pub const ROUTES: &[&str] = &[#(#names),*];
+
+            /// Calls the handler picked by the first byte of `data` with the rest
+            pub fn fuzz_route(data: &[u8]) {
+                const TARGETS: &[fn(&[u8])] = &[#(#fn_idents),*];
+                if let Some((index, data)) = data.split_first() {
+                    if !TARGETS.is_empty() {
+                        TARGETS[usize::from(*index) % TARGETS.len()](data);
+                    }
+                }
+            }
+
+            // Split `data` in `count` parts at its NUL bytes, the last one taking
+            // the rest, with empty parts when there are too few
+            fn split(data: &[u8], count: usize) -> Vec<&[u8]> {
+                let mut parts = data.splitn(count, |byte| *byte == 0).collect::<Vec<_>>();
+                parts.resize(count, &[]);
+                parts
+            }
+
+            #encode
+
+            #(#targets)*
+        }
+    };
+
+    TokenStream::from(expanded)
 }
 
-// Use a global RwLock map for storing registrations per unique module key
-static REGISTRATION_MAP: Lazy<RwLock<std::collections::HashMap<String, Vec<RouteInfo>>>> =
-    Lazy::new(|| RwLock::new(std::collections::HashMap::new()));
-
-#[proc_macro_attribute]
-pub fn auto_register(attr: TokenStream, item: TokenStream) -> TokenStream {
-    // Parse the input function
-    let input_fn = parse_macro_input!(item as ItemFn);
-    let fn_name = input_fn.sig.ident.to_string();
-
-    // Parse the prefix as a string literal
-    let prefix = if !attr.is_empty() {
-        let parsed_attr = parse_macro_input!(attr as syn::LitStr);
-        parsed_attr.value()
-    } else {
-        panic!("Expected a prefix (e.g., \"/scope\") as the argument to auto_register");
+#[proc_macro]
+pub fn generate_path_param_tests(input: TokenStream) -> TokenStream {
+    let args = match syn::parse::<args::TestAppArgs>(input) {
+        Ok(args) => args,
+        Err(error) => return TokenStream::from(error.to_compile_error()),
     };
+    let actix = &args.actix_crate;
 
-    // Extract the route path and HTTP verb from the function attributes
-    let mut route_path = None;
-    let mut verb = None;
+    // Safely read all handler registrations from the REGISTRATION_MAP
+    let map = read_registry();
 
-    for attr in &input_fn.attrs {
-        if let Some(segment) = attr.path().segments.last() {
-            if ["get", "post", "put", "delete", "patch"]
-                .contains(&segment.ident.to_string().as_str())
-            {
-                verb = Some(segment.ident.to_string().to_uppercase());
-                if let Ok(route_literal) = attr.parse_args::<LitStr>() {
-                    route_path = Some(route_literal.value());
+    // One property per handler with dynamic segments, named after its route path
+    // constant, feeding each segment values matching its expression and edge cases
+    let (constants, errors) = route_path_constants(&map, "generate_path_param_tests!()");
+    let encode = percent_encode_fn();
+    let tests = constants
+        .iter()
+        .filter(|(_, (path, _))| path.contains('{'))
+        .map(|(name, (path, route))| {
+            let fn_ident = syn::Ident::new(&name.to_lowercase(), proc_macro2::Span::call_site());
+            let (template, segments) = path_segments(path);
+            let params = (0..segments.len())
+                .map(|index| quote::format_ident!("param_{}", index))
+                .collect::<Vec<_>>();
+            let regexes = segments.iter().map(|(_, regex)| regex);
+            let app = test_app(route, &args);
+            let method = syn::Ident::new(http_method(&route.verb), proc_macro2::Span::call_site());
+            let (system, app_ref) = test_runtime(actix);
+            let cfg = cfg_attrs(route);
+
+            quote! {
+                #cfg
+                #[test]
+                fn #fn_ident(#(#params in values(#regexes)),*) {
+                    let uri = format!(#template, #(encode(#params.as_bytes(), b"-._~")),*);
+                    let status = #system.block_on(async {
+                        #[allow(unused_mut)]
+                        let mut app = #actix::test::init_service(#app).await;
+                        let request = #actix::test::TestRequest::default()
+                            .method(#actix::http::Method::#method)
+                            .uri(&uri)
+                            .to_request();
+                        #actix::test::call_service(#app_ref, request).await.status()
+                    });
+                    ::proptest::prop_assert_ne!(
+                        status,
+                        #actix::http::StatusCode::INTERNAL_SERVER_ERROR,
+                        "`{}` answered {}",
+                        uri,
+                        status
+                    );
                 }
             }
+        })
+        .collect::<Vec<_>>();
+
+    // Without dynamic segments, there is nothing to test
+    if tests.is_empty() {
+        return TokenStream::from(quote!(#(#errors)*));
+    }
+
+    // Generate the tests, run by `cargo test` with `proptest` among the dev-dependencies
+    let expanded = quote! {
+        #(#errors)*
+
+        #[cfg(test)]
+        mod path_param_tests {
+            #[allow(unused_imports)]
+            use super::*;
+
+            // Values matching the expression of a segment, or empty, unicode and
+            // overlong ones
+            fn values(
+                valid: &'static str,
+            ) -> impl ::proptest::strategy::Strategy<Value = String> {
+                ::proptest::prop_oneof![
+                    valid,
+                    ::proptest::strategy::Just(String::new()),
+                    "\\PC{1,16}",
+                    "[a-zA-Z0-9]{1024,4096}",
+                ]
+            }
+
+            #encode
+
+            ::proptest::proptest! {
+                #(#tests)*
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+#[proc_macro]
+pub fn generate_route_counts(_input: TokenStream) -> TokenStream {
+    // Safely read all handler registrations from the REGISTRATION_MAP
+    let map = read_registry();
+
+    // Handlers and static mounts are counted alike, as each is mounted once
+    let total = map.values().map(Vec::len).sum::<usize>();
+    let counts = map.iter().map(|(scope, routes)| {
+        let count = routes.len();
+        quote!((#scope, #count))
+    });
+
+    // Generate the counts and their lookup
+    let expanded = quote! {
+        /// The number of routes registered automatically, handlers and static mounts
+        pub const ROUTE_COUNT: usize = #total;
+
+        /// The `(key, number of routes)` of each module key, sorted by key
+        pub static ROUTE_COUNTS_BY_SCOPE: &[(&str, usize)] = &[#(#counts),*];
+
+        /// The number of routes registered under a module key, `None` for unknown keys
+        pub fn scope_route_count(scope: &str) -> Option<usize> {
+            ROUTE_COUNTS_BY_SCOPE
+                .iter()
+                .find(|(key, _)| *key == scope)
+                .map(|(_, count)| *count)
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+// A route as the runtime crate's `RouteEntry`, built in constant
+// contexts from the strings of the registry
+fn route_entry(route: &RouteInfo) -> proc_macro2::TokenStream {
+    let scope = route.prefix.as_ref();
+    let path = route.path.as_ref();
+    let verb = route.verb.as_ref();
+    let handler = route.handler_name.as_ref();
+    let source = format!("{}:{}", route.file, route.line);
+    quote! {
+        ::register_actix_routes_runtime::RouteEntry {
+            scope: ::std::borrow::Cow::Borrowed(#scope),
+            path: ::std::borrow::Cow::Borrowed(#path),
+            verb: ::std::borrow::Cow::Borrowed(#verb),
+            handler: ::std::borrow::Cow::Borrowed(#handler),
+            source: ::std::borrow::Cow::Borrowed(#source),
         }
     }
+}
 
-    // Validate the extracted route path and HTTP verb
-    if route_path.is_none() || verb.is_none() {
-        panic!(
-            "Could not extract the route path or verb from attributes on function '{}'. Ensure it has a valid Actix route macro like \
-            #[get(\"/path\")].",
-            fn_name
-        );
+// Name of the path constant of a handler: the words of its key followed by its
+// name, in upper case (e.g., "/api/{version}/events" and `search` -> `API_VERSION_EVENTS_SEARCH`)
+fn route_path_const_name(scope: &str, handler_name: &str) -> String {
+    let name = scope
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .chain(handler_name.split('_'))
+        .filter(|word| !word.is_empty())
+        .map(str::to_uppercase)
+        .collect::<Vec<_>>()
+        .join("_");
+    // Identifiers can't start with a digit (e.g., "/2fa")
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else {
+        name
     }
+}
 
-    // Use empty route path if valid (e.g., `""`)
-    let route_info = RouteInfo {
-        prefix: prefix.clone(),
-        handler_name: fn_name.clone(),
-        path: route_path.unwrap_or_else(|| "".to_string()),
-        verb: verb.unwrap(),
+#[proc_macro]
+pub fn generate_authz_matrix(_input: TokenStream) -> TokenStream {
+    // Safely read all handler registrations from the REGISTRATION_MAP
+    let map = read_registry();
+
+    let errors = unserved_scope_errors("generate_authz_matrix!()", map.keys().map(String::as_str));
+    let document = syn::LitStr::new(
+        &authz::routes_to_authz_matrix(&map),
+        proc_macro2::Span::call_site(),
+    );
+
+    // Generate code for the `authz_matrix` function
+    let expanded = quote! {
+        #(#errors)*
+
+        pub fn authz_matrix() -> String {
+            #document.to_string()
+        }
     };
 
-    // Safely store the route information
-    let mut map = REGISTRATION_MAP
-        .write()
-        .expect("Failed to acquire write lock");
-    map.entry(prefix.clone()).or_default().push(route_info);
+    TokenStream::from(expanded)
+}
 
-    // Generate the original function definition
+#[proc_macro]
+pub fn load_manifest(_input: TokenStream) -> TokenStream {
+    let file = manifest::OUT_DIR_FILE;
+
+    // Read the routes appended with `out-dir-manifest` as a `{ "routes": [...] }`
+    // manifest. Build scripts get `OUT_DIR` at runtime, while the targets of the
+    // package (e.g., integration tests) got it when they were compiled.
     let expanded = quote! {
-        #input_fn
+        match ::std::env::var_os("OUT_DIR")
+            .map(::std::path::PathBuf::from)
+            .or_else(|| ::core::option_env!("OUT_DIR").map(::std::path::PathBuf::from))
+        {
+            Some(out_dir) => ::std::fs::read_to_string(out_dir.join(#file)).map(|lines| {
+                let routes = lines
+                    .lines()
+                    .filter(|line| !line.is_empty())
+                    .collect::<::std::vec::Vec<_>>();
+                ::std::format!("{{\"routes\": [{}]}}", routes.join(", "))
+            }),
+            None => ::std::result::Result::Err(::std::io::Error::new(
+                ::std::io::ErrorKind::NotFound,
+                "OUT_DIR is not set, the package needs a build script",
+            )),
+        }
     };
 
     TokenStream::from(expanded)
 }
 
 #[proc_macro]
-pub fn generate_register_service(input: TokenStream) -> TokenStream {
-    // Parse the macro arguments (prefix and optional use_scope flag)
-    let args = parse_macro_input!(input as syn::ExprArray);
-    let module_key: Option<String>;
-    let mut use_scope = false; // Default to not using the prefix as the scope
-
-    // Parse the arguments
-    if let Some(syn::Expr::Lit(syn::ExprLit {
-        lit: syn::Lit::Str(lit_str),
-        ..
-    })) = args.elems.iter().next()
-    {
-        module_key = Some(lit_str.value());
-    } else {
-        panic!("Expected the first argument to be a string literal representing the module key.");
-    }
-
-    if let Some(syn::Expr::Assign(syn::ExprAssign { left, right, .. })) = args.elems.iter().nth(1) {
-        if let syn::Expr::Path(path) = &**left {
-            if path.path.is_ident("use_scope") {
-                if let syn::Expr::Lit(syn::ExprLit {
-                    lit: syn::Lit::Bool(lit_bool),
-                    ..
-                }) = &**right
-                {
-                    use_scope = lit_bool.value();
-                } else {
-                    panic!("The value of `use_scope` must be a boolean.");
+pub fn generate_routes_json(_input: TokenStream) -> TokenStream {
+    // Safely read all handler registrations from the REGISTRATION_MAP
+    let map = read_registry();
+
+    let errors = unserved_scope_errors("generate_routes_json!()", map.keys().map(String::as_str));
+    let json_parts = manifest::routes_to_json(&map);
+
+    // Generate code for the `routes_json` function
+    let expanded = quote! {
+        #(#errors)*
+
+        pub fn routes_json() -> String {
+            [#(#json_parts),*].concat()
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+#[proc_macro]
+pub fn generate_routes_mermaid(_input: TokenStream) -> TokenStream {
+    // Safely read all handler registrations from the REGISTRATION_MAP
+    let map = read_registry();
+
+    let diagram = syn::LitStr::new(
+        &mermaid::routes_to_mermaid(&map),
+        proc_macro2::Span::call_site(),
+    );
+
+    // Generate code for the `routes_mermaid` function
+    let expanded = quote! {
+        pub fn routes_mermaid() -> String {
+            #diagram.to_string()
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+#[proc_macro]
+pub fn generate_k6_script(input: TokenStream) -> TokenStream {
+    // Parse the defaults of the script, still defining `k6_script` on error
+    let args = match syn::parse::<args::K6Args>(input) {
+        Ok(args) => args,
+        Err(error) => {
+            let error = error.to_compile_error();
+            return TokenStream::from(quote! {
+                #error
+                pub fn k6_script() -> String {
+                    String::new()
                 }
-            }
+            });
         }
-    }
+    };
 
-    if module_key.is_none() {
-        panic!("Expected a module key as the first argument.");
-    }
+    // Safely read all handler registrations from the REGISTRATION_MAP
+    let map = read_registry();
 
-    // Safely read handler registrations for the specified module key
-    let map = REGISTRATION_MAP
-        .read()
-        .expect("Failed to acquire read lock");
-    let registrations = map.get(&module_key.unwrap()).cloned().unwrap_or_default();
-
-    // Group functions by their prefixes
-    let mut grouped_by_prefix: std::collections::HashMap<String, Vec<String>> =
-        std::collections::HashMap::new();
-    for RouteInfo {
-        prefix,
-        handler_name,
-        ..
-    } in registrations
-    {
-        grouped_by_prefix
-            .entry(prefix.clone())
-            .or_default()
-            .push(handler_name);
-    }
+    let errors = unserved_scope_errors("generate_k6_script!()", map.keys().map(String::as_str));
+    let script = syn::LitStr::new(
+        &k6::routes_to_k6(&map, &args),
+        proc_macro2::Span::call_site(),
+    );
 
-    // Generate the registration function code
-    let mut registration_functions = Vec::new();
-    for (prefix, functions) in grouped_by_prefix {
-        let fn_calls = functions.iter().map(|fn_name| {
-            let fn_ident = syn::Ident::new(fn_name, proc_macro2::Span::call_site());
-            quote! {
-                .service(#fn_ident)
-            }
-        });
+    // Generate code for the `k6_script` function
+    let expanded = quote! {
+        #(#errors)*
 
-        let scope_block = if use_scope {
-            quote! {
-                cfg.service(
-                    actix_web::web::scope(#prefix)
-                        #(#fn_calls)*
-                );
-            }
-        } else {
-            quote! {
-                cfg.service(
-                    actix_web::web::scope("")
-                        #(#fn_calls)*
-                );
-            }
-        };
+        pub fn k6_script() -> String {
+            #script.to_string()
+        }
+    };
 
-        registration_functions.push(scope_block);
-    }
+    TokenStream::from(expanded)
+}
+
+#[proc_macro]
+pub fn generate_routes_dot(_input: TokenStream) -> TokenStream {
+    // Safely read all handler registrations from the REGISTRATION_MAP
+    let map = read_registry();
 
+    let graph = syn::LitStr::new(&dot::routes_to_dot(&map), proc_macro2::Span::call_site());
+
+    // Generate code for the `routes_dot` function
     let expanded = quote! {
-        pub fn register_service(cfg: &mut actix_web::web::ServiceConfig) {
-            #(#registration_functions)*
+        pub fn routes_dot() -> String {
+            #graph.to_string()
         }
     };
 
@@ -178,62 +4332,326 @@ pub fn generate_register_service(input: TokenStream) -> TokenStream {
 }
 
 #[proc_macro]
-pub fn generate_list_routes(_input: TokenStream) -> TokenStream {
+pub fn generate_routes_markdown(_input: TokenStream) -> TokenStream {
     // Safely read all handler registrations from the REGISTRATION_MAP
-    let map = REGISTRATION_MAP
-        .read()
-        .expect("Failed to acquire read lock");
+    let map = read_registry();
 
-    // Collect all routes into a vector for table display
-    let mut rows = Vec::new();
-    for (scope, routes) in map.iter() {
-        for route in routes {
-            let scope_literal = syn::LitStr::new(scope, proc_macro2::Span::call_site());
-            let path_literal = syn::LitStr::new(&route.path, proc_macro2::Span::call_site());
-            let handler_literal =
-                syn::LitStr::new(&route.handler_name, proc_macro2::Span::call_site());
-            let verb_literal = syn::LitStr::new(&route.verb, proc_macro2::Span::call_site());
+    let document = syn::LitStr::new(
+        &markdown::routes_to_markdown(&map),
+        proc_macro2::Span::call_site(),
+    );
+
+    // Generate code for the `routes_markdown` function
+    let expanded = quote! {
+        pub fn routes_markdown() -> String {
+            #document.to_string()
+        }
+    };
+
+    TokenStream::from(expanded)
+}
 
-            rows.push(quote! {
-                Route {
-                    scope: #scope_literal.to_string(),
-                    path: #path_literal.to_string(),
-                    handler: #handler_literal.to_string(),
-                    verb: #verb_literal.to_string(),
+#[proc_macro]
+pub fn generate_openapi(input: TokenStream) -> TokenStream {
+    // Parse the target version and output format, still defining `openapi` on error
+    let args = match syn::parse::<args::OpenApiArgs>(input) {
+        Ok(args) => args,
+        Err(error) => {
+            let error = error.to_compile_error();
+            return TokenStream::from(quote! {
+                #error
+                pub fn openapi() -> String {
+                    String::new()
                 }
             });
         }
-    }
+    };
+
+    // Safely read all handler registrations from the REGISTRATION_MAP
+    let map = read_registry();
+
+    let errors = unserved_scope_errors("generate_openapi!()", map.keys().map(String::as_str));
+    let document = openapi::routes_to_openapi(&map, &args);
+    let parts = match args.format {
+        args::OpenApiFormat::Json => document.to_json(),
+        args::OpenApiFormat::Yaml => document.to_yaml(),
+    };
+    let schema_support = openapi::schema_support(&args.version);
 
-    // Generate code for the `list_routes` function
+    // Generate code for the `openapi` function
     let expanded = quote! {
-        pub fn list_routes() {
-            use tabled::{Table, Tabled};
+        #(#errors)*
 
-            #[derive(Tabled)]
-            struct Route {
-                #[tabled(rename = "Scope")]
-                scope: String,
-                #[tabled(rename = "Path")]
-                path: String,
-                #[tabled(rename = "Handler")]
-                handler: String,
-                #[tabled(rename = "Verb")]
-                verb: String,
-            }
+        pub fn openapi() -> String {
+            #schema_support
+            [#(#parts),*].concat()
+        }
+    };
 
-            let routes = vec![
-                #(#rows),*
-            ];
+    TokenStream::from(expanded)
+}
+
+#[proc_macro]
+pub fn generate_asyncapi(input: TokenStream) -> TokenStream {
+    // Parse the output format and `info` section, still defining `asyncapi` on error
+    let args = match syn::parse::<args::AsyncApiArgs>(input) {
+        Ok(args) => args,
+        Err(error) => {
+            let error = error.to_compile_error();
+            return TokenStream::from(quote! {
+                #error
+                pub fn asyncapi() -> String {
+                    String::new()
+                }
+            });
+        }
+    };
+
+    // Safely read all handler registrations from the REGISTRATION_MAP
+    let map = read_registry();
+
+    let errors = unserved_scope_errors("generate_asyncapi!()", map.keys().map(String::as_str));
+    let document = asyncapi::routes_to_asyncapi(&map, &args);
+    let parts = match args.format {
+        args::OpenApiFormat::Json => document.to_json(),
+        args::OpenApiFormat::Yaml => document.to_yaml(),
+    };
+
+    // Generate code for the `asyncapi` function
+    let expanded = quote! {
+        #(#errors)*
+
+        pub fn asyncapi() -> String {
+            [#(#parts),*].concat()
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+#[proc_macro]
+pub fn generate_pact(input: TokenStream) -> TokenStream {
+    // Parse the consumer and provider names, still defining `pact_contract` on error
+    let args = match syn::parse::<args::PactArgs>(input) {
+        Ok(args) => args,
+        Err(error) => {
+            let error = error.to_compile_error();
+            return TokenStream::from(quote! {
+                #error
+                pub fn pact_contract() -> String {
+                    String::new()
+                }
+            });
+        }
+    };
+
+    // Safely read all handler registrations from the REGISTRATION_MAP
+    let map = read_registry();
+
+    let errors = unserved_scope_errors("generate_pact!()", map.keys().map(String::as_str));
+    let parts = pact::routes_to_pact(&map, &args).to_json();
 
-            let table = Table::new(routes)
-                .with(tabled::settings::Style::modern())
-                .to_string();
+    // Generate code for the `pact_contract` function
+    let expanded = quote! {
+        #(#errors)*
+
+        pub fn pact_contract() -> String {
+            [#(#parts),*].concat()
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+#[proc_macro]
+pub fn generate_insomnia(input: TokenStream) -> TokenStream {
+    // Parse the workspace name and base URL, still defining `insomnia_export` on error
+    let args = match syn::parse::<args::InsomniaArgs>(input) {
+        Ok(args) => args,
+        Err(error) => {
+            let error = error.to_compile_error();
+            return TokenStream::from(quote! {
+                #error
+                pub fn insomnia_export() -> String {
+                    String::new()
+                }
+            });
+        }
+    };
+
+    // Safely read all handler registrations from the REGISTRATION_MAP
+    let map = read_registry();
+
+    let errors = unserved_scope_errors("generate_insomnia!()", map.keys().map(String::as_str));
+    let parts = insomnia::routes_to_insomnia(&map, &args).to_json();
+
+    // Generate code for the `insomnia_export` function
+    let expanded = quote! {
+        #(#errors)*
+
+        pub fn insomnia_export() -> String {
+            [#(#parts),*].concat()
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+#[proc_macro]
+pub fn generate_har(input: TokenStream) -> TokenStream {
+    // Parse the base URL, still defining `har_template` on error
+    let args = match syn::parse::<args::HarArgs>(input) {
+        Ok(args) => args,
+        Err(error) => {
+            let error = error.to_compile_error();
+            return TokenStream::from(quote! {
+                #error
+                pub fn har_template() -> String {
+                    String::new()
+                }
+            });
+        }
+    };
+
+    // Safely read all handler registrations from the REGISTRATION_MAP
+    let map = read_registry();
 
-            println!("List of the automatically registered routes:");
-            println!("{}", table);
+    let errors = unserved_scope_errors("generate_har!()", map.keys().map(String::as_str));
+    let parts = har::routes_to_har(&map, &args).to_json();
+
+    // Generate code for the `har_template` function
+    let expanded = quote! {
+        #(#errors)*
+
+        pub fn har_template() -> String {
+            [#(#parts),*].concat()
         }
     };
 
     TokenStream::from(expanded)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A `GET` handler of `src/api/events.rs` registered under `prefix`, for the
+    // tests to adjust
+    pub(crate) fn route(prefix: &str, path: &str, handler_name: &str) -> RouteInfo {
+        RouteInfo {
+            prefix: intern(prefix),
+            handler_name: intern(handler_name),
+            path: intern(path),
+            verb: intern("GET"),
+            file: intern("src/api/events.rs"),
+            line: 1,
+            module_path: intern("crate::api::events"),
+            ..RouteInfo::default()
+        }
+    }
+
+    #[test]
+    fn route_entries_are_the_runtime_crate_type() {
+        let entry = route_entry(&route("/events", "/search", "search")).to_string();
+        assert!(entry.starts_with(":: register_actix_routes_runtime :: RouteEntry {"));
+        assert!(entry.contains("\"src/api/events.rs:1\""));
+    }
+
+    #[test]
+    fn generators_fail_on_keys_not_served_yet() {
+        served_scopes().insert("/served".to_string(), "/served".to_string());
+        let errors =
+            unserved_scope_errors("generate_openapi!()", ["/served", "/pending", "/pending"]);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().contains(
+            "`generate_openapi!()` expanded before the `generate_register_service!` of `/pending`"
+        ));
+    }
+
+    #[test]
+    fn module_paths_follow_the_cargo_layout() {
+        assert_eq!(
+            module_path_from_file("src/lib.rs").as_deref(),
+            Some("crate")
+        );
+        assert_eq!(
+            module_path_from_file("/app/src/api/events/mod.rs").as_deref(),
+            Some("crate::api::events")
+        );
+        assert_eq!(
+            module_path_from_file("src\\api\\match.rs").as_deref(),
+            Some("crate::api::r#match")
+        );
+        assert_eq!(
+            module_path_from_file("src/bin/my-server.rs").as_deref(),
+            Some("crate")
+        );
+        assert_eq!(
+            module_path_from_file("src/bin/my-server/api.rs").as_deref(),
+            Some("crate::api")
+        );
+        assert_eq!(
+            module_path_from_file("examples/01_demo.rs").as_deref(),
+            Some("crate")
+        );
+        assert_eq!(
+            module_path_from_file("/home/me/examples/app/src/api.rs").as_deref(),
+            Some("crate::api")
+        );
+    }
+
+    #[test]
+    fn plain_tables_align_their_columns_and_span_the_scope_rows() {
+        let rows = [
+            vec!["/events".to_string()],
+            vec!["/{id}".to_string(), "GET".to_string(), "🔒".to_string()],
+            vec!["/".to_string(), "POST".to_string(), String::new()],
+        ];
+        assert_eq!(
+            plain_table(&["Path", "Verb", "Status"], &rows),
+            "Path   Verb  Status\n\
+             -----  ----  ------\n\
+             /events\n\
+             /{id}  GET   🔒\n\
+             /      POST"
+        );
+    }
+
+    #[test]
+    fn per_route_behaviors_are_rejected_on_routes_without_an_index() {
+        for option in [
+            r#"warn_slower_than_ms = 500"#,
+            r#"circuit_breaker(threshold = 5, cooldown = "30s")"#,
+        ] {
+            for route in [
+                r#"canary = 10"#,
+                r#"variant = "A""#,
+                r#"produces = "text/html""#,
+            ] {
+                let input = format!(r#""/events", {}, {}"#, route, option);
+                let error = match syn::parse_str::<args::AutoRegisterArgs>(&input) {
+                    Ok(_) => panic!("`{}` was accepted", input),
+                    Err(error) => error.to_string(),
+                };
+                assert!(error.contains("cannot be combined"), "{}: {}", input, error);
+            }
+        }
+    }
+
+    #[test]
+    fn files_not_named_like_modules_have_no_module_path() {
+        for file in [
+            "src/my-module.rs",
+            "src/01_demo.rs",
+            "src/self.rs",
+            "src/crate/api.rs",
+        ] {
+            assert_eq!(module_path_from_file(file), None, "{}", file);
+        }
+        assert_eq!(item_module_path("src/super.rs"), "crate");
+        assert_eq!(
+            module_prefix(&module_segments("src/my-module.rs")),
+            "/my-module"
+        );
+    }
+}
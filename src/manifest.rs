@@ -0,0 +1,301 @@
+use crate::document::Parts;
+use crate::{Registry, RouteInfo};
+use once_cell::sync::Lazy;
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+// Escape a value as a JSON string literal (quotes included)
+pub(crate) fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+// The fields of a route known at expansion time, as JSON values
+fn route_fields(scope: &str, route: &RouteInfo) -> Vec<(&'static str, String)> {
+    vec![
+        ("scope", json_string(scope)),
+        ("path", json_string(&route.path)),
+        ("handler", json_string(&route.handler_name)),
+        ("verb", json_string(&route.verb)),
+        ("sse", route.sse.to_string()),
+        (
+            "canary",
+            route
+                .canary
+                .map(|percent| percent.to_string())
+                .unwrap_or_else(|| "null".to_string()),
+        ),
+        (
+            "variant",
+            route
+                .variant
+                .as_ref()
+                .map(|(variant, _)| json_string(variant))
+                .unwrap_or_else(|| "null".to_string()),
+        ),
+        (
+            "weight",
+            route
+                .variant
+                .as_ref()
+                .map(|(_, weight)| weight.to_string())
+                .unwrap_or_else(|| "null".to_string()),
+        ),
+        (
+            "shadow_to",
+            route
+                .shadow_to
+                .as_deref()
+                .map(json_string)
+                .unwrap_or_else(|| "null".to_string()),
+        ),
+        (
+            "flag",
+            route
+                .flag
+                .as_deref()
+                .map(json_string)
+                .unwrap_or_else(|| "null".to_string()),
+        ),
+        (
+            "roles",
+            format!(
+                "[{}]",
+                route
+                    .roles
+                    .iter()
+                    .map(|role| json_string(role))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ),
+        (
+            "api_key",
+            route
+                .api_key
+                .as_deref()
+                .map(json_string)
+                .unwrap_or_else(|| "null".to_string()),
+        ),
+        ("csrf_exempt", route.csrf_exempt.to_string()),
+        ("session_required", route.session_required.to_string()),
+        (
+            "jwt_scopes",
+            format!(
+                "[{}]",
+                route
+                    .jwt_scopes
+                    .iter()
+                    .map(|scope| json_string(scope))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ),
+        ("audit", route.audit.to_string()),
+        ("idempotent", route.idempotent.to_string()),
+        (
+            "body_limit",
+            route
+                .body_limit
+                .as_deref()
+                .map(json_string)
+                .unwrap_or_else(|| "null".to_string()),
+        ),
+        (
+            "consumes",
+            route
+                .consumes
+                .as_deref()
+                .map(json_string)
+                .unwrap_or_else(|| "null".to_string()),
+        ),
+        (
+            "produces",
+            route
+                .produces
+                .as_deref()
+                .map(json_string)
+                .unwrap_or_else(|| "null".to_string()),
+        ),
+        ("etag", route.etag.to_string()),
+        (
+            "headers",
+            format!(
+                "{{{}}}",
+                route
+                    .headers
+                    .iter()
+                    .map(|(name, value)| {
+                        format!("{}: {}", json_string(name), json_string(value))
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ),
+        ("admin", route.admin.to_string()),
+        (
+            "log_format",
+            route
+                .log_format
+                .as_deref()
+                .map(json_string)
+                .unwrap_or_else(|| "null".to_string()),
+        ),
+        ("log", route.log.to_string()),
+        ("multipart", route.multipart.to_string()),
+        (
+            "multipart_limit",
+            route
+                .multipart_limit
+                .as_deref()
+                .map(json_string)
+                .unwrap_or_else(|| "null".to_string()),
+        ),
+        ("module", json_string(&route.module_path)),
+        ("file", json_string(&route.file)),
+        ("line", route.line.to_string()),
+        ("docs", json_string(&route.docs)),
+        (
+            "example_response",
+            route
+                .example_response
+                .as_deref()
+                .map(json_string)
+                .unwrap_or_else(|| "null".to_string()),
+        ),
+        (
+            "example_request",
+            route
+                .example_request
+                .as_deref()
+                .map(json_string)
+                .unwrap_or_else(|| "null".to_string()),
+        ),
+        (
+            "errors",
+            format!("[{}]", crate::error_codes_to_string(&route.errors)),
+        ),
+    ]
+}
+
+// Render every registered route as a JSON manifest:
+// { "routes": [ { "scope": ..., "path": ..., "handler": ..., "verb": ..., "sse": ..., "canary": ..., "variant": ..., "weight": ..., "shadow_to": ..., "flag": ..., "roles": [...], "api_key": ..., "csrf_exempt": ..., "session_required": ..., "jwt_scopes": [...], "audit": ..., "idempotent": ..., "body_limit": ..., "consumes": ..., "produces": ..., "etag": ..., "headers": {...}, "admin": ..., "log_format": ..., "log": ..., "multipart": ..., "multipart_limit": ..., "module": ..., "file": ..., "line": ..., "docs": ..., "example_response": ..., "example_request": ..., "errors": [...], "body": ..., "body_schema": ..., "query": ..., "query_schema": ... } ] }
+// and return it as the string expressions concatenated by `routes_json`
+pub(crate) fn routes_to_json(map: &Registry) -> Vec<TokenStream> {
+    let mut json = Parts::default();
+    let duplicates = crate::duplicate_routes(map);
+
+    json.push_str("{\n  \"routes\": [");
+    let mut first = true;
+    for (scope, routes) in map.iter() {
+        for route in routes {
+            let fields = route_fields(scope, route);
+
+            json.push_str(if first { "\n" } else { ",\n" });
+            first = false;
+
+            let fields = fields
+                .iter()
+                .map(|(key, value)| format!("      {}: {}", json_string(key), value))
+                .collect::<Vec<_>>()
+                .join(",\n");
+            json.push_str(&format!("    {{\n{}", fields));
+
+            for (part, ty) in [("body", &route.body_type), ("query", &route.query_type)] {
+                json.push_str(&format!(
+                    ",\n      {}: {}",
+                    json_string(part),
+                    ty.as_deref()
+                        .map(json_string)
+                        .unwrap_or_else(|| "null".to_string())
+                ));
+
+                // The schema is produced next to the handler, see `auto_register`
+                if cfg!(feature = "schemars") && ty.is_some() {
+                    let schema_fn = crate::schema_fn_path(route, part);
+                    json.push_str(&format!(",\n      \"{}_schema\": ", part));
+                    json.push_expr(quote!(#schema_fn()));
+                }
+            }
+
+            // Whether another route has the same verb and full path, see `duplicate_routes`
+            json.push_str(&format!(
+                ",\n      \"duplicate\": {}",
+                duplicates.contains(&(route.file.clone(), route.line))
+            ));
+
+            json.push_str("\n    }");
+        }
+    }
+    json.push_str(if first { "]\n}" } else { "\n  ]\n}" });
+
+    json.finish()
+}
+
+// Name of the file of `OUT_DIR` the routes are appended to with `out-dir-manifest`
+pub(crate) const OUT_DIR_FILE: &str = "register_actix_routes.jsonl";
+
+// Append a route to the `OUT_DIR` file as a JSON line, with the fields of the
+// manifest known at expansion time (schemas are only known at runtime). The file
+// is truncated by the first route of each compilation, so it never keeps the
+// routes of a previous build.
+pub(crate) fn append_to_out_dir(scope: &str, route: &RouteInfo) -> Result<(), String> {
+    static FILE: Lazy<Mutex<Option<File>>> = Lazy::new(|| Mutex::new(None));
+    static MISSING_OUT_DIR_REPORTED: AtomicBool = AtomicBool::new(false);
+
+    // Report a missing `OUT_DIR` once, rather than at every route
+    let Some(out_dir) = std::env::var_os("OUT_DIR") else {
+        if MISSING_OUT_DIR_REPORTED.swap(true, Ordering::Relaxed) {
+            return Ok(());
+        }
+        return Err("The `out-dir-manifest` feature needs `OUT_DIR`, which Cargo only sets for packages with a build script (an empty `fn main() {}` in `build.rs` is enough).".to_string());
+    };
+
+    let mut fields = route_fields(scope, route);
+    for (part, ty) in [("body", &route.body_type), ("query", &route.query_type)] {
+        let ty = ty
+            .as_deref()
+            .map(json_string)
+            .unwrap_or_else(|| "null".to_string());
+        fields.push((part, ty));
+    }
+    let line = format!(
+        "{{{}}}\n",
+        fields
+            .iter()
+            .map(|(key, value)| format!("{}: {}", json_string(key), value))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    let mut file = FILE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let path = Path::new(&out_dir).join(OUT_DIR_FILE);
+    if file.is_none() {
+        *file = Some(
+            File::create(&path)
+                .map_err(|error| format!("Failed to create `{}`: {}", path.display(), error))?,
+        );
+    }
+    file.as_mut()
+        .expect("The file was just created")
+        .write_all(line.as_bytes())
+        .map_err(|error| format!("Failed to write `{}`: {}", path.display(), error))
+}
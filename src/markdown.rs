@@ -0,0 +1,87 @@
+use crate::{Registry, RouteInfo};
+
+// Escape a value for a Markdown table cell (pipes split cells, newlines end rows)
+pub(crate) fn table_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', "<br>")
+}
+
+// Show an extracted type as code, or nothing when the handler has no such extractor
+fn type_cell(ty: Option<&str>) -> String {
+    ty.map(|ty| format!("`{}`", table_cell(ty)))
+        .unwrap_or_default()
+}
+
+// Show the request body: the `Json<T>` type and the media type the route consumes,
+// or the multipart upload and its limit
+fn body_cell(route: &RouteInfo) -> String {
+    match (route.multipart, &route.multipart_limit, &route.consumes) {
+        (true, Some(limit), _) => format!("multipart (up to {})", table_cell(limit)),
+        (true, None, _) => "multipart".to_string(),
+        (false, _, Some(media_type)) => match &route.body_type {
+            Some(ty) => format!("{} ({})", type_cell(Some(ty)), table_cell(media_type)),
+            None => table_cell(media_type),
+        },
+        (false, _, None) => type_cell(route.body_type.as_deref()),
+    }
+}
+
+// Render the registry as a Markdown API reference:
+// one section per scope, one table per resource (route path)
+pub(crate) fn routes_to_markdown(map: &Registry) -> String {
+    let mut document = String::from("# API Reference\n");
+
+    for (scope, routes) in map.iter() {
+        let scope_title = if scope.is_empty() {
+            "/"
+        } else {
+            scope.as_str()
+        };
+        document.push_str(&format!("\n## `{}`\n", scope_title));
+
+        // Group the routes of the scope by resource, keeping registration order
+        let mut resources: Vec<(&str, Vec<&RouteInfo>)> = Vec::new();
+        for route in routes {
+            match resources.iter_mut().find(|(path, _)| **path == *route.path) {
+                Some((_, resource_routes)) => resource_routes.push(route),
+                None => resources.push((&route.path, vec![route])),
+            }
+        }
+
+        for (path, resource_routes) in resources {
+            document.push_str(&format!("\n### `{}{}`\n\n", scope, path));
+            document
+                .push_str("| Verb | Handler | Description | Query | Body | Errors | Source |\n");
+            document
+                .push_str("|------|---------|-------------|-------|------|--------|--------|\n");
+            for route in &resource_routes {
+                document.push_str(&format!(
+                    "| {} | `{}` | {} | {} | {} | {} | `{}:{}` |\n",
+                    crate::verb_label(route),
+                    route.handler_name,
+                    table_cell(&route.docs),
+                    type_cell(route.query_type.as_deref()),
+                    body_cell(route),
+                    crate::error_codes_to_string(&route.errors),
+                    route.file,
+                    route.line
+                ));
+            }
+
+            for route in resource_routes
+                .iter()
+                .filter(|route| route.example_response.is_some())
+            {
+                document.push_str(&format!(
+                    "\nExample response of `{} {}{}`:\n\n```{}\n{}\n```\n",
+                    route.verb,
+                    scope,
+                    path,
+                    if route.sse { "text" } else { "json" },
+                    route.example_response.as_deref().unwrap_or_default()
+                ));
+            }
+        }
+    }
+
+    document
+}
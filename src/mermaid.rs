@@ -0,0 +1,46 @@
+use crate::Registry;
+
+// Escape a label for a quoted Mermaid node (quotes are not allowed inside)
+fn mermaid_label(value: &str) -> String {
+    value
+        .replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+}
+
+// Render the registry as a Mermaid flowchart: one node per scope, one node per route
+pub(crate) fn routes_to_mermaid(map: &Registry) -> String {
+    let mut lines = vec![
+        "flowchart LR".to_string(),
+        "    root((\"App\"))".to_string(),
+    ];
+
+    for (scope_index, (scope, routes)) in map.iter().enumerate() {
+        let scope_id = format!("scope_{}", scope_index);
+        let scope_label = if scope.is_empty() {
+            "/"
+        } else {
+            scope.as_str()
+        };
+        lines.push(format!(
+            "    {}[\"{}\"]",
+            scope_id,
+            mermaid_label(scope_label)
+        ));
+        lines.push(format!("    root --> {}", scope_id));
+
+        for (route_index, route) in routes.iter().enumerate() {
+            let route_id = format!("{}_route_{}", scope_id, route_index);
+            lines.push(format!(
+                "    {}[\"{} {}<br/>{}\"]",
+                route_id,
+                mermaid_label(&route.verb),
+                mermaid_label(&route.path),
+                mermaid_label(&route.handler_name)
+            ));
+            lines.push(format!("    {} --> {}", scope_id, route_id));
+        }
+    }
+
+    lines.join("\n")
+}
@@ -0,0 +1,1040 @@
+use crate::{RouteInfo, RouteKind};
+use proc_macro2::TokenStream;
+use quote::quote;
+
+// Per-route behaviors of `register_service`, run by a middleware wrapping each
+// resource, which knows the route it serves
+#[derive(Default)]
+pub(crate) struct RouteMiddleware {
+    // Statements run before the handler with `req` and `route` (the index of the
+    // request's route in the key's registrations, if any), which may answer early
+    pub(crate) before: Vec<TokenStream>,
+    // Statements run after the handler with `route`, `started` (an `Instant`)
+    // and `result` (the handler's `Result<ServiceResponse, Error>`)
+    pub(crate) after: Vec<TokenStream>,
+}
+
+impl RouteMiddleware {
+    // Whether any behavior was added, for the resources to be wrapped
+    pub(crate) fn is_empty(&self) -> bool {
+        self.before.is_empty() && self.after.is_empty()
+    }
+
+    // `__route_middleware`, running the behaviors around the handler of a route
+    // given its index, and `__route_wrap`, calling it from `middleware::from_fn`.
+    // Services are wrapped by their route macro, through the slot `register_service`
+    // hands `__route_middleware` to, so it takes the rest of the chain type-erased.
+    pub(crate) fn items(&self, actix: &syn::Path) -> TokenStream {
+        let (next_type, future_type) = next_types(actix);
+        let erase = erase_next(actix);
+        let before = &self.before;
+        let after = &self.after;
+        quote! {
+            fn __route_middleware(
+                route: Option<usize>,
+                req: #actix::dev::ServiceRequest,
+                next: #next_type,
+            ) -> #future_type {
+                ::std::boxed::Box::pin(async move {
+                    #(#before)*
+                    #[allow(unused_variables)]
+                    let started = ::std::time::Instant::now();
+                    let result = next(req).await;
+                    #(#after)*
+                    result
+                })
+            }
+
+            async fn __route_wrap<B: #actix::body::MessageBody + 'static>(
+                route: Option<usize>,
+                req: #actix::dev::ServiceRequest,
+                next: #actix::middleware::Next<B>,
+            ) -> ::std::result::Result<#actix::dev::ServiceResponse, #actix::Error> {
+                __route_middleware(route, req, #erase).await
+            }
+        }
+    }
+
+    // The `wrap` of a resource built by `register_service`
+    pub(crate) fn wrap_resource(actix: &syn::Path, route: Option<usize>) -> TokenStream {
+        let route = match route {
+            Some(route) => quote!(Some(#route)),
+            None => quote!(None),
+        };
+        quote! {
+            .wrap(#actix::middleware::from_fn(
+                |req: #actix::dev::ServiceRequest, next: #actix::middleware::Next<_>| {
+                    __route_wrap(#route, req, next)
+                },
+            ))
+        }
+    }
+}
+
+// Types of the rest of the middleware chain once type-erased, and of its future
+fn next_types(actix: &syn::Path) -> (TokenStream, TokenStream) {
+    let future_type = quote! {
+        ::std::pin::Pin<::std::boxed::Box<dyn ::std::future::Future<
+            Output = ::std::result::Result<#actix::dev::ServiceResponse, #actix::Error>,
+        >>>
+    };
+    let next_type = quote! {
+        ::std::boxed::Box<dyn ::std::ops::FnOnce(#actix::dev::ServiceRequest) -> #future_type>
+    };
+    (next_type, future_type)
+}
+
+// Expression erasing the type of `next`, a `middleware::Next<B>`
+fn erase_next(actix: &syn::Path) -> TokenStream {
+    let (_, future_type) = next_types(actix);
+    quote! {
+        ::std::boxed::Box::new(move |req: #actix::dev::ServiceRequest| -> #future_type {
+            ::std::boxed::Box::pin(async move {
+                next.call(req).await.map(|res| res.map_into_boxed_body())
+            })
+        })
+    }
+}
+
+// Name of the slot `register_service` stores the route index and middleware of a
+// service in, and of the middleware its route macro wraps it with
+pub(crate) fn slot_idents(handler_name: &str) -> (syn::Ident, syn::Ident) {
+    (
+        quote::format_ident!("__{}_ROUTE_MIDDLEWARE", handler_name.to_uppercase()),
+        quote::format_ident!("__{}_route_middleware", handler_name),
+    )
+}
+
+// The slot and the middleware defined next to a service by `auto_register`, for
+// its route macro to wrap it with. Requests reach the handler directly until
+// `register_service` fills the slot, or when it has no behavior to run.
+pub(crate) fn service_slot(actix: &syn::Path, handler_name: &str) -> TokenStream {
+    let (slot, middleware) = slot_idents(handler_name);
+    let (next_type, future_type) = next_types(actix);
+    let erase = erase_next(actix);
+    quote! {
+        #[doc(hidden)]
+        pub static #slot: ::std::sync::OnceLock<(
+            Option<usize>,
+            fn(Option<usize>, #actix::dev::ServiceRequest, #next_type) -> #future_type,
+        )> = ::std::sync::OnceLock::new();
+
+        #[doc(hidden)]
+        pub async fn #middleware<B: #actix::body::MessageBody + 'static>(
+            req: #actix::dev::ServiceRequest,
+            next: #actix::middleware::Next<B>,
+        ) -> ::std::result::Result<#actix::dev::ServiceResponse, #actix::Error> {
+            match #slot.get() {
+                Some((route, route_middleware)) => route_middleware(*route, req, #erase).await,
+                None => next.call(req).await.map(|res| res.map_into_boxed_body()),
+            }
+        }
+    }
+}
+
+// Index of the route whose behaviors apply to each registration: its own, the
+// stable handler's for a canary, whose requests are accounted to it, and none for
+// static mounts, A/B variants and negotiated handlers, which can't carry any
+pub(crate) fn route_indexes(registrations: &[RouteInfo]) -> Vec<Option<usize>> {
+    let is_stable = |route: &RouteInfo| {
+        route.kind != RouteKind::Static
+            && route.canary.is_none()
+            && route.variant.is_none()
+            && route.produces.is_none()
+    };
+    registrations
+        .iter()
+        .enumerate()
+        .map(|(index, route)| {
+            if route.canary.is_some() {
+                registrations.iter().position(|stable| {
+                    is_stable(stable)
+                        && stable.prefix == route.prefix
+                        && stable.verb == route.verb
+                        && stable.path == route.path
+                })
+            } else {
+                is_stable(route).then_some(index)
+            }
+        })
+        .collect()
+}
+
+// The statement naming the active OpenTelemetry span's route after the path
+// template Actix matched (e.g., "/events/{id}"), so traces aggregate by template
+pub(crate) fn otel_route() -> TokenStream {
+    quote! {
+        if let Some(template) = req.match_pattern() {
+            ::opentelemetry::trace::get_active_span(|span| {
+                span.set_attribute(::opentelemetry::KeyValue::new("http.route", template));
+            });
+        }
+    }
+}
+
+// Handler names of a key's registrations, indexed like the middleware's `route`
+pub(crate) fn route_handlers(registrations: &[RouteInfo]) -> TokenStream {
+    let count = registrations.len();
+    let handlers = registrations
+        .iter()
+        .map(|route| route.handler_name.as_ref());
+    quote! {
+        static __ROUTE_HANDLERS: [&str; #count] = [#(#handlers),*];
+    }
+}
+
+// The app data defaults of the key, shared by the workers, the statements
+// registering them with the app data where `register_service` is configured,
+// and the check panicking when a type the key or the request's route requires
+// is missing. Each route is checked on its first request only.
+pub(crate) fn required_data(
+    actix: &syn::Path,
+    key: &str,
+    requires: &[(syn::Path, Option<syn::Expr>)],
+    registrations: &[RouteInfo],
+) -> Option<RequiredData> {
+    if requires.is_empty() && registrations.iter().all(|route| route.requires.is_empty()) {
+        return None;
+    }
+
+    let mut items = Vec::new();
+    let mut defaults = Vec::new();
+    let mut checks = Vec::new();
+    for (index, (ty, default)) in requires.iter().enumerate() {
+        let name = crate::tokens_to_string(&quote!(#ty));
+        match default {
+            Some(default) => {
+                let cell = quote::format_ident!("__REQUIRED_DATA_DEFAULT_{}", index);
+                items.push(quote! {
+                    static #cell: ::std::sync::OnceLock<#actix::web::Data<#ty>> =
+                        ::std::sync::OnceLock::new();
+                });
+                defaults.push(quote! {
+                    cfg.app_data(#cell.get_or_init(|| #actix::web::Data::new(#default)).clone());
+                });
+            }
+            None => {
+                let message = format!(
+                    "register_service: the routes of `{}` require `web::Data<{}>`, add it with `App::app_data(web::Data::new(...))`",
+                    key, name
+                );
+                checks.push(quote! {
+                    if req.app_data::<#actix::web::Data<#ty>>().is_none() {
+                        panic!("{}", #message);
+                    }
+                });
+            }
+        }
+    }
+
+    // Types are checked by route, since they can't be listed in a static table
+    let arms = registrations
+        .iter()
+        .enumerate()
+        .filter(|(_, route)| !route.requires.is_empty())
+        .map(|(index, route)| {
+            let checks = route.requires.iter().map(|ty| {
+                let path: syn::Path =
+                    syn::parse_str(ty).expect("Types are validated by auto_register");
+                let message = format!(
+                    "register_service: route `{}` of `{}` requires `web::Data<{}>`, add it with `App::app_data(web::Data::new(...))`",
+                    route.handler_name, key, ty
+                );
+                quote! {
+                    if req.app_data::<#actix::web::Data<#path>>().is_none() {
+                        panic!("{}", #message);
+                    }
+                }
+            });
+            quote!(Some(#index) => { #(#checks)* })
+        })
+        .collect::<Vec<_>>();
+    if !arms.is_empty() {
+        checks.push(quote! {
+            match route {
+                #(#arms)*
+                _ => {}
+            }
+        });
+    }
+
+    // The data doesn't change once the app is built, so a route found complete
+    // stays complete. Routes without an index of their own share the last flag.
+    let check = (!checks.is_empty()).then(|| {
+        let count = registrations.len() + 1;
+        let unchecked = (0..count).map(|_| quote!(::std::sync::atomic::AtomicBool::new(false)));
+        items.push(quote! {
+            static __REQUIRED_DATA_CHECKED: [::std::sync::atomic::AtomicBool; #count] =
+                [#(#unchecked),*];
+        });
+        quote! {
+            let checked = &__REQUIRED_DATA_CHECKED[route.unwrap_or(#count - 1)];
+            if !checked.swap(true, ::std::sync::atomic::Ordering::Relaxed) {
+                #(#checks)*
+            }
+        }
+    });
+
+    Some(RequiredData {
+        items: quote!(#(#items)*),
+        defaults: quote!(#(#defaults)*),
+        check,
+    })
+}
+
+// What `required_data` adds to `register_service`: items next to it, statements
+// in its body and, when types have no default, the check of the middleware
+pub(crate) struct RequiredData {
+    pub(crate) items: TokenStream,
+    pub(crate) defaults: TokenStream,
+    pub(crate) check: Option<TokenStream>,
+}
+
+// Indexes of the routes running behaviors of their own: all but the ones without
+// an index of their own (see `route_indexes`) and re-exported handlers, which have
+// no route macro to wrap them
+fn routes_with_behaviors(registrations: &[RouteInfo]) -> Vec<usize> {
+    route_indexes(registrations)
+        .into_iter()
+        .enumerate()
+        .filter(|(index, route)| *route == Some(*index) && !registrations[*index].reexported)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+// `RouteToggles`, turning routes on and off by handler name at runtime, and
+// the check answering 503 for the disabled ones
+pub(crate) fn route_toggles(
+    actix: &syn::Path,
+    registrations: &[RouteInfo],
+) -> (TokenStream, TokenStream) {
+    let count = registrations.len();
+    let enabled = registrations
+        .iter()
+        .map(|_| quote!(::std::sync::atomic::AtomicBool::new(true)));
+    // Other routes, such as static mounts, can't be turned off by name
+    let toggled = routes_with_behaviors(registrations);
+    let toggled_count = toggled.len();
+
+    let items = quote! {
+        static __ROUTE_ENABLED: [::std::sync::atomic::AtomicBool; #count] = [#(#enabled),*];
+
+        /// Turns the routes registered by `register_service` on and off at runtime,
+        /// by handler name. Disabled routes answer `503 Service Unavailable`.
+        pub struct RouteToggles;
+
+        impl RouteToggles {
+            const TOGGLED: [usize; #toggled_count] = [#(#toggled),*];
+
+            /// Disables the routes of a handler, returning `false` if it isn't registered here
+            pub fn disable(handler: &str) -> bool {
+                Self::set(handler, false)
+            }
+
+            /// Enables the routes of a handler again, returning `false` if it isn't registered here
+            pub fn enable(handler: &str) -> bool {
+                Self::set(handler, true)
+            }
+
+            /// Whether the routes of a handler are enabled, `None` if it isn't registered here
+            pub fn is_enabled(handler: &str) -> Option<bool> {
+                Self::TOGGLED
+                    .into_iter()
+                    .find(|index| __ROUTE_HANDLERS[*index] == handler)
+                    .map(|index| __ROUTE_ENABLED[index].load(::std::sync::atomic::Ordering::Relaxed))
+            }
+
+            fn set(handler: &str, enabled: bool) -> bool {
+                let mut found = false;
+                for index in Self::TOGGLED {
+                    if __ROUTE_HANDLERS[index] == handler {
+                        __ROUTE_ENABLED[index].store(enabled, ::std::sync::atomic::Ordering::Relaxed);
+                        found = true;
+                    }
+                }
+                found
+            }
+        }
+    };
+
+    let check = quote! {
+        if let Some(route) = route {
+            if !__ROUTE_ENABLED[route].load(::std::sync::atomic::Ordering::Relaxed) {
+                return Ok(req.into_response(#actix::HttpResponse::ServiceUnavailable().finish()));
+            }
+        }
+    };
+
+    (items, check)
+}
+
+// `route_hit_counts()`, reading the number of requests served by each route,
+// and the counting statement
+pub(crate) fn route_hit_counts(registrations: &[RouteInfo]) -> (TokenStream, TokenStream) {
+    let count = registrations.len();
+    let hits = registrations
+        .iter()
+        .map(|_| quote!(::std::sync::atomic::AtomicU64::new(0)));
+    // Other routes, such as static mounts, aren't counted, so they aren't listed either
+    let counted = routes_with_behaviors(registrations);
+
+    let items = quote! {
+        static __ROUTE_HITS: [::std::sync::atomic::AtomicU64; #count] = [#(#hits),*];
+
+        /// Number of requests each route registered by `register_service` received
+        /// since startup, by handler name
+        pub fn route_hit_counts() -> Vec<(&'static str, u64)> {
+            [#(#counted),*]
+                .into_iter()
+                .map(|index: usize| {
+                    (
+                        __ROUTE_HANDLERS[index],
+                        __ROUTE_HITS[index].load(::std::sync::atomic::Ordering::Relaxed),
+                    )
+                })
+                .collect()
+        }
+    };
+
+    let count = quote! {
+        if let Some(route) = route {
+            __ROUTE_HITS[route].fetch_add(1, ::std::sync::atomic::Ordering::Relaxed);
+        }
+    };
+
+    (items, count)
+}
+
+// Thresholds of the routes to report when slow, from their own option or the
+// generator's, and the statement logging the requests exceeding them
+pub(crate) fn slow_request_warnings(
+    registrations: &[RouteInfo],
+    default_threshold: Option<u64>,
+) -> Option<(TokenStream, TokenStream)> {
+    let thresholds = registrations
+        .iter()
+        .map(|route| {
+            if route.kind == RouteKind::Static {
+                None
+            } else {
+                route.warn_slower_than_ms.or(default_threshold)
+            }
+        })
+        .collect::<Vec<_>>();
+    if thresholds.iter().all(Option::is_none) {
+        return None;
+    }
+
+    let count = thresholds.len();
+    let thresholds = thresholds.iter().map(|threshold| match threshold {
+        Some(threshold) => quote!(Some(#threshold)),
+        None => quote!(None),
+    });
+    let items = quote! {
+        static __ROUTE_SLOW_MS: [Option<u64>; #count] = [#(#thresholds),*];
+    };
+
+    let warning = quote! {
+        if let Some((route, threshold)) =
+            route.and_then(|route| Some((route, __ROUTE_SLOW_MS[route]?)))
+        {
+            let elapsed = started.elapsed().as_millis();
+            if elapsed > u128::from(threshold) {
+                ::log::warn!(
+                    "Slow request to `{}`: {} ms (threshold {} ms)",
+                    __ROUTE_HANDLERS[route],
+                    elapsed,
+                    threshold
+                );
+            }
+        }
+    };
+
+    Some((items, warning))
+}
+
+// Circuit breakers of the routes declaring one: the check answering 503 while a
+// breaker is open, and the statement counting consecutive failures (errors and
+// 5xx responses) to open it
+pub(crate) fn circuit_breakers(
+    actix: &syn::Path,
+    registrations: &[RouteInfo],
+) -> Option<(TokenStream, TokenStream, TokenStream)> {
+    if registrations
+        .iter()
+        .all(|route| route.circuit_breaker.is_none())
+    {
+        return None;
+    }
+
+    let count = registrations.len();
+    let breakers = registrations
+        .iter()
+        .map(|route| match route.circuit_breaker {
+            Some((threshold, cooldown_ms)) => quote!(Some((#threshold, #cooldown_ms))),
+            None => quote!(None),
+        });
+    let failures = registrations
+        .iter()
+        .map(|_| quote!(::std::sync::atomic::AtomicU32::new(0)));
+    let open_until = registrations
+        .iter()
+        .map(|_| quote!(::std::sync::atomic::AtomicU64::new(0)));
+
+    // Breakers stay open until a time in milliseconds since the Unix epoch
+    let items = quote! {
+        static __ROUTE_BREAKERS: [Option<(u32, u64)>; #count] = [#(#breakers),*];
+        static __ROUTE_FAILURES: [::std::sync::atomic::AtomicU32; #count] = [#(#failures),*];
+        static __ROUTE_OPEN_UNTIL: [::std::sync::atomic::AtomicU64; #count] = [#(#open_until),*];
+
+        fn __route_breaker_now() -> u64 {
+            ::std::time::SystemTime::now()
+                .duration_since(::std::time::UNIX_EPOCH)
+                .map_or(0, |now| now.as_millis() as u64)
+        }
+    };
+
+    let check = quote! {
+        if let Some(route) = route.filter(|route| __ROUTE_BREAKERS[*route].is_some()) {
+            let open_until = __ROUTE_OPEN_UNTIL[route].load(::std::sync::atomic::Ordering::Relaxed);
+            let now = __route_breaker_now();
+            if open_until > now {
+                let retry_after = (open_until - now).div_ceil(1000).to_string();
+                let response = #actix::HttpResponse::ServiceUnavailable()
+                    .insert_header(("Retry-After", retry_after))
+                    .finish();
+                return Ok(req.into_response(response));
+            }
+        }
+    };
+
+    let record = quote! {
+        if let Some((route, (threshold, cooldown_ms))) =
+            route.and_then(|route| Some((route, __ROUTE_BREAKERS[route]?)))
+        {
+            let failed = result
+                .as_ref()
+                .map_or(true, |res| res.status().is_server_error());
+            if !failed {
+                __ROUTE_FAILURES[route].store(0, ::std::sync::atomic::Ordering::Relaxed);
+            } else if __ROUTE_FAILURES[route].fetch_add(1, ::std::sync::atomic::Ordering::Relaxed) + 1
+                >= threshold
+            {
+                __ROUTE_FAILURES[route].store(0, ::std::sync::atomic::Ordering::Relaxed);
+                __ROUTE_OPEN_UNTIL[route].store(
+                    __route_breaker_now() + cooldown_ms,
+                    ::std::sync::atomic::Ordering::Relaxed,
+                );
+            }
+        }
+    };
+
+    Some((items, check, record))
+}
+
+// Flags of the routes declaring one, and the check answering 404 unless the
+// app's provider enables the flag
+pub(crate) fn feature_flags(
+    actix: &syn::Path,
+    registrations: &[RouteInfo],
+) -> Option<(TokenStream, TokenStream)> {
+    if registrations.iter().all(|route| route.flag.is_none()) {
+        return None;
+    }
+
+    let count = registrations.len();
+    let flags = registrations
+        .iter()
+        .map(|route| match route.flag.as_deref() {
+            Some(flag) => quote!(Some(#flag)),
+            None => quote!(None),
+        });
+    let items = quote! {
+        static __ROUTE_FLAGS: [Option<&str>; #count] = [#(#flags),*];
+    };
+
+    // Without a provider the flags are off, so dark launched routes stay hidden
+    let check = quote! {
+        if let Some(flag) = route.and_then(|route| __ROUTE_FLAGS[route]) {
+            let enabled = req
+                .app_data::<#actix::web::Data<dyn ::register_actix_routes_runtime::FeatureFlagProvider>>()
+                .is_some_and(|provider| provider.is_enabled(flag));
+            if !enabled {
+                return Ok(req.into_response(#actix::HttpResponse::NotFound().finish()));
+            }
+        }
+    };
+
+    Some((items, check))
+}
+
+// Headers of the routes requiring an API key, and the check answering 401
+// unless the app's store accepts the key sent
+pub(crate) fn api_keys(
+    actix: &syn::Path,
+    registrations: &[RouteInfo],
+) -> Option<(TokenStream, TokenStream)> {
+    if registrations.iter().all(|route| route.api_key.is_none()) {
+        return None;
+    }
+
+    let count = registrations.len();
+    let headers = registrations
+        .iter()
+        .map(|route| match route.api_key.as_deref() {
+            Some(header) => quote!(Some(#header)),
+            None => quote!(None),
+        });
+    let items = quote! {
+        static __ROUTE_API_KEYS: [Option<&str>; #count] = [#(#headers),*];
+    };
+
+    let check = quote! {
+        if let Some(header) = route.and_then(|route| __ROUTE_API_KEYS[route]) {
+            let valid = req
+                .headers()
+                .get(header)
+                .and_then(|key| key.to_str().ok())
+                .zip(req.app_data::<#actix::web::Data<dyn ::register_actix_routes_runtime::ApiKeyStore>>())
+                .is_some_and(|(key, store)| store.is_valid(key));
+            if !valid {
+                return Ok(req.into_response(#actix::HttpResponse::Unauthorized().finish()));
+            }
+        }
+    };
+
+    Some((items, check))
+}
+
+// Routes requiring a session, and the check answering 401 when actix-session
+// finds no session data for the request
+pub(crate) fn required_sessions(
+    actix: &syn::Path,
+    registrations: &[RouteInfo],
+) -> Option<(TokenStream, TokenStream)> {
+    if registrations.iter().all(|route| !route.session_required) {
+        return None;
+    }
+
+    let count = registrations.len();
+    let required = registrations.iter().map(|route| route.session_required);
+    let items = quote! {
+        static __ROUTE_SESSIONS: [bool; #count] = [#(#required),*];
+    };
+
+    // A session that was never written to, or was purged, has no entries
+    let check = quote! {
+        if route.is_some_and(|route| __ROUTE_SESSIONS[route]) {
+            use ::actix_session::SessionExt;
+            if req.get_session().entries().is_empty() {
+                return Ok(req.into_response(#actix::HttpResponse::Unauthorized().finish()));
+            }
+        }
+    };
+
+    Some((items, check))
+}
+
+// Scopes of the routes requiring a bearer token, and the check answering 401
+// unless the app's verifier accepts the token, or 403 when it lacks a scope
+pub(crate) fn jwt_scopes(
+    actix: &syn::Path,
+    registrations: &[RouteInfo],
+) -> Option<(TokenStream, TokenStream)> {
+    if registrations
+        .iter()
+        .all(|route| route.jwt_scopes.is_empty())
+    {
+        return None;
+    }
+
+    let count = registrations.len();
+    let scopes = registrations.iter().map(|route| {
+        let scopes = &route.jwt_scopes;
+        quote!(&[#(#scopes),*])
+    });
+    let items = quote! {
+        static __ROUTE_JWT_SCOPES: [&[&str]; #count] = [#(#scopes),*];
+    };
+
+    let check = quote! {
+        if let Some(required) = route
+            .map(|route| __ROUTE_JWT_SCOPES[route])
+            .filter(|required| !required.is_empty())
+        {
+            let granted = req
+                .headers()
+                .get("Authorization")
+                .and_then(|header| header.to_str().ok())
+                .and_then(|header| header.strip_prefix("Bearer "))
+                .zip(req.app_data::<#actix::web::Data<dyn ::register_actix_routes_runtime::JwtVerifier>>())
+                .and_then(|(token, verifier)| verifier.scopes(token.trim()));
+            let Some(granted) = granted else {
+                return Ok(req.into_response(#actix::HttpResponse::Unauthorized().finish()));
+            };
+            if !required.iter().all(|scope| granted.iter().any(|granted| granted == scope)) {
+                return Ok(req.into_response(#actix::HttpResponse::Forbidden().finish()));
+            }
+        }
+    };
+
+    Some((items, check))
+}
+
+// Media types of the routes restricting their request body, and the check
+// answering 415 to other Content-Types (parameters such as `charset` are ignored)
+pub(crate) fn content_types(
+    actix: &syn::Path,
+    registrations: &[RouteInfo],
+) -> Option<(TokenStream, TokenStream)> {
+    if registrations.iter().all(|route| route.consumes.is_none()) {
+        return None;
+    }
+
+    let count = registrations.len();
+    let media_types = registrations
+        .iter()
+        .map(|route| match route.consumes.as_deref() {
+            Some(media_type) => quote!(Some(#media_type)),
+            None => quote!(None),
+        });
+    let items = quote! {
+        static __ROUTE_CONSUMES: [Option<&str>; #count] = [#(#media_types),*];
+    };
+
+    let check = quote! {
+        if let Some(media_type) = route.and_then(|route| __ROUTE_CONSUMES[route]) {
+            let matches = req
+                .headers()
+                .get(#actix::http::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.split(';').next())
+                .is_some_and(|value| value.trim().eq_ignore_ascii_case(media_type));
+            if !matches {
+                return Ok(req.into_response(
+                    #actix::HttpResponse::UnsupportedMediaType().finish(),
+                ));
+            }
+        }
+    };
+
+    Some((items, check))
+}
+
+// Headers of the routes declaring some, and the statement adding them to the
+// responses of the handler, replacing the ones it set
+pub(crate) fn response_headers(
+    actix: &syn::Path,
+    registrations: &[RouteInfo],
+) -> Option<(TokenStream, TokenStream)> {
+    if registrations.iter().all(|route| route.headers.is_empty()) {
+        return None;
+    }
+
+    // Names are validated by auto_register, and lowercase as `from_static` requires
+    let count = registrations.len();
+    let headers = registrations.iter().map(|route| {
+        let headers = route.headers.iter().map(|(name, value)| {
+            let name = name.to_ascii_lowercase();
+            quote!((#name, #value))
+        });
+        quote!(&[#(#headers),*])
+    });
+    let items = quote! {
+        static __ROUTE_HEADERS: [&[(&str, &str)]; #count] = [#(#headers),*];
+    };
+
+    let insert = quote! {
+        let result = result.map(|mut res| {
+            for (name, value) in route.map_or(&[][..], |route| __ROUTE_HEADERS[route]) {
+                res.headers_mut().insert(
+                    #actix::http::header::HeaderName::from_static(name),
+                    #actix::http::header::HeaderValue::from_static(value),
+                );
+            }
+            res
+        });
+    };
+
+    Some((items, insert))
+}
+
+// Routes tagged with an ETag, the statement reading the request's `If-None-Match`
+// header, and the one buffering a successful response to hash its body: a
+// matching tag is answered with 304 and no body, and both get the `ETag` header
+pub(crate) fn etags(
+    actix: &syn::Path,
+    registrations: &[RouteInfo],
+) -> Option<(TokenStream, TokenStream, TokenStream)> {
+    if registrations.iter().all(|route| !route.etag) {
+        return None;
+    }
+
+    let count = registrations.len();
+    let tagged = registrations.iter().map(|route| route.etag);
+    let items = quote! {
+        static __ROUTE_ETAGS: [bool; #count] = [#(#tagged),*];
+    };
+
+    let capture = quote! {
+        let if_none_match = route.filter(|route| __ROUTE_ETAGS[*route]).map(|_| {
+            req.headers()
+                .get(#actix::http::header::IF_NONE_MATCH)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        });
+    };
+
+    let tag = quote! {
+        let result = match (result, if_none_match) {
+            (Ok(res), Some(if_none_match)) if res.status() == #actix::http::StatusCode::OK => {
+                let (request, response) = res.into_parts();
+                let (head, body) = response.into_parts();
+                match #actix::body::to_bytes(body).await {
+                    Ok(bytes) => {
+                        // FNV-1a, which unlike `DefaultHasher` tags a body alike
+                        // across builds, processes and instances
+                        let hash = bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+                            (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+                        });
+                        let etag = format!("\"{:016x}\"", hash);
+                        let matches = if_none_match.is_some_and(|tags| {
+                            tags.split(',').any(|tag| {
+                                let tag = tag.trim();
+                                tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag
+                            })
+                        });
+                        // A 304 keeps the headers of the response it stands for
+                        let mut response = if matches {
+                            let mut response = head.set_body(#actix::body::BoxBody::new(()));
+                            *response.status_mut() = #actix::http::StatusCode::NOT_MODIFIED;
+                            response
+                        } else {
+                            head.set_body(#actix::body::BoxBody::new(bytes))
+                        };
+                        if let Ok(value) = #actix::http::header::HeaderValue::from_str(&etag) {
+                            response
+                                .headers_mut()
+                                .insert(#actix::http::header::ETAG, value);
+                        }
+                        Ok(#actix::dev::ServiceResponse::new(request, response))
+                    }
+                    Err(error) => Err(#actix::error::ErrorInternalServerError(error)),
+                }
+            }
+            (result, _) => result,
+        };
+    };
+
+    Some((items, capture, tag))
+}
+
+// Routes reporting to the audit sink, the statement capturing the request
+// before the handler runs, and the one recording it with the response status.
+// Requests are reported with the pattern of their route rather than their path,
+// so entries group by route and don't copy identifiers from the path.
+pub(crate) fn audits(
+    actix: &syn::Path,
+    registrations: &[RouteInfo],
+) -> Option<(TokenStream, TokenStream, TokenStream)> {
+    if registrations.iter().all(|route| !route.audit) {
+        return None;
+    }
+
+    let count = registrations.len();
+    let audited = registrations.iter().map(|route| route.audit);
+    let items = quote! {
+        static __ROUTE_AUDITS: [bool; #count] = [#(#audited),*];
+    };
+
+    let sink = quote!(::register_actix_routes_runtime::AuditSink<#actix::HttpRequest>);
+    let start = quote! {
+        let audit = route
+            .filter(|route| __ROUTE_AUDITS[*route])
+            .and_then(|_| req.app_data::<#actix::web::Data<dyn #sink>>().cloned())
+            .map(|sink| {
+                let caller = sink.caller(req.request());
+                let pattern = req.match_pattern().unwrap_or_else(|| req.path().to_string());
+                (sink, req.method().to_string(), pattern, caller)
+            });
+    };
+
+    let record = quote! {
+        if let Some((sink, method, path, caller)) = audit {
+            let status = match &result {
+                Ok(res) => res.status(),
+                Err(error) => error.as_response_error().status_code(),
+            };
+            sink.record(&method, &path, caller.as_deref(), status.as_u16());
+        }
+    };
+
+    Some((items, start, record))
+}
+
+// Routes requiring an idempotency key, the statement claiming the key in the
+// app's store (answering 400 without a key or 409 for a retry), and the one
+// releasing it when the request failed
+pub(crate) fn idempotency_keys(
+    actix: &syn::Path,
+    registrations: &[RouteInfo],
+) -> Option<(TokenStream, TokenStream, TokenStream)> {
+    if registrations.iter().all(|route| !route.idempotent) {
+        return None;
+    }
+
+    let count = registrations.len();
+    let idempotent = registrations.iter().map(|route| route.idempotent);
+    let items = quote! {
+        static __ROUTE_IDEMPOTENT: [bool; #count] = [#(#idempotent),*];
+    };
+
+    // Without a store, retries can't be detected, so the requests are refused
+    // rather than reaching the handler again
+    let claim = quote! {
+        let idempotency_claim = match route.filter(|route| __ROUTE_IDEMPOTENT[*route]) {
+            Some(route) => {
+                let Some(key) = req
+                    .headers()
+                    .get("Idempotency-Key")
+                    .and_then(|key| key.to_str().ok())
+                    .map(str::to_string)
+                else {
+                    return Ok(req.into_response(#actix::HttpResponse::BadRequest().finish()));
+                };
+                let Some(store) = req.app_data::<#actix::web::Data<dyn ::register_actix_routes_runtime::IdempotencyStore>>().cloned() else {
+                    return Ok(req.into_response(#actix::HttpResponse::InternalServerError().finish()));
+                };
+                if !store.claim(__ROUTE_HANDLERS[route], &key) {
+                    return Ok(req.into_response(#actix::HttpResponse::Conflict().finish()));
+                }
+                Some((store, route, key))
+            }
+            None => None,
+        };
+    };
+
+    let release = quote! {
+        if let Some((store, route, key)) = idempotency_claim {
+            let failed = result
+                .as_ref()
+                .map_or(true, |res| res.status().is_server_error());
+            if failed {
+                store.release(__ROUTE_HANDLERS[route], &key);
+            }
+        }
+    };
+
+    Some((items, claim, release))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::route;
+
+    #[test]
+    fn feature_flags_are_read_from_the_runtime_provider() {
+        let actix: syn::Path = syn::parse_quote!(actix_web);
+        let mut flagged = route("/events", "/search", "search");
+        flagged.flag = Some("new-search".to_string());
+        let routes = [route("/events", "/list", "list"), flagged];
+
+        let (items, check) = feature_flags(&actix, &routes).unwrap();
+        assert_eq!(
+            items.to_string(),
+            quote!(
+                static __ROUTE_FLAGS: [Option<&str>; 2usize] = [None, Some("new-search")];
+            )
+            .to_string()
+        );
+        assert!(check
+            .to_string()
+            .contains("dyn :: register_actix_routes_runtime :: FeatureFlagProvider"));
+        assert!(feature_flags(&actix, &routes[..1]).is_none());
+    }
+
+    #[test]
+    fn api_keys_are_checked_by_the_runtime_store() {
+        let actix: syn::Path = syn::parse_quote!(actix_web);
+        let mut protected = route("/reports", "/export", "export");
+        protected.api_key = Some("x-api-key".to_string());
+
+        let (items, check) = api_keys(&actix, &[protected]).unwrap();
+        assert_eq!(
+            items.to_string(),
+            quote!(
+                static __ROUTE_API_KEYS: [Option<&str>; 1usize] = [Some("x-api-key")];
+            )
+            .to_string()
+        );
+        assert!(check
+            .to_string()
+            .contains("dyn :: register_actix_routes_runtime :: ApiKeyStore"));
+        assert!(api_keys(&actix, &[route("/reports", "/list", "list")]).is_none());
+    }
+
+    #[test]
+    fn jwt_scopes_are_granted_by_the_runtime_verifier() {
+        let actix: syn::Path = syn::parse_quote!(actix_web);
+        let mut protected = route("/events", "/search", "search");
+        protected.jwt_scopes = vec!["events:read".to_string(), "events:list".to_string()];
+        let routes = [protected, route("/events", "/list", "list")];
+
+        let (items, check) = jwt_scopes(&actix, &routes).unwrap();
+        assert_eq!(
+            items.to_string(),
+            quote!(
+                static __ROUTE_JWT_SCOPES: [&[&str]; 2usize] =
+                    [&["events:read", "events:list"], &[]];
+            )
+            .to_string()
+        );
+        assert!(check
+            .to_string()
+            .contains("dyn :: register_actix_routes_runtime :: JwtVerifier"));
+        assert!(jwt_scopes(&actix, &routes[1..]).is_none());
+    }
+
+    #[test]
+    fn audits_report_to_the_runtime_sink_of_the_actix_request() {
+        let actix: syn::Path = syn::parse_quote!(::my_framework::actix_web);
+        let mut audited = route("/accounts", "/{id}", "close_account");
+        audited.audit = true;
+
+        let (items, start, _) = audits(&actix, &[audited]).unwrap();
+        assert_eq!(
+            items.to_string(),
+            quote!(
+                static __ROUTE_AUDITS: [bool; 1usize] = [true];
+            )
+            .to_string()
+        );
+        assert!(start.to_string().contains(
+            "dyn :: register_actix_routes_runtime :: AuditSink < :: my_framework :: actix_web :: HttpRequest >"
+        ));
+        assert!(audits(&actix, &[route("/accounts", "/", "list")]).is_none());
+    }
+
+    #[test]
+    fn idempotency_keys_are_claimed_in_the_runtime_store() {
+        let actix: syn::Path = syn::parse_quote!(actix_web);
+        let mut idempotent = route("/payments", "/charge", "charge");
+        idempotent.verb = crate::intern("POST");
+        idempotent.idempotent = true;
+
+        let (items, claim, release) = idempotency_keys(&actix, &[idempotent]).unwrap();
+        assert_eq!(
+            items.to_string(),
+            quote!(
+                static __ROUTE_IDEMPOTENT: [bool; 1usize] = [true];
+            )
+            .to_string()
+        );
+        assert!(claim
+            .to_string()
+            .contains("dyn :: register_actix_routes_runtime :: IdempotencyStore"));
+        assert!(release.to_string().contains("store . release"));
+        assert!(idempotency_keys(&actix, &[route("/payments", "/", "list")]).is_none());
+    }
+}
@@ -0,0 +1,646 @@
+use crate::args::{OpenApiArgs, OpenApiVersion};
+use crate::document::Value;
+use crate::{http_method, Registry, RouteInfo};
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::collections::BTreeSet;
+
+// Convert an Actix path template into an OpenAPI one, returning its parameters
+// (e.g., "/events/{id:\\d+}" -> "/events/{id}" with ["id"], "/files/{tail}*" -> "/files/{tail}")
+pub(crate) fn openapi_path(path: &str) -> (String, Vec<String>) {
+    let mut converted = String::with_capacity(path.len());
+    let mut parameters = Vec::new();
+    let mut chars = path.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '{' {
+            converted.push(c);
+            continue;
+        }
+
+        // Read up to the matching brace, a custom regex may contain braces itself
+        let mut depth = 1;
+        let mut segment = String::new();
+        for c in chars.by_ref() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                break;
+            }
+            segment.push(c);
+        }
+        if chars.peek() == Some(&'*') {
+            chars.next();
+        }
+
+        let name = segment.split(':').next().unwrap_or_default().to_string();
+        converted.push_str(&format!("{{{}}}", name));
+        parameters.push(name);
+    }
+
+    (converted, parameters)
+}
+
+// Describe a status code with its standard reason phrase
+fn status_description(code: u16) -> &'static str {
+    match code {
+        101 => "Switching Protocols",
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        204 => "No Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        406 => "Not Acceptable",
+        409 => "Conflict",
+        410 => "Gone",
+        412 => "Precondition Failed",
+        413 => "Payload Too Large",
+        415 => "Unsupported Media Type",
+        422 => "Unprocessable Entity",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "Error",
+    }
+}
+
+// Schema of a body or query type: the real one with the `schemars` feature,
+// converted by `openapi_schema` (see `schema_support`), otherwise an object
+// titled after the Rust type
+fn type_schema(route: &RouteInfo, part: &str, ty: &str) -> Value {
+    if cfg!(feature = "schemars") {
+        let schema_fn = crate::schema_fn_path(route, part);
+        return Value::RuntimeJson(quote!(openapi_schema(#schema_fn(), &schemas)));
+    }
+
+    Value::object(vec![
+        ("type", Value::str("object")),
+        ("title", Value::str(ty)),
+    ])
+}
+
+// Embed an example as JSON when it looks like JSON, as a plain string otherwise
+pub(crate) fn example_value(example: &str) -> Value {
+    let trimmed = example.trim_start();
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        Value::RawJson(example.to_string())
+    } else {
+        Value::str(example)
+    }
+}
+
+// Name of the security scheme of an API key header, restricted to the
+// characters OpenAPI allows in component names
+fn api_key_scheme(header: &str) -> String {
+    header
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+// Name of the security scheme of bearer tokens
+const BEARER_SCHEME: &str = "bearerAuth";
+
+// Name of the security scheme of actix-session cookies, and the name of the
+// cookie of actix-session's `SessionMiddleware` by default
+const SESSION_SCHEME: &str = "sessionCookie";
+const SESSION_COOKIE: &str = "id";
+
+fn operation(route: &RouteInfo, operation_id: &str, parameters: &[String]) -> Value {
+    let mut operation = vec![("operationId", Value::str(operation_id))];
+
+    let mut doc_lines = route.docs.lines().filter(|line| !line.is_empty());
+    if let Some(summary) = doc_lines.next() {
+        operation.push(("summary", Value::str(summary)));
+        if route.docs.lines().count() > 1 {
+            operation.push(("description", Value::str(&route.docs)));
+        }
+    }
+
+    let mut parameter_values: Vec<Value> = parameters
+        .iter()
+        .map(|name| {
+            Value::object(vec![
+                ("name", Value::str(name)),
+                ("in", Value::str("path")),
+                ("required", Value::Bool(true)),
+                (
+                    "schema",
+                    Value::object(vec![("type", Value::str("string"))]),
+                ),
+            ])
+        })
+        .collect();
+    if route.idempotent {
+        parameter_values.push(Value::object(vec![
+            ("name", Value::str("Idempotency-Key")),
+            ("in", Value::str("header")),
+            ("required", Value::Bool(true)),
+            (
+                "schema",
+                Value::object(vec![("type", Value::str("string"))]),
+            ),
+        ]));
+    }
+    // An exploded object parameter documents every field of `Query<T>` with the
+    // `schemars` feature; without it, its schema only names `T`
+    if let Some(query_type) = &route.query_type {
+        parameter_values.push(Value::object(vec![
+            ("name", Value::str("query")),
+            ("in", Value::str("query")),
+            ("style", Value::str("form")),
+            ("explode", Value::Bool(true)),
+            ("schema", type_schema(route, "query", query_type)),
+        ]));
+    }
+    if !parameter_values.is_empty() {
+        operation.push(("parameters", Value::Array(parameter_values)));
+    }
+
+    if route.multipart {
+        let mut request_body = vec![
+            ("required", Value::Bool(true)),
+            (
+                "content",
+                Value::object(vec![(
+                    "multipart/form-data",
+                    Value::object(vec![(
+                        "schema",
+                        Value::object(vec![("type", Value::str("object"))]),
+                    )]),
+                )]),
+            ),
+        ];
+        if let Some(limit) = &route.multipart_limit {
+            request_body.push((
+                "description",
+                Value::str(&format!("Multipart upload of up to {}", limit)),
+            ));
+        }
+        operation.push(("requestBody", Value::object(request_body)));
+    } else if let Some(media_type) = crate::request_media_type(route) {
+        // The declared media type replaces JSON, and a body without a `Json<T>`
+        // extractor has no schema
+        let mut media = match &route.body_type {
+            Some(body_type) => vec![("schema", type_schema(route, "body", body_type))],
+            None => Vec::new(),
+        };
+        match &route.example_request {
+            Some(example) if media_type == "application/json" => {
+                media.push(("example", example_value(example)));
+            }
+            Some(example) => media.push(("example", Value::str(example))),
+            None => {}
+        }
+        operation.push((
+            "requestBody",
+            Value::object(vec![
+                ("required", Value::Bool(true)),
+                (
+                    "content",
+                    Value::object(vec![(media_type, Value::object(media))]),
+                ),
+            ]),
+        ));
+    }
+
+    // Admin-only routes are flagged for gateways and documentation tools to filter
+    if route.admin {
+        operation.push(("x-admin", Value::Bool(true)));
+    }
+
+    // WebSocket upgrades answer by switching protocols rather than with a body
+    let success = if &*route.verb == "WS" {
+        operation.push(("x-websocket", Value::Bool(true)));
+        (
+            "101".to_string(),
+            Value::object(vec![("description", Value::str(status_description(101)))]),
+        )
+    } else {
+        let mut success = vec![("description", Value::str("Successful response"))];
+        if route.sse {
+            // Event streams are documented as text, with the example if any
+            let mut media = vec![(
+                "schema",
+                Value::object(vec![("type", Value::str("string"))]),
+            )];
+            if let Some(example) = &route.example_response {
+                media.push(("example", Value::str(example)));
+            }
+            success.push((
+                "content",
+                Value::object(vec![("text/event-stream", Value::object(media))]),
+            ));
+        } else if route.example_response.is_some() || route.produces.is_some() {
+            // Negotiated handlers document their media type, with the example if any
+            let media_type = route.produces.as_deref().unwrap_or("application/json");
+            let media = match &route.example_response {
+                Some(example) if media_type == "application/json" => {
+                    vec![("example", example_value(example))]
+                }
+                Some(example) => vec![("example", Value::str(example))],
+                None => Vec::new(),
+            };
+            success.push((
+                "content",
+                Value::object(vec![(media_type, Value::object(media))]),
+            ));
+        }
+        // Headers added by the route to its responses
+        if !route.headers.is_empty() {
+            let headers = route.headers.iter().map(|(name, value)| {
+                let schema = vec![
+                    ("type", Value::str("string")),
+                    ("example", Value::str(value)),
+                ];
+                (
+                    name.clone(),
+                    Value::object(vec![("schema", Value::object(schema))]),
+                )
+            });
+            success.push(("headers", Value::Object(headers.collect())));
+        }
+        ("200".to_string(), Value::object(success))
+    };
+    let mut responses = vec![success];
+    // Protected routes require every scheme the middleware checks for them
+    // together, and answer 401 without valid credentials or 403 when a token
+    // lacks a scope
+    let mut errors = route.errors.clone();
+    let mut security = Vec::new();
+    if let Some(header) = &route.api_key {
+        security.push((api_key_scheme(header), Value::Array(Vec::new())));
+        errors.push(401);
+    }
+    if route.session_required {
+        security.push((SESSION_SCHEME.to_string(), Value::Array(Vec::new())));
+        errors.push(401);
+    }
+    if !route.jwt_scopes.is_empty() {
+        let scopes = route.jwt_scopes.iter().map(|scope| Value::str(scope));
+        security.push((BEARER_SCHEME.to_string(), Value::Array(scopes.collect())));
+        errors.extend([401, 403]);
+    }
+    // Roles aren't enforced, so they are only described, as an extension
+    if !route.roles.is_empty() {
+        let roles = route.roles.iter().map(|role| Value::str(role));
+        operation.push(("x-roles", Value::Array(roles.collect())));
+    }
+    // Idempotent routes answer 400 without a key and 409 for a retry
+    if route.idempotent {
+        errors.extend([400, 409]);
+    }
+    if route.consumes.is_some() {
+        errors.push(415);
+    }
+    // Tagged routes answer 304 when the client's copy is current
+    if route.etag {
+        errors.push(304);
+    }
+    if !security.is_empty() {
+        operation.push(("security", Value::Array(vec![Value::Object(security)])));
+    }
+    errors.sort_unstable();
+    errors.dedup();
+    for code in &errors {
+        responses.push((
+            code.to_string(),
+            Value::object(vec![("description", Value::str(status_description(*code)))]),
+        ));
+    }
+    operation.push(("responses", Value::Object(responses)));
+
+    Value::object(operation)
+}
+
+// The entry of an object value, if any
+fn entry_mut<'a>(value: &'a mut Value, key: &str) -> Option<&'a mut Value> {
+    match value {
+        Value::Object(entries) => entries
+            .iter_mut()
+            .find(|(existing, _)| existing == key)
+            .map(|(_, value)| value),
+        _ => None,
+    }
+}
+
+// Add the media type of a negotiated handler's success response to the
+// operation of the first handler of its path
+fn merge_success_content(existing: &mut Value, mut operation: Value) {
+    let content = entry_mut(&mut operation, "responses")
+        .and_then(|responses| entry_mut(responses, "200"))
+        .and_then(|success| entry_mut(success, "content"))
+        .map(|content| std::mem::replace(content, Value::Object(Vec::new())));
+    let existing_content = entry_mut(existing, "responses")
+        .and_then(|responses| entry_mut(responses, "200"))
+        .and_then(|success| entry_mut(success, "content"));
+    if let (Some(Value::Object(media)), Some(Value::Object(existing_media))) =
+        (content, existing_content)
+    {
+        existing_media.extend(media);
+    }
+}
+
+// Derive the operationId of a route from its handler name, qualified with its
+// module when another route took the name (e.g., "search", then "v2_search"),
+// and numbered as a last resort. The declared `operation_id`s are reserved first.
+fn derive_operation_id(route: &RouteInfo, used: &mut BTreeSet<String>) -> String {
+    let module = route
+        .module_path
+        .split("::")
+        .filter(|segment| *segment != "crate")
+        .collect::<Vec<_>>()
+        .join("_");
+    let mut candidates = vec![route.handler_name.to_string()];
+    if !module.is_empty() {
+        candidates.push(format!("{}_{}", module, route.handler_name));
+    }
+    let qualified = candidates.last().cloned().unwrap_or_default();
+    let operation_id = candidates
+        .into_iter()
+        .chain((2..).map(|index| format!("{}_{}", qualified, index)))
+        .find(|candidate| !used.contains(candidate))
+        .unwrap_or_default();
+    used.insert(operation_id.clone());
+    operation_id
+}
+
+// Build the OpenAPI document of every registered route
+pub(crate) fn routes_to_openapi(map: &Registry, args: &OpenApiArgs) -> Value {
+    // Keep the operationIds stable for client generators: declared ones as is,
+    // derived ones unique among them
+    let mut used_operation_ids = map
+        .values()
+        .flatten()
+        .filter_map(|route| route.operation_id.clone())
+        .collect::<BTreeSet<_>>();
+
+    // Group operations by path template, keeping registration order
+    let mut paths: Vec<(String, Vec<(String, Value)>)> = Vec::new();
+    for (scope, routes) in map.iter() {
+        for route in routes {
+            let method = http_method(&route.verb).to_lowercase();
+            if ![
+                "get", "put", "post", "delete", "options", "head", "patch", "trace",
+            ]
+            .contains(&method.as_str())
+            {
+                continue;
+            }
+
+            let (path, parameters) = openapi_path(&crate::served_pattern(scope, route));
+            let path = if path.is_empty() {
+                "/".to_string()
+            } else {
+                path
+            };
+            let operation_id = match &route.operation_id {
+                Some(operation_id) => operation_id.clone(),
+                None => derive_operation_id(route, &mut used_operation_ids),
+            };
+            let operation = operation(route, &operation_id, &parameters);
+            let Some((_, operations)) = paths.iter_mut().find(|(existing, _)| *existing == path)
+            else {
+                paths.push((path, vec![(method, operation)]));
+                continue;
+            };
+            // The negotiated handlers of a path are one operation answering each
+            // of their media types
+            let negotiated = operations
+                .iter_mut()
+                .find(|(existing, _)| *existing == method && route.produces.is_some());
+            match negotiated {
+                Some((_, existing)) => merge_success_content(existing, operation),
+                None => operations.push((method, operation)),
+            }
+        }
+    }
+
+    // One API key scheme per header used by the routes, and the bearer scheme
+    let api_key_headers = map
+        .values()
+        .flatten()
+        .filter_map(|route| route.api_key.as_deref())
+        .collect::<BTreeSet<_>>();
+
+    let openapi = match args.version {
+        OpenApiVersion::V3_0 => "3.0.3",
+        OpenApiVersion::V3_1 => "3.1.0",
+    };
+
+    let mut document = vec![
+        ("openapi", Value::str(openapi)),
+        (
+            "info",
+            Value::object(vec![
+                ("title", Value::str(&args.title)),
+                ("version", Value::str(&args.api_version)),
+            ]),
+        ),
+        (
+            "paths",
+            Value::Object(
+                paths
+                    .into_iter()
+                    .map(|(path, operations)| (path, Value::Object(operations)))
+                    .collect(),
+            ),
+        ),
+    ];
+    let mut schemes = api_key_headers
+        .into_iter()
+        .map(|header| {
+            (
+                api_key_scheme(header),
+                Value::object(vec![
+                    ("type", Value::str("apiKey")),
+                    ("in", Value::str("header")),
+                    ("name", Value::str(header)),
+                ]),
+            )
+        })
+        .collect::<Vec<_>>();
+    if map.values().flatten().any(|route| route.session_required) {
+        schemes.push((
+            SESSION_SCHEME.to_string(),
+            Value::object(vec![
+                ("type", Value::str("apiKey")),
+                ("in", Value::str("cookie")),
+                ("name", Value::str(SESSION_COOKIE)),
+            ]),
+        ));
+    }
+    // Bearer tokens carry the scopes of `jwt_scopes`
+    if map
+        .values()
+        .flatten()
+        .any(|route| !route.jwt_scopes.is_empty())
+    {
+        schemes.push((
+            BEARER_SCHEME.to_string(),
+            Value::object(vec![
+                ("type", Value::str("http")),
+                ("scheme", Value::str("bearer")),
+                ("bearerFormat", Value::str("JWT")),
+            ]),
+        ));
+    }
+    // The definitions of the schemas, gathered while writing the operations
+    let mut components = Vec::new();
+    if cfg!(feature = "schemars")
+        && map
+            .values()
+            .flatten()
+            .any(|route| route.body_type.is_some() || route.query_type.is_some())
+    {
+        components.push((
+            "schemas",
+            Value::RuntimeJson(quote! {
+                ::serde_json::to_string(&schemas.take()).expect("Failed to serialize the schemas")
+            }),
+        ));
+    }
+    if !schemes.is_empty() {
+        components.push(("securitySchemes", Value::Object(schemes)));
+    }
+    if !components.is_empty() {
+        document.push(("components", Value::object(components)));
+    }
+
+    Value::object(document)
+}
+
+// The items `openapi` converts the JSON Schemas of `schemars` with, which are
+// only known at runtime: the definitions of the types they reference are moved
+// to `components/schemas` (the `schemas` map), the references rewritten to point
+// there, `$schema` dropped, and for OpenAPI 3.0, which has no `null` type,
+// nullable types marked with `nullable` instead. Empty without `schemars`.
+pub(crate) fn schema_support(version: &OpenApiVersion) -> TokenStream {
+    if !cfg!(feature = "schemars") {
+        return TokenStream::new();
+    }
+    // OpenAPI 3.1 schemas are JSON Schemas, where `null` is a type like any other
+    let (nullable, nullable_fn) = match version {
+        OpenApiVersion::V3_0 => (
+            Some(quote!(nullable_schema(object);)),
+            Some(nullable_schema()),
+        ),
+        OpenApiVersion::V3_1 => (None, None),
+    };
+
+    quote! {
+        let schemas = ::std::cell::RefCell::new(::serde_json::Map::new());
+
+        fn openapi_schema(
+            json: String,
+            schemas: &::std::cell::RefCell<::serde_json::Map<String, ::serde_json::Value>>,
+        ) -> String {
+            let mut schema: ::serde_json::Value =
+                ::serde_json::from_str(&json).expect("Failed to parse the schema");
+            if let Some(object) = schema.as_object_mut() {
+                object.remove("$schema");
+                for key in ["definitions", "$defs"] {
+                    if let Some(::serde_json::Value::Object(definitions)) = object.remove(key) {
+                        for (name, mut definition) in definitions {
+                            convert_schema(&mut definition);
+                            schemas.borrow_mut().insert(name, definition);
+                        }
+                    }
+                }
+            }
+            convert_schema(&mut schema);
+            ::serde_json::to_string(&schema).expect("Failed to serialize the schema")
+        }
+
+        fn convert_schema(value: &mut ::serde_json::Value) {
+            use ::serde_json::Value;
+            match value {
+                Value::Object(object) => {
+                    if let Some(Value::String(reference)) = object.get_mut("$ref") {
+                        for prefix in ["#/definitions/", "#/$defs/"] {
+                            if let Some(name) = reference.strip_prefix(prefix) {
+                                *reference = format!("#/components/schemas/{}", name);
+                                break;
+                            }
+                        }
+                    }
+                    #nullable
+                    object.values_mut().for_each(convert_schema);
+                }
+                Value::Array(items) => items.iter_mut().for_each(convert_schema),
+                _ => {}
+            }
+        }
+
+        #nullable_fn
+    }
+}
+
+// The function marking nullable schemas the way of OpenAPI 3.0:
+// `"type": ["string", "null"]` becomes `"type": "string", "nullable": true`, and
+// `"anyOf": [{"$ref": ...}, {"type": "null"}]` (an `Option` of a referenced type)
+// becomes `"allOf": [{"$ref": ...}], "nullable": true`
+fn nullable_schema() -> TokenStream {
+    quote! {
+        fn nullable_schema(object: &mut ::serde_json::Map<String, ::serde_json::Value>) {
+            use ::serde_json::Value;
+            let is_null = |value: &Value| value == "null" || *value == ::serde_json::json!({ "type": "null" });
+            if let Some(Value::Array(types)) = object.get("type") {
+                let mut types = types.clone();
+                if types.iter().any(is_null) {
+                    types.retain(|value| !is_null(value));
+                    object.insert("nullable".to_string(), Value::Bool(true));
+                }
+                match types.len() {
+                    0 => {
+                        object.remove("type");
+                    }
+                    1 => {
+                        object.insert("type".to_string(), types.remove(0));
+                    }
+                    _ => {
+                        object.remove("type");
+                        let any_of = types.into_iter().map(|ty| ::serde_json::json!({ "type": ty }));
+                        object.insert("anyOf".to_string(), Value::Array(any_of.collect()));
+                    }
+                }
+            }
+            for key in ["anyOf", "oneOf"] {
+                let Some(Value::Array(variants)) = object.get_mut(key) else {
+                    continue;
+                };
+                if !variants.iter().any(is_null) {
+                    continue;
+                }
+                variants.retain(|value| !is_null(value));
+                let variants = ::std::mem::take(variants);
+                object.remove(key);
+                object.insert("nullable".to_string(), Value::Bool(true));
+                if variants.len() == 1 {
+                    object.insert("allOf".to_string(), Value::Array(variants));
+                } else {
+                    object.insert(key.to_string(), Value::Array(variants));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::args::PactArgs;
+use crate::document::Value;
+use crate::openapi::example_value;
+use crate::{example_path, http_method, request_media_type, Registry, RouteInfo, RouteKind};
+
+// The request of a route's interactions, matching the dynamic segments of its path
+// with the expression Actix matches them with
+fn request(route: &RouteInfo, path: &str) -> Value {
+    let mut request = vec![
+        ("method", Value::str(http_method(&route.verb))),
+        ("path", Value::str(&example_path(path))),
+    ];
+
+    let mut headers = Vec::new();
+    if let Some(media_type) = request_media_type(route) {
+        headers.push(("Content-Type", Value::str(media_type)));
+    }
+    if let Some(media_type) = &route.produces {
+        headers.push(("Accept", Value::str(media_type)));
+    }
+    if route.idempotent {
+        headers.push(("Idempotency-Key", Value::str("example-key")));
+    }
+    if !headers.is_empty() {
+        request.push(("headers", Value::object(headers)));
+    }
+    if let Some(example) = &route.example_request {
+        request.push(("body", example_value(example)));
+    }
+
+    if path.contains('{') {
+        request.push((
+            "matchingRules",
+            Value::object(vec![(
+                "$.path",
+                Value::object(vec![("regex", Value::str(&crate::pattern_regex(path)))]),
+            )]),
+        ));
+    }
+    Value::object(request)
+}
+
+// The interactions of a route: its success, then each of its error status codes,
+// every one under its own provider state for the provider to set up
+fn interactions(scope: &str, route: &RouteInfo) -> Vec<Value> {
+    let path = crate::served_pattern(scope, route);
+    let method = http_method(&route.verb);
+    let mut interactions = Vec::new();
+
+    let status = if route.verb.as_ref() == "WS" {
+        101
+    } else {
+        200
+    };
+    let mut response = vec![("status", Value::RawJson(status.to_string()))];
+    if let Some(media_type) = &route.produces {
+        response.push((
+            "headers",
+            Value::object(vec![("Content-Type", Value::str(media_type))]),
+        ));
+    }
+    if let Some(example) = &route.example_response {
+        response.push(("body", example_value(example)));
+    }
+    interactions.push(Value::object(vec![
+        (
+            "description",
+            Value::str(&format!("{} {} ({})", method, path, route.handler_name)),
+        ),
+        (
+            "providerState",
+            Value::str(&format!("{} can be called", route.handler_name)),
+        ),
+        ("request", request(route, &path)),
+        ("response", Value::object(response)),
+    ]));
+
+    for code in &route.errors {
+        interactions.push(Value::object(vec![
+            (
+                "description",
+                Value::str(&format!(
+                    "{} {} ({}) answering {}",
+                    method, path, route.handler_name, code
+                )),
+            ),
+            (
+                "providerState",
+                Value::str(&format!("{} answers {}", route.handler_name, code)),
+            ),
+            ("request", request(route, &path)),
+            (
+                "response",
+                Value::object(vec![("status", Value::RawJson(code.to_string()))]),
+            ),
+        ]));
+    }
+    interactions
+}
+
+// Build a Pact (specification 2.0.0) contract stub with the interactions of every
+// route, for consumer teams to refine
+pub(crate) fn routes_to_pact(map: &Registry, args: &PactArgs) -> Value {
+    let interactions = map
+        .iter()
+        .flat_map(|(scope, routes)| {
+            routes
+                .iter()
+                .filter(|route| route.kind != RouteKind::Static)
+                .flat_map(move |route| interactions(scope, route))
+        })
+        .collect();
+
+    Value::object(vec![
+        (
+            "consumer",
+            Value::object(vec![("name", Value::str(&args.consumer))]),
+        ),
+        (
+            "provider",
+            Value::object(vec![("name", Value::str(&args.provider))]),
+        ),
+        ("interactions", Value::Array(interactions)),
+        (
+            "metadata",
+            Value::object(vec![(
+                "pactSpecification",
+                Value::object(vec![("version", Value::str("2.0.0"))]),
+            )]),
+        ),
+    ])
+}
@@ -0,0 +1,112 @@
+use crate::{handler_ident, item_module_path};
+use std::fs;
+use std::path::{Path, PathBuf};
+use syn::ext::IdentExt;
+use syn::parse::ParseStream;
+use syn::{Item, LitStr};
+
+// A handler found in the sources by `generate_register_service!(scan = "...")`
+pub(crate) struct ScannedRoute {
+    pub(crate) handler_name: String, // The name of the handler function
+    pub(crate) module_path: String,  // The module of the handler (e.g., "crate::api")
+    pub(crate) verb: String,         // The verb of the route macro (e.g., "GET")
+    pub(crate) path: String,         // The path of the route macro (e.g., "/search")
+}
+
+// Find the handlers annotated with an Actix route macro in a source file, or in
+// every source file below a directory, relative to the package's manifest.
+// Files are read in path order and their handlers in source order.
+pub(crate) fn scan(relative: &str) -> Result<Vec<ScannedRoute>, String> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let root = Path::new(&manifest_dir).join(relative);
+
+    let mut files = Vec::new();
+    collect_files(&root, &mut files)
+        .map_err(|error| format!("Failed to scan `{}`: {}", relative, error))?;
+    files.sort();
+
+    let mut routes = Vec::new();
+    for file in files {
+        let source = fs::read_to_string(&file)
+            .map_err(|error| format!("Failed to read `{}`: {}", file.display(), error))?;
+        let parsed = syn::parse_file(&source)
+            .map_err(|error| format!("Failed to parse `{}`: {}", file.display(), error))?;
+        // The module follows the Cargo layout of the path below the manifest
+        // (e.g., "src/api/events.rs" -> "crate::api::events")
+        let relative_file = file.strip_prefix(&manifest_dir).unwrap_or(&file);
+        let module_path = item_module_path(&relative_file.to_string_lossy());
+        scan_items(&parsed.items, &module_path, &mut routes);
+    }
+    Ok(routes)
+}
+
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    if path.is_file() {
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+    for entry in fs::read_dir(path)? {
+        let path = entry?.path();
+        if path.is_dir() || path.extension().is_some_and(|extension| extension == "rs") {
+            collect_files(&path, files)?;
+        }
+    }
+    Ok(())
+}
+
+// Collect the handlers of a module, descending into its inline modules except
+// the test ones
+fn scan_items(items: &[Item], module_path: &str, routes: &mut Vec<ScannedRoute>) {
+    for item in items {
+        match item {
+            Item::Fn(item_fn) => {
+                if let Some((verb, path)) = route_macro(&item_fn.attrs) {
+                    routes.push(ScannedRoute {
+                        handler_name: item_fn.sig.ident.unraw().to_string(),
+                        module_path: module_path.to_string(),
+                        verb,
+                        path,
+                    });
+                }
+            }
+            Item::Mod(item_mod) => {
+                let is_test = item_mod.attrs.iter().any(|attr| {
+                    attr.path().is_ident("cfg")
+                        && attr
+                            .parse_args::<syn::Ident>()
+                            .is_ok_and(|ident| ident == "test")
+                });
+                if let (Some((_, items)), false) = (&item_mod.content, is_test) {
+                    let module_path = format!(
+                        "{}::{}",
+                        module_path,
+                        handler_ident(&item_mod.ident.unraw().to_string())
+                            .expect("Module names are identifiers")
+                    );
+                    scan_items(items, &module_path, routes);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// The verb and path of the Actix route macro of a function (e.g., `#[get("/search")]`)
+fn route_macro(attrs: &[syn::Attribute]) -> Option<(String, String)> {
+    attrs.iter().find_map(|attr| {
+        let segment = attr.path().segments.last()?;
+        let verb = segment.ident.to_string();
+        if !["get", "post", "put", "delete", "patch"].contains(&verb.as_str()) {
+            return None;
+        }
+        // The path comes first, possibly followed by options (e.g., `guard = "..."`)
+        let path = attr
+            .parse_args_with(|input: ParseStream| {
+                let path: LitStr = input.parse()?;
+                input.parse::<proc_macro2::TokenStream>()?;
+                Ok(path)
+            })
+            .ok()?;
+        Some((verb.to_uppercase(), path.value()))
+    })
+}